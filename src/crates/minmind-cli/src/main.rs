@@ -2,24 +2,50 @@
 //!
 //! Usage: mm <command> [options]
 
-use std::path::PathBuf;
-use std::process::Command;
+use std::collections::HashSet;
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode, Output, Stdio};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, Instant};
 
 use clap::{Parser, Subcommand, ValueEnum};
 use minmind_core::{
-    parse_plan_content, update_plan_markers, ActionStatus, Article, ArticleStatus, Note, NoteType,
-    Room, Status, SummaryConfig, UserAction, DEFAULT_SUMMARY_PROMPT,
+    audit_sources, parse_flexible_datetime, parse_plan_content_with, parse_time_expr,
+    parse_todo_list, render_room_markdown_with, resolve_short_id, summary_style_instruction,
+    update_plan_markers, ActionFilter, ActionStatus, Article, ArticleNoteOptions, ArticleStatus,
+    ContentDiff, ContentFormat, CoreError, DiffLine, EntitySource, Genius, Link, Note, NoteType,
+    NoteWithLinks, ParsedAction, PlanParseOptions, Priority, Provider, Room, RoomMarkdownOptions,
+    ShortIdError, Status, SummaryConfig, SummaryUsage, UserAction, DEFAULT_MAX_PROMPT_LEN,
+    DEFAULT_SUMMARY_PROMPT, DEFAULT_SUMMARY_STYLE, MIN_SHORT_ID_LEN,
 };
-use minmind_store::Store;
+use minmind_store::{default_database_path, is_blank_search_query, Progress, Store, StoreError};
+
+mod style;
+
+use style::{Color, ColorMode, Icon, Style};
 
 /// MinMind - Your Mind Palace for execution
 #[derive(Parser)]
 #[command(name = "mm")]
 #[command(author, version, about, long_about = None)]
+#[command(after_help = EXIT_CODES_HELP)]
 struct Cli {
-    /// Path to the MinMind database
-    #[arg(short, long, env = "MINMIND_DB", default_value = "~/.minmind/minmind.db")]
-    database: String,
+    /// Path to the MinMind database [default: platform data directory, or ~/.minmind]
+    #[arg(short, long, env = "MINMIND_DB")]
+    database: Option<String>,
+
+    /// Minimum number of characters accepted for short id prefixes
+    #[arg(long, env = "MINMIND_ID_PREFIX", default_value_t = MIN_SHORT_ID_LEN, global = true)]
+    id_prefix: usize,
+
+    /// Don't ask before deleting anything
+    #[arg(short, long, global = true)]
+    yes: bool,
+
+    /// When to use color, emoji and box drawing in output
+    #[arg(long, value_enum, default_value_t = ColorMode::Auto, global = true)]
+    color: ColorMode,
 
     #[command(subcommand)]
     command: Commands,
@@ -37,32 +63,94 @@ enum Commands {
         #[command(subcommand)]
         action: NoteCommands,
     },
+    /// Manage note tags
+    Tag {
+        #[command(subcommand)]
+        action: TagCommands,
+    },
+    /// Manage note templates
+    Template {
+        #[command(subcommand)]
+        action: TemplateCommands,
+    },
     /// Search notes
     Search {
         /// Search query
         query: String,
+        /// Only search notes in this room (ID or name)
+        #[arg(short, long)]
+        room: Option<String>,
+        /// Suggest near-miss titles when nothing matches exactly
+        #[arg(short, long)]
+        fuzzy: bool,
+        /// Show at most this many of the best matches
+        #[arg(short, long, default_value_t = DEFAULT_SEARCH_LIMIT)]
+        limit: usize,
     },
     /// Manage articles for reading and summarization
     Article {
         #[command(subcommand)]
         action: ArticleCommands,
+        /// Give up on the extractor or summarizer after this long (e.g. 90s, 5m)
+        #[arg(long, global = true, default_value = "120s", value_parser = parse_timeout)]
+        timeout: std::time::Duration,
     },
     /// Manage summary configuration
     Config {
         #[command(subcommand)]
         action: ConfigCommands,
     },
+    /// Manage geniuses (AI providers and models)
+    Genius {
+        #[command(subcommand)]
+        action: GeniusCommands,
+    },
     /// Manage user action todos from plans
     Todo {
         #[command(subcommand)]
         action: TodoCommands,
     },
+    /// Export the Mind Palace (or some rooms) as JSON
+    Export {
+        /// Only export this room (ID or name); repeat for several rooms
+        #[arg(short, long)]
+        room: Vec<String>,
+        /// Include rooms nested below the selected rooms
+        #[arg(long, requires = "room")]
+        recursive: bool,
+        /// Keep links from exported notes to notes outside the export
+        #[arg(long, requires = "room")]
+        include_external_links: bool,
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Import a JSON export, adding the rooms, notes, links and articles missing here
+    Import {
+        /// Export file to read ("-" for stdin)
+        file: PathBuf,
+    },
+    /// Inspect the database file
+    Db {
+        #[command(subcommand)]
+        action: DbCommands,
+    },
+    /// Show statistics about your Mind Palace
+    Stats {
+        /// Only count notes and links in this room (ID or name)
+        #[arg(short, long)]
+        room: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
 enum RoomCommands {
     /// List all rooms
-    List,
+    List {
+        /// Show note, article and todo counts for each room
+        #[arg(short, long)]
+        stats: bool,
+    },
     /// Create a new room
     Create {
         /// Name of the room
@@ -73,12 +161,38 @@ enum RoomCommands {
         /// Parent room ID (for nesting)
         #[arg(short, long)]
         parent: Option<String>,
+        /// Reuse a room with the same name under the same parent instead of
+        /// creating another, so scripts can run repeatedly
+        #[arg(long)]
+        exists_ok: bool,
     },
     /// Delete a room
     Delete {
         /// Room ID or name
         room: String,
     },
+    /// Merge one room into another: its notes, articles and sub-rooms move
+    /// over and it is deleted
+    Merge {
+        /// Room to keep (ID or name)
+        keep: String,
+        /// Room to merge into it and delete (ID or name)
+        absorb: String,
+    },
+    /// Print a room's notes as one markdown document, most recently updated first
+    Cat {
+        /// Room ID or name
+        room: String,
+        /// Only include notes of this type (idea, task, reference, log)
+        #[arg(short = 't', long = "type")]
+        note_type: Option<NoteType>,
+        /// Add each note's type, status and last update under its heading
+        #[arg(short, long)]
+        metadata: bool,
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -87,6 +201,47 @@ enum NoteCommands {
     List {
         /// Room ID or name
         room: String,
+        /// Also list notes in the room's sub-rooms, however deeply nested
+        #[arg(short, long)]
+        recursive: bool,
+        /// Show a one-line preview of each note's content
+        #[arg(short, long)]
+        preview: bool,
+        /// Only list notes created this way (manual, plan, article, import, api)
+        #[arg(short, long)]
+        source: Option<EntitySource>,
+        /// Only list notes of this type (idea, task, reference, log)
+        #[arg(short = 't', long = "type")]
+        note_type: Option<NoteType>,
+        /// Only list notes with this status (active, completed, archived)
+        #[arg(long)]
+        status: Option<Status>,
+        /// Only list notes updated at or after this time (e.g. 7d, yesterday, 2024-03-01)
+        #[arg(long, value_parser = parse_time)]
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        /// Only list notes updated before this time (a bare date means its midnight)
+        #[arg(long, value_parser = parse_time)]
+        until: Option<chrono::DateTime<chrono::Utc>>,
+        /// Only list notes with this tag
+        #[arg(long)]
+        tag: Option<String>,
+    },
+    /// Search notes, optionally only in one room and/or of one type
+    Search {
+        /// Search query
+        query: String,
+        /// Only search notes in this room (ID or name)
+        #[arg(short, long)]
+        room: Option<String>,
+        /// Only search notes of this type (idea, task, reference, log)
+        #[arg(short = 't', long = "type")]
+        note_type: Option<NoteType>,
+        /// Suggest near-miss titles when nothing matches exactly
+        #[arg(short, long)]
+        fuzzy: bool,
+        /// Show at most this many of the best matches
+        #[arg(short, long, default_value_t = DEFAULT_SEARCH_LIMIT)]
+        limit: usize,
     },
     /// Create a new note
     Create {
@@ -97,20 +252,164 @@ enum NoteCommands {
         /// Note type (idea, task, reference, log)
         #[arg(short = 't', long, default_value = "idea")]
         note_type: String,
-        /// Note content
+        /// Note content ("-" reads it from stdin)
         #[arg(short, long)]
         content: Option<String>,
+        /// Read note content from a file
+        #[arg(long, value_name = "PATH")]
+        content_file: Option<PathBuf>,
+        /// Template to start from when no content is given (defaults to the note type)
+        #[arg(long)]
+        template: Option<String>,
+        /// Content format (markdown, plain)
+        #[arg(short, long, default_value = "markdown")]
+        format: String,
     },
     /// Show a note
     Show {
-        /// Note ID
+        /// Note ID, short ID or title
         id: String,
+        /// Render markdown content (the default when writing to a terminal)
+        #[arg(long, conflicts_with = "raw")]
+        render: bool,
+        /// Print content verbatim, even on a terminal
+        #[arg(long)]
+        raw: bool,
+    },
+    /// Show the most recently edited note in a room
+    Latest {
+        /// Room ID or name
+        room: String,
+        /// Render markdown content (the default when writing to a terminal)
+        #[arg(long, conflicts_with = "raw")]
+        render: bool,
+        /// Print content verbatim, even on a terminal
+        #[arg(long)]
+        raw: bool,
     },
-    /// Delete a note
+    /// Move a note to the trash (or delete it outright with --permanent)
     Delete {
-        /// Note ID
+        /// Note ID, short ID or title
+        id: String,
+        /// Delete immediately instead of moving to the trash
+        #[arg(long)]
+        permanent: bool,
+        /// Don't ask for confirmation when the note has links
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// List trashed notes, or purge old ones with --purge
+    Trash {
+        /// Permanently delete notes trashed longer ago than --older-than
+        #[arg(long)]
+        purge: bool,
+        /// Retention period for --purge, e.g. 30d or 12h
+        #[arg(long, default_value = "30d", value_parser = parse_duration, requires = "purge")]
+        older_than: chrono::Duration,
+    },
+    /// Restore a note from the trash
+    Restore {
+        /// Note ID, short ID or title
+        id: String,
+    },
+    /// Copy a note (without its links), optionally into another room
+    Duplicate {
+        /// Note ID, short ID or title
+        id: String,
+        /// Target room ID or name (defaults to the note's room)
+        #[arg(short, long)]
+        room: Option<String>,
+    },
+    /// Attach a file (image, PDF, ...) to a note
+    Attach {
+        /// Note ID, short ID or title
+        id: String,
+        /// File to attach
+        file: PathBuf,
+    },
+    /// Move a note to another room and/or give it a new title
+    Move {
+        /// Note ID, short ID or title
+        id: String,
+        /// Target room ID or name
+        #[arg(short, long)]
+        room: Option<String>,
+        /// New title for the note
+        #[arg(short, long)]
+        title: Option<String>,
+        /// Rewrite [[Old Title]] wikilinks in other notes to the new title
+        #[arg(long, requires = "title")]
+        update_backlinks: bool,
+    },
+    /// Add tags to a note (or remove them with --remove)
+    Tag {
+        /// Note ID, short ID or title
+        id: String,
+        /// Tags to add or remove
+        #[arg(required = true)]
+        tags: Vec<String>,
+        /// Remove the tags instead of adding them
+        #[arg(short, long)]
+        remove: bool,
+    },
+    /// Remove tags from a note (same as `tag --remove`)
+    Untag {
+        /// Note ID, short ID or title
+        id: String,
+        /// Tags to remove
+        #[arg(required = true)]
+        tags: Vec<String>,
+    },
+    /// Show what changed between an earlier version of a note and now
+    Diff {
+        /// Note ID, short ID or title
         id: String,
+        /// Earlier version to compare with (1 is the oldest)
+        version: u32,
+    },
+}
+
+#[derive(Subcommand)]
+enum TagCommands {
+    /// List tags with usage counts
+    List,
+    /// Rename a tag everywhere it is used (merges into an existing tag)
+    Rename {
+        /// Current tag name
+        old: String,
+        /// New tag name
+        new: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TemplateCommands {
+    /// List templates
+    List,
+    /// Show a template
+    Show {
+        /// Note type or template name
+        name: String,
     },
+    /// Create or replace a template from a file
+    Set {
+        /// Note type (e.g. task) or a custom name used with --template
+        name: String,
+        /// File holding the template body ("-" reads stdin)
+        #[arg(short, long, value_name = "PATH")]
+        file: PathBuf,
+    },
+    /// Delete a template
+    Delete {
+        /// Note type or template name
+        name: String,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportFormat {
+    Markdown,
+    Json,
 }
 
 #[derive(Clone, Copy, ValueEnum)]
@@ -136,34 +435,88 @@ impl From<ArticleStatusArg> for Option<ArticleStatus> {
 
 #[derive(Subcommand)]
 enum ArticleCommands {
-    /// Add an article by URL (fetches and extracts content)
+    /// Add an article by URL (fetches and extracts content) or from a local file
     Add {
-        /// URL of the article to add
-        url: String,
+        /// URL of the article to add. With --file, the source URL to record
+        /// (defaults to a file:// URL)
+        #[arg(required_unless_present_any = ["file", "urls"])]
+        url: Option<String>,
+        /// Read a saved .html page or a .md file instead of fetching
+        #[arg(short, long, conflicts_with = "urls")]
+        file: Option<PathBuf>,
+        /// Fetch every URL listed in this file, one per line ("-" reads stdin)
+        #[arg(long, value_name = "PATH", conflicts_with = "url")]
+        urls: Option<PathBuf>,
+        /// How many URLs from --urls to fetch at once
+        #[arg(short, long, default_value_t = 4, requires = "urls")]
+        jobs: usize,
         /// Room to assign the article to
         #[arg(short, long)]
         room: Option<String>,
+        /// Keep URLs that fail to fetch as articles holding the error, to
+        /// retry later with mm article refetch
+        #[arg(long)]
+        save_failures: bool,
     },
     /// List articles
     List {
         /// Filter by status
         #[arg(short, long, value_enum, default_value = "all")]
         status: ArticleStatusArg,
+        /// Only show articles filed in this room (ID or name)
+        #[arg(short, long)]
+        room: Option<String>,
+        /// Only show articles that haven't been read yet
+        #[arg(short, long)]
+        unread: bool,
+        /// Only show articles updated at or after this time (e.g. 7d, yesterday, 2024-03-01)
+        #[arg(long, value_parser = parse_time)]
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        /// Only show articles updated before this time (a bare date means its midnight)
+        #[arg(long, value_parser = parse_time)]
+        until: Option<chrono::DateTime<chrono::Utc>>,
     },
     /// Interactive review dashboard for pending articles
-    Review,
+    Review {
+        /// Number of articles shown per page
+        #[arg(long, default_value_t = 10)]
+        page_size: usize,
+    },
     /// Show an article's content and summary
     Show {
         /// Article ID (or partial ID)
         id: String,
+        /// Show the summary in this style instead of the default one
+        #[arg(short, long, default_value = DEFAULT_SUMMARY_STYLE)]
+        style: String,
+    },
+    /// Mark an article as read (independent of its review status)
+    Read {
+        /// Article ID (or partial ID)
+        id: String,
     },
     /// Summarize an article using AI
     Summarize {
         /// Article ID (or partial ID)
         id: String,
-        /// Provider to use (anthropic, openai)
-        #[arg(short, long, default_value = "anthropic")]
-        provider: String,
+        /// Provider to use (anthropic, openai, ollama). Defaults to the
+        /// genius bound to the room's summary config, then anthropic
+        #[arg(short, long, conflicts_with = "genius")]
+        provider: Option<String>,
+        /// Genius (ID or name) to summarize with
+        #[arg(short, long)]
+        genius: Option<String>,
+        /// Model to use (defaults per provider). Ollama's endpoint can be set
+        /// with MINMIND_OLLAMA_URL
+        #[arg(short, long)]
+        model: Option<String>,
+        /// Summary style: default, tldr, bullet or tweet. Each style is kept
+        /// separately, so a tldr doesn't replace the default summary
+        #[arg(short, long, default_value = DEFAULT_SUMMARY_STYLE)]
+        style: String,
+        /// Summarize with this prompt instead of the room's summary config
+        #[arg(long)]
+        prompt: Option<String>,
     },
     /// Mark an article as reviewed and optionally convert to a Note
     Approve {
@@ -172,17 +525,51 @@ enum ArticleCommands {
         /// Room to create the Note in
         #[arg(short, long)]
         room: Option<String>,
+        /// Type of the created note (idea, task, reference, log)
+        #[arg(short = 't', long, default_value = "reference")]
+        note_type: NoteType,
+        /// Leave the article's full content out of the note
+        #[arg(long)]
+        summary_only: bool,
+        /// Lay the note out with this template ({{title}}, {{url}}, {{summary}}, {{content}})
+        #[arg(long)]
+        template: Option<String>,
     },
     /// Archive an article
     Archive {
         /// Article ID (or partial ID)
         id: String,
     },
+    /// Fetch an article's content again, e.g. after a failed or purged fetch
+    Refetch {
+        /// Article ID (or partial ID)
+        id: String,
+    },
+    /// Clear an article's summary and put it back in the Pending queue
+    Requeue {
+        /// Article ID (or partial ID)
+        id: String,
+    },
     /// Delete an article
     Delete {
         /// Article ID (or partial ID)
         id: String,
     },
+    /// Archive reviewed articles that haven't changed in a while
+    Cleanup {
+        /// Archive reviewed articles last updated longer ago than this, e.g. 30d
+        #[arg(long, default_value = "30d", value_parser = parse_duration)]
+        older_than: chrono::Duration,
+        /// Also drop the raw content of old reviewed and archived articles,
+        /// keeping their summary and URL
+        #[arg(long)]
+        drop_content: bool,
+    },
+    /// Show an article's status history
+    History {
+        /// Article ID (or partial ID)
+        id: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -193,12 +580,20 @@ enum ConfigCommands {
     Create {
         /// Name for this configuration
         name: String,
-        /// System prompt for summarization
+        /// System prompt for summarization (opens $EDITOR when omitted)
         #[arg(short, long)]
         prompt: Option<String>,
         /// Room to apply this config to (omit for global)
         #[arg(short, long)]
         room: Option<String>,
+        /// Genius (ID or name) to summarize with
+        #[arg(short, long)]
+        genius: Option<String>,
+    },
+    /// Edit a configuration's prompt in $EDITOR
+    Edit {
+        /// Config ID
+        id: String,
     },
     /// Show the default summary prompt
     Default,
@@ -209,6 +604,37 @@ enum ConfigCommands {
     },
 }
 
+#[derive(Subcommand)]
+enum GeniusCommands {
+    /// List geniuses
+    List,
+    /// Add a genius
+    Add {
+        /// Name of the genius
+        name: String,
+        /// Provider (anthropic, openai, ollama)
+        #[arg(short, long)]
+        provider: String,
+        /// Model name
+        #[arg(short, long)]
+        model: String,
+        /// System prompt
+        #[arg(short, long)]
+        system_prompt: Option<String>,
+        /// Sampling temperature (0 to 2)
+        #[arg(long)]
+        temperature: Option<f32>,
+        /// Maximum number of tokens in a response
+        #[arg(long)]
+        max_tokens: Option<u32>,
+    },
+    /// Delete a genius
+    Delete {
+        /// Genius ID or name
+        id: String,
+    },
+}
+
 #[derive(Clone, Copy, ValueEnum)]
 enum TodoStatusArg {
     Pending,
@@ -230,6 +656,16 @@ impl From<TodoStatusArg> for Option<ActionStatus> {
     }
 }
 
+#[derive(Subcommand)]
+enum DbCommands {
+    /// Show each table's row count and approximate size, largest first
+    Stats,
+    /// Check that the search indexes cover every note, article and todo
+    Check,
+    /// Rebuild the full-text search indexes from the notes, articles and todos
+    Reindex,
+}
+
 #[derive(Subcommand)]
 enum TodoCommands {
     /// List user action todos
@@ -240,12 +676,49 @@ enum TodoCommands {
         /// Filter by plan file
         #[arg(short, long)]
         plan: Option<String>,
+        /// Filter by priority (low, medium, high)
+        #[arg(long)]
+        priority: Option<String>,
+        /// Only todos due before this date (YYYY-MM-DD)
+        #[arg(long, value_parser = parse_date)]
+        due_before: Option<chrono::DateTime<chrono::Utc>>,
+        /// Only open todos whose due date has passed
+        #[arg(long, conflicts_with_all = ["status", "due_before", "due_today"])]
+        overdue: bool,
+        /// Only open todos due by the end of today (UTC), overdue ones included
+        #[arg(long, conflicts_with_all = ["status", "due_before"])]
+        due_today: bool,
+        /// Only todos matching this full-text query
+        #[arg(long)]
+        search: Option<String>,
+    },
+    /// Search todo titles and descriptions
+    Search {
+        /// Search query
+        query: String,
+    },
+    /// Report completed, in-progress and pending todos, e.g. for a standup
+    Report {
+        /// Count todos completed since: today, yesterday, a duration like 7d, or YYYY-MM-DD
+        #[arg(long, default_value = "yesterday", value_parser = parse_time)]
+        since: chrono::DateTime<chrono::Utc>,
+        /// Output format
+        #[arg(long, value_enum, default_value = "markdown")]
+        format: ReportFormat,
     },
     /// Sync todos from plan files to database
     Sync {
         /// Directory containing plan files (default: ./plans)
         #[arg(short, long, default_value = "plans")]
         dir: String,
+        /// Also sync plain `- [ ]`/`- [x]` checkboxes as todos
+        #[arg(long)]
+        include_checkboxes: bool,
+    },
+    /// Import todos from a plain list, one per line ("x " or "[x]" done, "~" skipped)
+    Import {
+        /// Todo list file
+        file: String,
     },
     /// Mark a todo as completed
     Complete {
@@ -269,46 +742,202 @@ enum TodoCommands {
         /// Source file to associate with
         #[arg(short, long)]
         plan: Option<String>,
+        /// Also append a `- [USER] <title>` line to the plan file (creating it
+        /// if missing), so the todo stays in sync with it
+        #[arg(long, requires = "plan")]
+        append: bool,
+        /// Priority (low, medium, high)
+        #[arg(long)]
+        priority: Option<String>,
+        /// Due date (YYYY-MM-DD)
+        #[arg(long, value_parser = parse_date)]
+        due: Option<chrono::DateTime<chrono::Utc>>,
     },
+    /// Move todos to a renamed or moved plan file, keeping their history
+    Relink {
+        /// Plan file the todos were synced from
+        #[arg(long)]
+        from: String,
+        /// Plan file's new path
+        #[arg(long)]
+        to: String,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        // --help and --version aren't failures
+        Err(err) if !err.use_stderr() => err.exit(),
+        Err(err) => {
+            let _ = err.print();
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+    };
+
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {:?}", err);
+            ExitCode::from(exit_code(&err))
+        }
+    }
 }
 
-fn main() -> anyhow::Result<()> {
-    let cli = Cli::parse();
+fn run(cli: Cli) -> anyhow::Result<()> {
 
-    // Expand ~ in database path
-    let db_path = expand_path(&cli.database);
+    // Expand ~ in database path, defaulting to the platform data directory
+    let db_path = cli
+        .database
+        .as_deref()
+        .map(expand_path)
+        .unwrap_or_else(default_database_path);
 
     // Ensure parent directory exists
     if let Some(parent) = db_path.parent() {
         std::fs::create_dir_all(parent)?;
     }
 
-    let store = Store::open(&db_path)?;
+    let mut store = open_store(&db_path)?;
+    store.set_min_id_prefix(cli.id_prefix);
+    let style = Style::new(cli.color);
 
     match cli.command {
-        Commands::Room { action } => handle_room_command(&store, action),
-        Commands::Note { action } => handle_note_command(&store, action),
-        Commands::Search { query } => handle_search(&store, &query),
-        Commands::Article { action } => handle_article_command(&store, action, &db_path),
-        Commands::Config { action } => handle_config_command(&store, action),
-        Commands::Todo { action } => handle_todo_command(&store, action),
+        Commands::Room { action } => handle_room_command(&store, action, cli.yes),
+        Commands::Note { action } => handle_note_command(&store, action, cli.yes, style),
+        Commands::Tag { action } => handle_tag_command(&store, action),
+        Commands::Template { action } => handle_template_command(&store, action, cli.yes),
+        Commands::Search {
+            query,
+            room,
+            fuzzy,
+            limit,
+        } => handle_search(&store, &query, room.as_deref(), None, fuzzy, limit.max(1)),
+        Commands::Article { action, timeout } => {
+            handle_article_command(&store, action, &db_path, timeout, cli.yes, style)
+        }
+        Commands::Config { action } => handle_config_command(&store, action, cli.yes),
+        Commands::Genius { action } => handle_genius_command(&store, action, cli.yes),
+        Commands::Todo { action } => handle_todo_command(&store, action, style),
+        Commands::Export {
+            room,
+            recursive,
+            include_external_links,
+            output,
+        } => handle_export(&store, &room, recursive, include_external_links, output),
+        Commands::Import { file } => handle_import(&store, &file),
+        Commands::Db { action } => handle_db_command(&store, action),
+        Commands::Stats { room } => handle_stats(&store, room.as_deref()),
+    }
+}
+
+const EXIT_ERROR: u8 = 1;
+const EXIT_NOT_FOUND: u8 = 2;
+const EXIT_AMBIGUOUS_ID: u8 = 3;
+const EXIT_INVALID_INPUT: u8 = 4;
+
+const EXIT_CODES_HELP: &str = "Exit codes:
+  0  success
+  1  error
+  2  not found
+  3  ambiguous id prefix
+  4  invalid input or arguments";
+
+/// A failure with its own exit code, for scripts that check `$?`
+#[derive(Debug, thiserror::Error)]
+enum CliError {
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    InvalidInput(String),
+}
+
+/// The exit code for a failed command, from the first cause that has one
+fn exit_code(err: &anyhow::Error) -> u8 {
+    for cause in err.chain() {
+        if let Some(err) = cause.downcast_ref::<CliError>() {
+            return match err {
+                CliError::NotFound(_) => EXIT_NOT_FOUND,
+                CliError::InvalidInput(_) => EXIT_INVALID_INPUT,
+            };
+        }
+        if let Some(err) = cause.downcast_ref::<ShortIdError>() {
+            return match err {
+                ShortIdError::NotFound(_) => EXIT_NOT_FOUND,
+                ShortIdError::Ambiguous { .. } => EXIT_AMBIGUOUS_ID,
+                ShortIdError::TooShort { .. } => EXIT_INVALID_INPUT,
+            };
+        }
+        match cause.downcast_ref::<StoreError>() {
+            Some(StoreError::NotFound(_)) => return EXIT_NOT_FOUND,
+            Some(StoreError::InvalidQuery(_) | StoreError::DuplicateName(_)) => {
+                return EXIT_INVALID_INPUT
+            }
+            _ => {}
+        }
+        match cause.downcast_ref::<CoreError>() {
+            Some(CoreError::NotFound(_)) => return EXIT_NOT_FOUND,
+            Some(CoreError::InvalidInput(_)) => return EXIT_INVALID_INPUT,
+            _ => {}
+        }
+        if cause.is::<uuid::Error>() {
+            return EXIT_INVALID_INPUT;
+        }
+    }
+    EXIT_ERROR
+}
+
+/// Open the store, turning a corrupt database file into actionable advice
+fn open_store(db_path: &Path) -> anyhow::Result<Store> {
+    match Store::open(db_path) {
+        Ok(store) => Ok(store),
+        Err(StoreError::CorruptDatabase { path }) => anyhow::bail!(
+            "The database at {} is corrupt or is not a MinMind database.\n\
+             Restore it from a backup, or move it aside to start fresh \
+             (e.g. `mv {} {}.bak`).\n\
+             `sqlite3 {} 'PRAGMA integrity_check'` may show what is damaged.",
+            path.display(),
+            path.display(),
+            path.display(),
+            path.display()
+        ),
+        Err(e) => Err(e.into()),
     }
 }
 
-fn handle_room_command(store: &Store, action: RoomCommands) -> anyhow::Result<()> {
+fn handle_room_command(store: &Store, action: RoomCommands, yes: bool) -> anyhow::Result<()> {
     match action {
-        RoomCommands::List => {
+        RoomCommands::List { stats: true } => {
+            let rooms = store.list_rooms_with_counts()?;
+            if rooms.is_empty() {
+                println!("No rooms yet. Create one with: mm room create <name>");
+            } else {
+                println!(
+                    "{:<36}  {:<20}  {:>6}  {:>8}  {:>6}",
+                    "ID", "NAME", "NOTES", "ARTICLES", "TODOS"
+                );
+                println!("{}", "-".repeat(84));
+                for (room, counts) in rooms {
+                    println!(
+                        "{:<36}  {:<20}  {:>6}  {:>8}  {:>6}",
+                        room.id, room.name, counts.notes, counts.articles, counts.todos
+                    );
+                }
+            }
+        }
+        RoomCommands::List { stats: false } => {
             let rooms = store.list_rooms()?;
             if rooms.is_empty() {
                 println!("No rooms yet. Create one with: mm room create <name>");
             } else {
-                println!("{:<36}  {:<20}  {}", "ID", "NAME", "DESCRIPTION");
-                println!("{}", "-".repeat(80));
+                println!("{:<36}  {:<20}  {:<20}  DESCRIPTION", "ID", "NAME", "SLUG");
+                println!("{}", "-".repeat(100));
                 for room in rooms {
                     println!(
-                        "{:<36}  {:<20}  {}",
+                        "{:<36}  {:<20}  {:<20}  {}",
                         room.id,
                         room.name,
+                        room.slug,
                         room.description.as_deref().unwrap_or("-")
                     );
                 }
@@ -318,34 +947,143 @@ fn handle_room_command(store: &Store, action: RoomCommands) -> anyhow::Result<()
             name,
             description,
             parent,
+            exists_ok,
         } => {
+            let parent = parent.map(|id| uuid::Uuid::parse_str(&id)).transpose()?;
+            if exists_ok {
+                let mut room = store.get_or_create_room(&name, parent)?;
+                if room.description.is_none() && description.is_some() {
+                    room.description = description;
+                    store.update_room(&room)?;
+                }
+                println!("Room: {} ({}) [{}]", room.name, room.id, room.slug);
+                return Ok(());
+            }
             let mut room = Room::new(&name);
             if let Some(desc) = description {
                 room = room.with_description(desc);
             }
-            if let Some(parent_id) = parent {
-                let parent_uuid = uuid::Uuid::parse_str(&parent_id)?;
+            if let Some(parent_uuid) = parent {
                 room = room.with_parent(parent_uuid);
             }
-            store.create_room(&room)?;
-            println!("Created room: {} ({})", name, room.id);
+            store.create_room(&mut room)?;
+            println!("Created room: {} ({}) [{}]", name, room.id, room.slug);
         }
         RoomCommands::Delete { room } => {
             let room_id = find_room_id(store, &room)?;
+            let counts = store
+                .list_rooms_with_counts()?
+                .into_iter()
+                .find(|(r, _)| r.id == room_id)
+                .map(|(_, counts)| counts)
+                .unwrap_or_default();
+            let question = format!(
+                "Delete room {} ({} note(s), {} article(s))?",
+                room, counts.notes, counts.articles
+            );
+            if !confirm(&question, yes)? {
+                println!("Kept room: {}", room);
+                return Ok(());
+            }
             store.delete_room(room_id)?;
             println!("Deleted room: {}", room_id);
         }
+        RoomCommands::Merge { keep, absorb } => {
+            let keep_id = find_room_id(store, &keep)?;
+            let absorb_id = find_room_id(store, &absorb)?;
+            if !confirm(&format!("Merge room {} into {} and delete it?", absorb, keep), yes)? {
+                println!("Kept room: {}", absorb);
+                return Ok(());
+            }
+            let moved = store.merge_rooms(keep_id, absorb_id)?;
+            println!("Merged {} into {} ({} note(s) moved)", absorb, keep, moved);
+        }
+        RoomCommands::Cat {
+            room,
+            note_type,
+            metadata,
+            output,
+        } => {
+            let room_id = find_room_id(store, &room)?;
+            let name = store.get_room(room_id)?.map_or(room, |r| r.name);
+            let notes = store.list_notes_in_room_filtered(room_id, note_type, None)?;
+            let mut options = RoomMarkdownOptions::default().with_title(name);
+            if metadata {
+                options = options.with_metadata();
+            }
+            let document = render_room_markdown_with(&notes, &options);
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, document)?;
+                    eprintln!("Wrote {} note(s) to {}", notes.len(), path.display());
+                }
+                None => print!("{}", document),
+            }
+        }
     }
     Ok(())
 }
 
-fn handle_note_command(store: &Store, action: NoteCommands) -> anyhow::Result<()> {
+fn handle_note_command(
+    store: &Store,
+    action: NoteCommands,
+    yes: bool,
+    style: Style,
+) -> anyhow::Result<()> {
     match action {
-        NoteCommands::List { room } => {
+        NoteCommands::List {
+            room,
+            recursive,
+            preview,
+            source,
+            note_type,
+            status,
+            since,
+            until,
+            tag,
+        } => {
             let room_id = find_room_id(store, &room)?;
-            let notes = store.list_notes_in_room(room_id)?;
-            if notes.is_empty() {
+            let mut notes = if recursive {
+                let mut notes = store.notes_in_room_recursive(room_id)?;
+                notes.retain(|note| {
+                    note_type.is_none_or(|t| note.note_type == t)
+                        && status.is_none_or(|s| note.status == Some(s))
+                });
+                notes
+            } else {
+                store.list_notes_in_room_filtered(room_id, note_type, status)?
+            };
+            if let Some(source) = source {
+                notes.retain(|note| note.source == source);
+            }
+            if let Some(tag) = &tag {
+                let tagged: HashSet<_> =
+                    store.list_notes_with_tag(tag)?.into_iter().map(|note| note.id).collect();
+                notes.retain(|note| tagged.contains(&note.id));
+            }
+            notes.retain(|note| within(note.updated_at, since, until));
+            let filtered = source.is_some()
+                || note_type.is_some()
+                || status.is_some()
+                || since.is_some()
+                || until.is_some()
+                || tag.is_some();
+            if notes.is_empty() && filtered {
+                println!("No matching notes in this room.");
+            } else if notes.is_empty() {
                 println!("No notes in this room. Create one with: mm note create <room> <title>");
+            } else if preview {
+                println!("{:<36}  {:<8}  {:<30}  PREVIEW", "ID", "TYPE", "TITLE");
+                println!("{}", "-".repeat(120));
+                for note in notes {
+                    println!(
+                        "{:<36}  {:<8}  {:<30}  {}",
+                        note.id,
+                        note.note_type.to_string(),
+                        truncate_string(&note.title, 30),
+                        note.content_summary(40)
+                    );
+                }
             } else {
                 println!("{:<36}  {:<8}  {:<20}", "ID", "TYPE", "TITLE");
                 println!("{}", "-".repeat(70));
@@ -359,142 +1097,665 @@ fn handle_note_command(store: &Store, action: NoteCommands) -> anyhow::Result<()
             title,
             note_type,
             content,
+            content_file,
+            template,
+            format,
         } => {
+            let content = match (content, content_file) {
+                (Some(_), Some(_)) => anyhow::bail!(CliError::InvalidInput(
+                    "Give note content either with --content or with --content-file, not both"
+                        .to_string()
+                )),
+                (Some(c), None) if c == "-" => {
+                    let mut buf = String::new();
+                    std::io::stdin().read_to_string(&mut buf)?;
+                    Some(buf)
+                }
+                (Some(c), None) => Some(c),
+                (None, Some(path)) => Some(std::fs::read_to_string(&path).map_err(|e| {
+                    anyhow::anyhow!("Could not read {}: {}", path.display(), e)
+                })?),
+                (None, None) => None,
+            };
             let room_id = find_room_id(store, &room)?;
-            let nt: NoteType = note_type.parse().map_err(|e: String| anyhow::anyhow!(e))?;
-            let mut note = Note::new(room_id, &title, nt);
+            let nt: NoteType = note_type.parse().map_err(CliError::InvalidInput)?;
+            let content = match (content, template) {
+                (Some(c), _) => Some(c),
+                (None, Some(name)) => Some(
+                    store
+                        .get_template(&name)?
+                        .ok_or_else(|| {
+                            CliError::NotFound(format!("Template not found: {}", name))
+                        })?,
+                ),
+                (None, None) => store.get_template(&nt.to_string())?,
+            };
+            let cf: ContentFormat = format.parse().map_err(CliError::InvalidInput)?;
+            let mut note = Note::new(room_id, &title, nt).with_content_format(cf);
             if let Some(c) = content {
                 note = note.with_content(c);
             }
-            if nt == NoteType::Task {
-                note = note.with_status(Status::Active);
-            }
             store.create_note(&note)?;
             println!("Created note: {} ({})", title, note.id);
         }
-        NoteCommands::Show { id } => {
-            let note_id = uuid::Uuid::parse_str(&id)?;
-            match store.get_note(note_id)? {
-                Some(note) => {
+        NoteCommands::Show { id, render, raw } => {
+            let note_id = find_note(store, &id)?.id;
+            match store.get_note_with_links(note_id)? {
+                Some(NoteWithLinks {
+                    note,
+                    outgoing,
+                    incoming,
+                }) => {
                     println!("Title: {}", note.title);
                     println!("Type: {}", note.note_type);
                     if let Some(status) = note.status {
                         println!("Status: {}", status);
                     }
                     println!("Room: {}", note.room_id);
+                    println!("Source: {}", note.source);
+                    let tags = store.tags_for_note(note.id)?;
+                    if !tags.is_empty() {
+                        println!("Tags: {}", tags.join(", "));
+                    }
                     println!("Created: {}", note.created_at);
                     println!("Updated: {}", note.updated_at);
-                    println!("\n{}", note.content);
+                    print_linked_notes("Links to", &outgoing);
+                    print_linked_notes("Linked from", &incoming);
+                    let attachments = store.list_attachments(note.id)?;
+                    if !attachments.is_empty() {
+                        println!("Attachments:");
+                        for attachment in &attachments {
+                            println!(
+                                "  {} ({}, {} bytes)",
+                                attachment.filename, attachment.mime_type, attachment.size
+                            );
+                        }
+                    }
+                    println!();
+                    if should_render(note.content_format, render, raw, style.is_fancy()) {
+                        termimad::MadSkin::default().print_text(&note.content);
+                    } else {
+                        println!("{}", note.content);
+                    }
+                }
+                None => return Err(CliError::NotFound(format!("Note not found: {}", id)).into()),
+            }
+        }
+        NoteCommands::Latest { room, render, raw } => {
+            let room_id = find_room_id(store, &room)?;
+            let Some(note) = store.latest_note_in_room(room_id)? else {
+                println!("No notes in this room. Create one with: mm note create <room> <title>");
+                return Ok(());
+            };
+            let show = NoteCommands::Show {
+                id: note.id.to_string(),
+                render,
+                raw,
+            };
+            return handle_note_command(store, show, yes, style);
+        }
+        NoteCommands::Delete {
+            id,
+            permanent,
+            force,
+        } => {
+            let note = find_note(store, &id)?;
+            let note_id = note.id;
+            if permanent {
+                let links = store
+                    .get_note_with_links(note_id)?
+                    .map_or(0, |n| n.outgoing.len() + n.incoming.len());
+                let keep = if links > 0 {
+                    !force && !yes && !confirm_link_removal(links)?
+                } else {
+                    !confirm(&format!("Permanently delete note '{}'?", note.title), yes)?
+                };
+                if keep {
+                    println!("Kept note: {}", note_id);
+                    return Ok(());
+                }
+                let report = store.delete_note_report(note_id)?;
+                println!("Deleted note: {}", note_id);
+                if report.removed_links > 0 {
+                    println!(
+                        "Removed {} link(s) to: {}",
+                        report.removed_links,
+                        report.linked_titles.join(", ")
+                    );
                 }
-                None => {
-                    println!("Note not found: {}", id);
+            } else {
+                store.trash_note(note_id)?;
+                println!(
+                    "Moved note to trash: {} (restore with: mm note restore {})",
+                    note_id, note_id
+                );
+            }
+        }
+        NoteCommands::Trash { purge, older_than } => {
+            let trashed = store.list_trashed_notes()?;
+            if purge {
+                let cutoff = chrono::Utc::now() - older_than;
+                for note in trashed.iter().filter(|n| n.deleted_at.is_some_and(|at| at < cutoff)) {
+                    println!("Purging: {} ({})", note.title, note.id);
+                }
+                let purged = store.purge_trashed(older_than)?;
+                println!("Purged {} note(s)", purged);
+            } else if trashed.is_empty() {
+                println!("Trash is empty");
+            } else {
+                println!("{:<36}  {:<20}  TITLE", "ID", "TRASHED");
+                println!("{}", "-".repeat(80));
+                for note in trashed {
+                    let at = note.deleted_at.map(|at| at.format("%Y-%m-%d %H:%M").to_string());
+                    println!("{:<36}  {:<20}  {}", note.id, at.unwrap_or_default(), note.title);
+                }
+            }
+        }
+        NoteCommands::Restore { id } => {
+            let trashed = store.list_trashed_notes()?;
+            let note_id = resolve_note(&id, &trashed, store.min_id_prefix())?.id;
+            store.restore_note(note_id)?;
+            println!("Restored note: {}", note_id);
+        }
+        NoteCommands::Duplicate { id, room } => {
+            let note_id = find_note(store, &id)?.id;
+            let target_room = room.map(|r| find_room_id(store, &r)).transpose()?;
+            let copy = store.duplicate_note(note_id, target_room)?;
+            println!("Created note: {} ({})", copy.title, copy.id);
+        }
+        NoteCommands::Attach { id, file } => {
+            let note_id = find_note(store, &id)?.id;
+            let attachment = store.add_attachment(note_id, &file)?;
+            println!(
+                "Attached {} ({}, {} bytes) to note {}",
+                attachment.filename, attachment.mime_type, attachment.size, note_id
+            );
+        }
+        NoteCommands::Move {
+            id,
+            room,
+            title,
+            update_backlinks,
+        } => {
+            if room.is_none() && title.is_none() {
+                anyhow::bail!(CliError::InvalidInput(
+                    "Nothing to do: give --room and/or --title".to_string()
+                ));
+            }
+            let note_id = find_note(store, &id)?.id;
+            if let Some(room) = room {
+                let room_id = find_room_id(store, &room)?;
+                store.move_note(note_id, room_id)?;
+                println!("Moved note {} to {}", note_id, room);
+            }
+            if let Some(title) = title {
+                let updated = store.rename_note(note_id, &title, update_backlinks)?;
+                println!("Renamed note to: {}", title.trim());
+                if update_backlinks {
+                    println!("Updated links in {} other note(s)", updated);
+                }
+            }
+        }
+        NoteCommands::Tag { id, tags, remove } => {
+            let note_id = find_note(store, &id)?.id;
+            for tag in &tags {
+                if !remove {
+                    store.add_note_tag(note_id, tag)?;
+                } else if !store.remove_note_tag(note_id, tag)? {
+                    eprintln!("Note {} wasn't tagged {}", note_id, tag);
                 }
             }
+            let current = store.tags_for_note(note_id)?;
+            if current.is_empty() {
+                println!("Note {} has no tags", note_id);
+            } else {
+                println!("Tags: {}", current.join(", "));
+            }
         }
-        NoteCommands::Delete { id } => {
-            let note_id = uuid::Uuid::parse_str(&id)?;
-            store.delete_note(note_id)?;
-            println!("Deleted note: {}", note_id);
+        NoteCommands::Untag { id, tags } => {
+            let tag = NoteCommands::Tag { id, tags, remove: true };
+            handle_note_command(store, tag, yes, style)?;
+        }
+        NoteCommands::Search {
+            query,
+            room,
+            note_type,
+            fuzzy,
+            limit,
+        } => handle_search(store, &query, room.as_deref(), note_type, fuzzy, limit.max(1))?,
+        NoteCommands::Diff { id, version } => {
+            let note = find_note(store, &id)?;
+            let Some(earlier) = store.get_note_version(note.id, version)? else {
+                anyhow::bail!(CliError::NotFound(format!(
+                    "Note '{}' has no version {} ({} saved)",
+                    note.title,
+                    version,
+                    store.note_version_count(note.id)?
+                )));
+            };
+            print_diff(&note.diff(&earlier), version, &earlier, style);
         }
     }
     Ok(())
 }
 
-fn handle_search(store: &Store, query: &str) -> anyhow::Result<()> {
-    let notes = store.search_notes(query)?;
-    if notes.is_empty() {
-        println!("No notes found matching: {}", query);
-    } else {
-        println!("Found {} note(s):", notes.len());
-        println!("{:<36}  {:<8}  {:<20}", "ID", "TYPE", "TITLE");
-        println!("{}", "-".repeat(70));
-        for note in notes {
-            println!("{:<36}  {:<8}  {}", note.id, note.note_type, note.title);
+/// Parse a date given as `YYYY-MM-DD` (midnight UTC) or a full RFC 3339 timestamp
+fn parse_date(input: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return Ok(date.and_time(chrono::NaiveTime::MIN).and_utc());
+    }
+    chrono::DateTime::parse_from_rfc3339(input)
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+        .map_err(|_| format!("Invalid date '{}': expected YYYY-MM-DD", input))
+}
+
+/// Parse a `--since`/`--until` bound; see [`parse_time_expr`]
+fn parse_time(input: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    parse_time_expr(input).ok_or_else(|| {
+        format!(
+            "Invalid time '{}': use today, yesterday, a duration like 7d or 2w, or YYYY-MM-DD",
+            input
+        )
+    })
+}
+
+/// Whether `at` falls in `[since, until)`; a missing bound is open
+fn within(
+    at: chrono::DateTime<chrono::Utc>,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    until: Option<chrono::DateTime<chrono::Utc>>,
+) -> bool {
+    since.is_none_or(|since| at >= since) && until.is_none_or(|until| at < until)
+}
+
+/// Parse a positive duration like `30d`, `12h` or `45m`, short enough to
+/// count back from now
+fn parse_duration(input: &str) -> Result<chrono::Duration, String> {
+    let input = input.trim();
+    let split = input.char_indices().last().map_or(0, |(i, _)| i);
+    let (amount, unit) = input.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| format!("Invalid duration '{}': expected e.g. 30d or 12h", input))?;
+    if amount <= 0 {
+        return Err(format!("Invalid duration '{}': must be positive", input));
+    }
+    let duration = match unit {
+        "d" => chrono::Duration::try_days(amount),
+        "h" => chrono::Duration::try_hours(amount),
+        "m" => chrono::Duration::try_minutes(amount),
+        "s" => chrono::Duration::try_seconds(amount),
+        _ => return Err(format!("Invalid duration unit in '{}': use d, h, m or s", input)),
+    };
+    duration
+        .filter(|duration| chrono::Utc::now().checked_sub_signed(*duration).is_some())
+        .ok_or_else(|| format!("Invalid duration '{}': too long", input))
+}
+
+/// Parse a subprocess time limit such as "120s" or "5m"
+fn parse_timeout(input: &str) -> Result<std::time::Duration, String> {
+    parse_duration(input)?
+        .to_std()
+        .ok()
+        .filter(|timeout| !timeout.is_zero())
+        .ok_or_else(|| format!("Invalid timeout '{}': must be positive", input.trim()))
+}
+
+/// Print a note diff with -/+ line prefixes, optionally in red and green
+fn print_diff(diff: &ContentDiff, version: u32, earlier: &Note, style: Style) {
+    if diff.is_empty() {
+        println!("No changes since version {}", version);
+        return;
+    }
+    println!("Version {} ({}) -> current", version, earlier.updated_at.format("%Y-%m-%d %H:%M"));
+    if diff.title_changed() {
+        println!("{}", style.paint(Color::Red, format!("- Title: {}", diff.old_title)));
+        println!("{}", style.paint(Color::Green, format!("+ Title: {}", diff.new_title)));
+    }
+    if !diff.content_changed() {
+        println!("Content unchanged");
+        return;
+    }
+    println!();
+    for line in &diff.lines {
+        match line {
+            DiffLine::Unchanged(text) => println!("  {}", text),
+            DiffLine::Removed(text) => {
+                println!("{}", style.paint(Color::Red, format!("- {}", text)))
+            }
+            DiffLine::Added(text) => {
+                println!("{}", style.paint(Color::Green, format!("+ {}", text)))
+            }
+        }
+    }
+}
+
+/// Whether note content should go through the markdown renderer: markdown
+/// notes are rendered on a terminal or with `--render`, never with `--raw`
+fn should_render(format: ContentFormat, render: bool, raw: bool, tty: bool) -> bool {
+    format == ContentFormat::Markdown && !raw && (render || tty)
+}
+
+fn handle_tag_command(store: &Store, action: TagCommands) -> anyhow::Result<()> {
+    match action {
+        TagCommands::List => {
+            let tags = store.list_tags()?;
+            if tags.is_empty() {
+                println!("No tags yet. Add one with: mm note tag <note-id> <tag>");
+            } else {
+                println!("{:<30}  NOTES", "TAG");
+                println!("{}", "-".repeat(40));
+                for (name, count) in tags {
+                    println!("{:<30}  {}", name, count);
+                }
+            }
+        }
+        TagCommands::Rename { old, new } => {
+            store.rename_tag(&old, &new)?;
+            println!("Renamed tag: {} -> {}", old, new);
         }
     }
     Ok(())
 }
 
-fn handle_article_command(store: &Store, action: ArticleCommands, db_path: &PathBuf) -> anyhow::Result<()> {
+fn handle_template_command(
+    store: &Store,
+    action: TemplateCommands,
+    yes: bool,
+) -> anyhow::Result<()> {
     match action {
-        ArticleCommands::Review => {
-            handle_review_dashboard(store, db_path)?;
+        TemplateCommands::List => {
+            let names = store.list_templates()?;
+            if names.is_empty() {
+                println!("No templates yet. Add one with: mm template set <type> --file <path>");
+            } else {
+                for name in names {
+                    println!("{}", name);
+                }
+            }
         }
-        ArticleCommands::Add { url, room } => {
-            // Check if article already exists
-            if store.get_article_by_url(&url)?.is_some() {
-                anyhow::bail!("Article already exists: {}", url);
+        TemplateCommands::Show { name } => match store.get_template(&name)? {
+            Some(content) => println!("{}", content),
+            None => anyhow::bail!(CliError::NotFound(format!("Template not found: {}", name))),
+        },
+        TemplateCommands::Set { name, file } => {
+            let content = if file.as_os_str() == "-" {
+                let mut buf = String::new();
+                std::io::stdin().read_to_string(&mut buf)?;
+                buf
+            } else {
+                std::fs::read_to_string(&file)
+                    .map_err(|e| anyhow::anyhow!("Could not read {}: {}", file.display(), e))?
+            };
+            store.set_template(&name, &content)?;
+            println!("Saved template: {}", name);
+        }
+        TemplateCommands::Delete { name } => {
+            if store.get_template(&name)?.is_none() {
+                anyhow::bail!(CliError::NotFound(format!("Template not found: {}", name)));
             }
+            if !confirm(&format!("Delete template {}?", name), yes)? {
+                println!("Kept template: {}", name);
+                return Ok(());
+            }
+            store.delete_template(&name)?;
+            println!("Deleted template: {}", name);
+        }
+    }
+    Ok(())
+}
 
-            // Call Python to extract article content
-            println!("Fetching article from {}...", url);
-            let output = Command::new("python")
-                .args(["-m", "minmind.cli", "extract", &url])
-                .current_dir(find_python_dir()?)
-                .output()?;
-
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                anyhow::bail!("Failed to extract article: {}", stderr);
+fn handle_export(
+    store: &Store,
+    rooms: &[String],
+    recursive: bool,
+    include_external_links: bool,
+    output: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let json = if rooms.is_empty() {
+        store.export_json()?
+    } else {
+        let mut room_ids = Vec::new();
+        for room in rooms {
+            let room_id = find_room_id(store, room)?;
+            if recursive {
+                room_ids.extend(store.room_subtree_ids(room_id)?);
+            } else {
+                room_ids.push(room_id);
             }
+        }
+        store.export_room_json(&room_ids, include_external_links)?
+    };
 
-            // Parse the JSON output
-            let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
-            
-            let title = json["title"].as_str().unwrap_or("Untitled");
-            let content = json["content"].as_str().unwrap_or("");
-            
-            let mut article = Article::new(&url, title, content);
-            
-            // Set room if provided
-            if let Some(room_name) = room {
-                let room_id = find_room_id(store, &room_name)?;
-                article = article.with_room(room_id);
+    match output {
+        Some(path) => {
+            std::fs::write(&path, json)?;
+            eprintln!("Exported to {}", path.display());
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
+fn handle_import(store: &Store, file: &Path) -> anyhow::Result<()> {
+    let json = if file.as_os_str() == "-" {
+        let mut buf = String::new();
+        std::io::stdin().read_to_string(&mut buf)?;
+        buf
+    } else {
+        std::fs::read_to_string(file)
+            .map_err(|e| anyhow::anyhow!("Could not read {}: {}", file.display(), e))?
+    };
+    let report = store.import_json_with_progress(&json, &mut print_progress)?;
+    println!(
+        "Imported {} room(s), {} note(s), {} link(s) and {} article(s)",
+        report.rooms, report.notes, report.links, report.articles
+    );
+    Ok(())
+}
+
+/// Show a long operation's progress as a counter on stderr, rewritten in
+/// place; nothing is printed when stderr isn't a terminal
+fn print_progress(progress: Progress) {
+    if !std::io::stderr().is_terminal() {
+        return;
+    }
+    eprint!("\r  {}: {}/{}", progress.phase, progress.done, progress.total);
+    if progress.is_phase_done() {
+        eprintln!();
+    }
+}
+
+/// Number of "did you mean" suggestions shown by `mm search --fuzzy`
+const FUZZY_SUGGESTIONS: usize = 5;
+
+/// Number of matches `mm search` shows unless given `--limit`
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+fn handle_search(
+    store: &Store,
+    query: &str,
+    room: Option<&str>,
+    note_type: Option<NoteType>,
+    fuzzy: bool,
+    limit: usize,
+) -> anyhow::Result<()> {
+    if is_blank_search_query(query) {
+        println!("Please provide a search term.");
+        return Ok(());
+    }
+    let room_id = room.map(|room| find_room_id(store, room)).transpose()?;
+    let notes = store.search_notes_filtered(query, room_id, note_type, limit)?;
+    if notes.is_empty() {
+        println!("No notes found matching: {}", query);
+        if fuzzy {
+            let mut suggestions = store.search_notes_fuzzy(query, room_id, FUZZY_SUGGESTIONS)?;
+            suggestions.retain(|note| note_type.is_none_or(|t| note.note_type == t));
+            if !suggestions.is_empty() {
+                println!("\nDid you mean:");
+                for note in suggestions {
+                    println!("  {}  {}", note.id, note.title);
+                }
             }
+        }
+    } else {
+        let total = store.count_search_matches_filtered(query, room_id, note_type)?;
+        if total > notes.len() as u64 {
+            println!("Showing top {} of {} note(s):", notes.len(), total);
+        } else {
+            println!("Found {} note(s):", notes.len());
+        }
+        println!("{:<36}  {:<8}  {:<20}", "ID", "TYPE", "TITLE");
+        println!("{}", "-".repeat(70));
+        for note in notes {
+            println!("{:<36}  {:<8}  {}", note.id, note.note_type, note.title);
+        }
+    }
+    Ok(())
+}
+
+fn handle_article_command(
+    store: &Store,
+    action: ArticleCommands,
+    db_path: &PathBuf,
+    timeout: Duration,
+    yes: bool,
+    style: Style,
+) -> anyhow::Result<()> {
+    match action {
+        ArticleCommands::Review { page_size } => {
+            handle_review_dashboard(store, db_path, page_size.max(1), timeout, style)?;
+        }
+        ArticleCommands::Add {
+            urls: Some(list),
+            jobs,
+            room,
+            save_failures,
+            ..
+        } => add_articles_from_list(store, &list, room.as_deref(), jobs, save_failures, timeout)?,
+        ArticleCommands::Add {
+            url,
+            file,
+            room,
+            save_failures,
+            ..
+        } => {
+            let url = match (url, &file) {
+                (Some(url), _) => url,
+                (None, Some(path)) => format!("file://{}", std::fs::canonicalize(path)?.display()),
+                (None, None) => {
+                    anyhow::bail!(CliError::InvalidInput("Provide a URL or --file".to_string()))
+                }
+            };
 
-            // Parse metadata if available
-            if let Some(metadata) = json.get("metadata") {
-                let mut source_metadata = minmind_core::SourceMetadata::default();
-                if let Some(author) = metadata["author"].as_str() {
-                    source_metadata.author = Some(author.to_string());
+            // Check if article already exists
+            if let Some(existing) = store.get_article_by_url(&url)? {
+                if existing.last_error.is_some() {
+                    anyhow::bail!(
+                        "Article already exists: {} (its last fetch failed; retry with: \
+                         mm article refetch {})",
+                        url,
+                        existing.id
+                    );
                 }
-                if let Some(site) = metadata["site_name"].as_str() {
-                    source_metadata.site_name = Some(site.to_string());
+                anyhow::bail!("Article already exists: {}", url);
+            }
+            if let Some(path) = &file {
+                if !is_markdown_file(path) && !is_html_file(path) {
+                    anyhow::bail!(
+                        "Unsupported file type: {} (expected .html or .md)",
+                        path.display()
+                    );
                 }
-                if let Some(desc) = metadata["description"].as_str() {
-                    source_metadata.description = Some(desc.to_string());
+            }
+            let room_id = room.map(|room| find_room_id(store, &room)).transpose()?;
+
+            let json = match extract_article_json(&url, file.as_deref(), timeout) {
+                Ok(json) => json,
+                Err(err) if save_failures => {
+                    let stub = save_failed_fetch(store, &url, room_id, &err)?;
+                    println!("Saved {} to retry later with: mm article refetch {}", url, stub.id);
+                    return Err(err);
                 }
-                article = article.with_metadata(source_metadata);
+                Err(err) => return Err(err),
+            };
+
+            let mut article = article_from_extracted(&url, &json);
+            if let Some(room_id) = room_id {
+                article = article.with_room(room_id);
             }
 
             store.create_article(&article)?;
-            println!("Added article: {} ({})", title, article.id);
+            println!("Added article: {} ({})", article.title, article.id);
         }
-        ArticleCommands::List { status } => {
-            let articles = match Option::<ArticleStatus>::from(status) {
-                Some(s) => store.list_articles_by_status(s)?,
-                None => store.list_articles()?,
+        ArticleCommands::List {
+            status,
+            room,
+            unread,
+            since,
+            until,
+        } => {
+            let status = Option::<ArticleStatus>::from(status);
+            let mut articles = match (&room, status) {
+                (Some(room), status) => {
+                    store.list_articles_in_room(find_room_id(store, room)?, status)?
+                }
+                (None, Some(s)) => store.list_articles_by_status(s)?,
+                (None, None) => store.list_articles()?,
             };
+            if unread {
+                articles.retain(|article| !article.is_read());
+            }
+            articles.retain(|article| within(article.updated_at, since, until));
 
-            if articles.is_empty() {
+            if articles.is_empty() && unread {
+                println!("No unread articles.");
+            } else if articles.is_empty() && room.is_some() {
+                println!("No articles in that room.");
+            } else if articles.is_empty() && (since.is_some() || until.is_some()) {
+                println!("No articles in that time range.");
+            } else if articles.is_empty() {
                 println!("No articles found. Add one with: mm article add <url>");
             } else {
-                println!("{:<12}  {:<12}  {}", "ID", "STATUS", "TITLE");
-                println!("{}", "-".repeat(80));
+                let all_ids = store.article_ids()?;
+                println!("{:<12}  {:<12}  {:<4}  TITLE", "ID", "STATUS", "READ");
+                println!("{}", "-".repeat(86));
                 for article in articles {
-                    // Show short ID for easier typing
-                    let short_id = &article.id.to_string()[..8];
-                    let title = if article.title.len() > 50 {
-                        format!("{}...", &article.title[..47])
-                    } else {
-                        article.title.clone()
+                    // Show the shortest unambiguous ID for easier typing
+                    let short_id = store.shortest_unique_prefix(article.id, &all_ids);
+                    let title = truncate_string(&article.title, 50);
+                    let title = match &article.last_error {
+                        Some(_) => {
+                            format!("{} {} (fetch failed)", style.icon(Icon::Warning), title)
+                        }
+                        None => title,
                     };
-                    println!("{:<12}  {:<12}  {}", short_id, article.status, title);
+                    let read = if article.is_read() { style.icon(Icon::Success) } else { "" };
+                    let status = article.status.to_string();
+                    println!("{:<12}  {:<12}  {:<4}  {}", short_id, status, read, title);
                 }
             }
         }
-        ArticleCommands::Show { id } => {
+        ArticleCommands::Read { id } => {
+            let mut article = find_article(store, &id)?;
+            if let Some(read_at) = article.read_at {
+                println!("Already read on {}: {}", read_at.format("%Y-%m-%d"), article.title);
+            } else {
+                article.mark_read();
+                store.update_article(&article)?;
+                println!("Marked as read: {}", article.title);
+            }
+        }
+        ArticleCommands::Show { id, style } => {
             let article = find_article(store, &id)?;
+            let style = style.trim().to_lowercase();
+            let summary = article.summary_in(&style);
+            if summary.is_none() && style != DEFAULT_SUMMARY_STYLE {
+                anyhow::bail!(CliError::NotFound(format!(
+                    "No {} summary yet; make one with: mm article summarize {} --style {}",
+                    style, id, style
+                )));
+            }
             println!("Title: {}", article.title);
             println!("URL: {}", article.url);
             println!("Status: {}", article.status);
@@ -504,46 +1765,127 @@ fn handle_article_command(store: &Store, action: ArticleCommands, db_path: &Path
                     println!("Room: {}", room.name);
                 }
             }
+            if !article.source_metadata.authors.is_empty() {
+                println!("Authors: {}", article.source_metadata.authors.join(", "));
+            }
             println!("Created: {}", article.created_at);
+            if let Some(read_at) = article.read_at {
+                println!("Read: {}", read_at);
+            }
+            if let Some(error) = &article.last_error {
+                println!("Last fetch failed: {}", error);
+                println!("Retry with: mm article refetch {}", id);
+            }
+            
+            if let Some(usage) = &article.summary_usage {
+                println!("Summary usage: {}", format_usage(usage));
+            }
             
-            if let Some(summary) = &article.summary {
-                println!("\n--- Summary ---\n");
+            if article.summaries.len() > 1 {
+                let styles: Vec<&str> = article.summaries.keys().map(String::as_str).collect();
+                println!("Summary styles: {}", styles.join(", "));
+            }
+
+            if let Some(summary) = summary {
+                match style.as_str() {
+                    DEFAULT_SUMMARY_STYLE => println!("\n--- Summary ---\n"),
+                    style => println!("\n--- Summary ({}) ---\n", style),
+                }
                 println!("{}", summary);
             }
             
-            println!("\n--- Content (first 500 chars) ---\n");
-            let preview = if article.raw_content.len() > 500 {
-                format!("{}...", &article.raw_content[..500])
+            if article.content_purged {
+                println!("\nContent was purged to save space. Re-fetch it from: {}", article.url);
             } else {
-                article.raw_content.clone()
-            };
-            println!("{}", preview);
+                println!("\n--- Content (first 500 chars) ---\n");
+                let preview = if article.raw_content.len() > 500 {
+                    format!("{}...", &article.raw_content[..500])
+                } else {
+                    article.raw_content.clone()
+                };
+                println!("{}", preview);
+            }
         }
-        ArticleCommands::Summarize { id, provider } => {
+        ArticleCommands::Summarize {
+            id,
+            provider,
+            genius,
+            model,
+            style,
+            prompt,
+        } => {
+            let style = style.trim().to_lowercase();
+            let style_instruction = summary_style_instruction(&style)?;
             let mut article = find_article(store, &id)?;
+            if article.content_purged {
+                anyhow::bail!(
+                    "Article content was purged; re-fetch it from {} before summarizing",
+                    article.url
+                );
+            }
+            if let Some(reason) = missing_content(&article) {
+                anyhow::bail!(CliError::InvalidInput(reason));
+            }
             
-            if article.summary.is_some() {
-                println!("Article already has a summary. Regenerating...");
+            if article.summary_in(&style).is_some() {
+                println!("Article already has a {} summary. Regenerating...", style);
             }
 
-            // Get the active summary config
-            let config = store.get_active_summary_config(article.room_id)?;
-            let prompt = config.map(|c| c.system_prompt).unwrap_or_else(|| DEFAULT_SUMMARY_PROMPT.to_string());
+            let resolved = store.resolve_summary_prompt(article.room_id, prompt.as_deref())?;
 
-            println!("Summarizing with {}...", provider);
+            // An explicit --genius or --provider wins over the config's genius
+            let genius = match (genius, &provider) {
+                (Some(genius), _) => Some(find_genius(store, &genius)?),
+                (None, None) => match resolved.genius_id {
+                    Some(genius_id) => store.get_genius(genius_id)?,
+                    None => None,
+                },
+                (None, Some(_)) => None,
+            };
+            let provider: Provider = match (&genius, provider) {
+                (Some(genius), _) => genius.provider,
+                (None, Some(provider)) => provider.parse().map_err(CliError::InvalidInput)?,
+                (None, None) => Provider::Anthropic,
+            };
+            if provider == Provider::Custom {
+                anyhow::bail!(CliError::InvalidInput(
+                    "Summarization supports anthropic, openai and ollama providers".to_string()
+                ));
+            }
+            require_api_key(provider)?;
+            let model = model.or_else(|| genius.as_ref().map(|g| g.model.clone()));
+            if let Some(model) = &model {
+                warn_on_unlikely_model(provider, model);
+            }
+
+            let mut prompt = resolved.prompt;
+            if let Some(instruction) = style_instruction {
+                prompt = format!("{}\n\n{}", prompt, instruction);
+            }
+
+            match &genius {
+                Some(genius) => println!("Summarizing with {} ({})...", genius.name, provider),
+                None => println!("Summarizing with {}...", provider),
+            }
+            println!("Using {}", resolved.source);
             
             // Call Python to summarize
-            let output = Command::new("python")
-                .args([
-                    "-m", "minmind.cli", 
-                    "summarize",
-                    "--provider", &provider,
-                    "--prompt", &prompt,
-                    &article.id.to_string(),
-                ])
-                .current_dir(find_python_dir()?)
-                .env("MINMIND_DB", db_path)
-                .output()?;
+            let mut command = Command::new("python");
+            command.args([
+                "-m", "minmind.cli", 
+                "summarize",
+                "--provider", &provider.to_string(),
+                "--prompt", &prompt,
+                &article.id.to_string(),
+            ]);
+            if let Some(model) = &model {
+                command.args(["--model", model]);
+            }
+            if let Some(genius) = &genius {
+                add_genius_sampling_args(&mut command, genius);
+            }
+            command.current_dir(find_python_dir()?).env("MINMIND_DB", db_path);
+            let output = output_with_timeout(&mut command, timeout)?;
 
             if !output.status.success() {
                 let stderr = String::from_utf8_lossy(&output.stderr);
@@ -553,13 +1895,24 @@ fn handle_article_command(store: &Store, action: ArticleCommands, db_path: &Path
             let result: serde_json::Value = serde_json::from_slice(&output.stdout)?;
             let summary = result["summary"].as_str().unwrap_or("");
             
-            article.set_summary(summary);
-            store.update_article(&article)?;
+            match parse_summary_usage(&result) {
+                Some(usage) if style == DEFAULT_SUMMARY_STYLE => {
+                    article.set_summary_with_usage(summary, usage)
+                }
+                _ => article.set_styled_summary(&style, summary),
+            }
+            store.update_article_with_source(&article, audit_sources::SUMMARIZER)?;
 
             println!("\n--- Summary ---\n");
             println!("{}", summary);
         }
-        ArticleCommands::Approve { id, room } => {
+        ArticleCommands::Approve {
+            id,
+            room,
+            note_type,
+            summary_only,
+            template,
+        } => {
             let mut article = find_article(store, &id)?;
             
             // Determine target room
@@ -568,24 +1921,23 @@ fn handle_article_command(store: &Store, action: ArticleCommands, db_path: &Path
             } else if let Some(rid) = article.room_id {
                 rid
             } else {
-                anyhow::bail!("No room specified. Use --room or assign article to a room first.");
+                anyhow::bail!(CliError::InvalidInput(
+                    "No room specified. Use --room or assign article to a room first.".to_string()
+                ));
             };
 
-            // Create a note from the article
-            let content = if let Some(summary) = &article.summary {
-                format!(
-                    "## Summary\n\n{}\n\n## Source\n\n{}\n\n## Full Content\n\n{}",
-                    summary, article.url, article.raw_content
-                )
-            } else {
-                format!(
-                    "## Source\n\n{}\n\n## Content\n\n{}",
-                    article.url, article.raw_content
-                )
+            let mut options = ArticleNoteOptions {
+                summary_only,
+                template: None,
             };
+            if let Some(name) = template {
+                let template = store.get_template(&name)?.ok_or_else(|| {
+                    CliError::NotFound(format!("Template not found: {}", name))
+                })?;
+                options = options.with_template(template);
+            }
 
-            let note = Note::new(target_room_id, &article.title, NoteType::Reference)
-                .with_content(content);
+            let note = article.to_note(target_room_id, note_type, &options);
             store.create_note(&note)?;
 
             article.mark_reviewed();
@@ -599,62 +1951,142 @@ fn handle_article_command(store: &Store, action: ArticleCommands, db_path: &Path
             store.update_article(&article)?;
             println!("Archived article: {}", article.title);
         }
+        ArticleCommands::Refetch { id } => {
+            let mut article = find_article(store, &id)?;
+            let file = article.url.strip_prefix("file://").map(PathBuf::from);
+            match extract_article_json(&article.url, file.as_deref(), timeout) {
+                Ok(json) => {
+                    let fetched = article_from_extracted(&article.url, &json);
+                    article.refetched(fetched);
+                    store.update_article(&article)?;
+                    println!("Refetched article: {}", article.title);
+                }
+                Err(err) => {
+                    article.record_fetch_error(format!("{:#}", err));
+                    store.update_article(&article)?;
+                    return Err(err);
+                }
+            }
+        }
+        ArticleCommands::Requeue { id } => {
+            let mut article = find_article(store, &id)?;
+            article.requeue();
+            store.update_article(&article)?;
+            println!("Requeued article: {}", article.title);
+        }
         ArticleCommands::Delete { id } => {
             let article = find_article(store, &id)?;
+            if !confirm(&format!("Delete article '{}'?", article.title), yes)? {
+                println!("Kept article: {}", article.title);
+                return Ok(());
+            }
             store.delete_article(article.id)?;
             println!("Deleted article: {}", article.title);
         }
+        ArticleCommands::Cleanup {
+            older_than,
+            drop_content,
+        } => {
+            let cutoff = chrono::Utc::now() - older_than;
+            // Purge before archiving, which bumps updated_at past the cutoff
+            if drop_content {
+                let purged = store.purge_article_content_before(cutoff)?;
+                println!("Dropped content of {} article(s)", purged);
+            }
+            let archived = store.archive_reviewed_before(cutoff)?;
+            println!("Archived {} reviewed article(s)", archived);
+        }
+        ArticleCommands::History { id } => {
+            let article = find_article(store, &id)?;
+            let trail = store.audit_trail(article.id)?;
+            println!("History for: {}\n", article.title);
+            if trail.is_empty() {
+                println!("No recorded status changes.");
+            } else {
+                println!("{:<25}  {:<12}  {:<12}  SOURCE", "WHEN", "FROM", "TO");
+                println!("{}", "-".repeat(70));
+                for entry in trail {
+                    println!(
+                        "{:<25}  {:<12}  {:<12}  {}",
+                        entry.at.format("%Y-%m-%d %H:%M:%S UTC"),
+                        entry.from_status.as_deref().unwrap_or("-"),
+                        entry.to_status,
+                        entry.source
+                    );
+                }
+            }
+        }
     }
     Ok(())
 }
 
-fn handle_review_dashboard(store: &Store, db_path: &PathBuf) -> anyhow::Result<()> {
+fn handle_review_dashboard(
+    store: &Store,
+    db_path: &PathBuf,
+    page_size: usize,
+    timeout: Duration,
+    style: Style,
+) -> anyhow::Result<()> {
     use std::io::{self, Write};
 
-    println!("\n╔══════════════════════════════════════════════════════════════╗");
-    println!("║           MinMind Article Review Dashboard                    ║");
-    println!("╚══════════════════════════════════════════════════════════════╝\n");
+    let title = format!("{:^62}", "MinMind Article Review Dashboard");
+    println!("\n{}\n", style.boxed(&[vec![title]]));
+
+    let mut sort = DashboardSort::Status;
+    let mut page = 0;
 
     loop {
-        // Get articles ready for review (summarized or pending)
-        let mut articles: Vec<_> = store.list_articles_by_status(ArticleStatus::Summarized)?;
-        let pending = store.list_articles_by_status(ArticleStatus::Pending)?;
-        
-        let summarized_count = articles.len();
-        let pending_count = pending.len();
-        
-        articles.extend(pending);
-        
+        // Articles ready for review, leaving out stubs whose fetch failed. The
+        // header counts come from the same single query, so they can't drift
+        // from the list even if an article is summarized meanwhile.
+        let mut articles =
+            store.list_articles_by_statuses(&[ArticleStatus::Summarized, ArticleStatus::Pending])?;
+        articles.retain(|article| article.last_error.is_none());
+        let summarized_count =
+            articles.iter().filter(|a| a.status == ArticleStatus::Summarized).count();
+        let pending_count = articles.len() - summarized_count;
+
         if articles.is_empty() {
             println!("No articles to review. Add some with: mm article add <url>");
             break;
         }
+        sort.apply(&mut articles);
+
+        // Re-slice the current page, clamping it if articles went away
+        let page_count = articles.len().div_ceil(page_size);
+        page = page.min(page_count - 1);
+        let start = page * page_size;
+        let visible = &articles[start..(start + page_size).min(articles.len())];
 
-        println!("┌────────────────────────────────────────────────────────────────┐");
-        println!("│ {} summarized, {} pending review                              │", 
-            summarized_count, pending_count);
-        println!("└────────────────────────────────────────────────────────────────┘\n");
+        let counts = format!("{} summarized, {} pending review", summarized_count, pending_count);
+        println!("{}\n", style.boxed(&[vec![counts]]));
 
         // Show article list
-        for (i, article) in articles.iter().enumerate() {
+        for (i, article) in visible.iter().enumerate() {
             let status_icon = match article.status {
-                ArticleStatus::Pending => "⏳",
-                ArticleStatus::Summarized => "📝",
-                _ => "  ",
-            };
-            let title = if article.title.len() > 50 {
-                format!("{}...", &article.title[..47])
-            } else {
-                article.title.clone()
+                ArticleStatus::Pending => style.icon(Icon::Pending),
+                ArticleStatus::Summarized => style.icon(Icon::Summary),
+                _ if style.is_fancy() => "  ",
+                _ => " ",
             };
+            let title = truncate_string(&article.title, 50);
             println!("  {} [{}] {}", status_icon, i + 1, title);
         }
+        println!(
+            "\n  Page {} of {} (sorted by {})",
+            page + 1,
+            page_count,
+            sort.label()
+        );
 
         println!("\n  Commands:");
         println!("    [n]    - View article n (e.g., '1' for first article)");
         println!("    [s n]  - Summarize article n");
         println!("    [a n]  - Approve article n (creates Note)");
         println!("    [x n]  - Archive article n");
+        println!("    [next] - Show the next page");
+        println!("    [prev] - Show the previous page");
+        println!("    [sort k] - Sort by date, title or status");
         println!("    [q]    - Quit dashboard\n");
 
         print!("  > ");
@@ -673,72 +2105,125 @@ fn handle_review_dashboard(store: &Store, db_path: &PathBuf) -> anyhow::Result<(
         let parts: Vec<&str> = input.split_whitespace().collect();
         
         match parts.as_slice() {
+            // Page navigation
+            ["next"] => {
+                if page + 1 < page_count {
+                    page += 1;
+                } else {
+                    println!("\n  {} Already on the last page\n", style.icon(Icon::Warning));
+                }
+            }
+            ["prev"] => {
+                if page > 0 {
+                    page -= 1;
+                } else {
+                    println!("\n  {} Already on the first page\n", style.icon(Icon::Warning));
+                }
+            }
+            // Sort order
+            ["sort", key] => match key.parse::<DashboardSort>() {
+                Ok(new_sort) => {
+                    sort = new_sort;
+                    page = 0;
+                }
+                Err(e) => println!("\n  {} {}\n", style.icon(Icon::Warning), e),
+            },
             // View article by number
             [num] if num.parse::<usize>().is_ok() => {
                 let idx = num.parse::<usize>().unwrap();
-                if idx == 0 || idx > articles.len() {
-                    println!("\n  ⚠ Invalid article number\n");
+                if idx == 0 || idx > visible.len() {
+                    println!("\n  {} Invalid article number\n", style.icon(Icon::Warning));
                     continue;
                 }
-                let article = &articles[idx - 1];
-                show_article_detail(article, store)?;
+                let article = &visible[idx - 1];
+                show_article_detail(article, store, style)?;
             }
             // Summarize
             ["s", num] | ["summarize", num] => {
                 let idx = num.parse::<usize>().unwrap_or(0);
-                if idx == 0 || idx > articles.len() {
-                    println!("\n  ⚠ Invalid article number\n");
+                if idx == 0 || idx > visible.len() {
+                    println!("\n  {} Invalid article number\n", style.icon(Icon::Warning));
                     continue;
                 }
-                let article = &articles[idx - 1];
-                println!("\n  Summarizing '{}'...", article.title);
-                
-                // Get summary config
-                let config = store.get_active_summary_config(article.room_id)?;
-                let prompt = config.map(|c| c.system_prompt).unwrap_or_else(|| DEFAULT_SUMMARY_PROMPT.to_string());
-
-                let output = Command::new("python")
-                    .args([
-                        "-m", "minmind.cli",
-                        "summarize",
-                        "--provider", "anthropic",
-                        "--prompt", &prompt,
-                        &article.id.to_string(),
-                    ])
-                    .current_dir(find_python_dir()?)
-                    .env("MINMIND_DB", db_path)
-                    .output()?;
+                let article = &visible[idx - 1];
+                if let Some(reason) = missing_content(article) {
+                    println!("\n  {} {}\n", style.icon(Icon::Warning), reason);
+                    continue;
+                }
+                // Use the genius bound to the room or global config, as
+                // `mm article summarize` does
+                let resolved = store.resolve_summary_prompt(article.room_id, None)?;
+                let genius = match resolved.genius_id {
+                    Some(genius_id) => store.get_genius(genius_id)?,
+                    None => None,
+                };
+                let provider = genius.as_ref().map_or(Provider::Anthropic, |g| g.provider);
+                if provider == Provider::Custom {
+                    let icon = style.icon(Icon::Failure);
+                    println!("\n  {} Summarization doesn't support custom providers\n", icon);
+                    continue;
+                }
+                if let Err(err) = require_api_key(provider) {
+                    println!("\n  {} Failed to summarize: {}\n", style.icon(Icon::Failure), err);
+                    continue;
+                }
+                println!("\n  Summarizing '{}' with {}...", article.title, provider);
+
+                let mut command = Command::new("python");
+                command.args([
+                    "-m", "minmind.cli",
+                    "summarize",
+                    "--provider", &provider.to_string(),
+                    "--prompt", &resolved.prompt,
+                    &article.id.to_string(),
+                ]);
+                if let Some(genius) = &genius {
+                    command.args(["--model", &genius.model]);
+                    add_genius_sampling_args(&mut command, genius);
+                }
+                command.current_dir(find_python_dir()?).env("MINMIND_DB", db_path);
+                let output = match output_with_timeout(&mut command, timeout) {
+                    Ok(output) => output,
+                    Err(err) => {
+                        let icon = style.icon(Icon::Failure);
+                        println!("\n  {} Failed to summarize: {}\n", icon, err);
+                        continue;
+                    }
+                };
 
                 if output.status.success() {
                     let result: serde_json::Value = serde_json::from_slice(&output.stdout)?;
                     let summary = result["summary"].as_str().unwrap_or("");
                     
                     let mut updated_article = article.clone();
-                    updated_article.set_summary(summary);
-                    store.update_article(&updated_article)?;
+                    match parse_summary_usage(&result) {
+                        Some(usage) => updated_article.set_summary_with_usage(summary, usage),
+                        None => updated_article.set_summary(summary),
+                    }
+                    store.update_article_with_source(&updated_article, audit_sources::SUMMARIZER)?;
                     
-                    println!("\n  ✓ Summary generated!\n");
-                    println!("  ─────────────────────────────────────────────");
+                    println!("\n  {} Summary generated!\n", style.icon(Icon::Success));
+                    println!("  {}", style.rule(45));
                     for line in summary.lines().take(10) {
                         println!("  {}", line);
                     }
                     if summary.lines().count() > 10 {
                         println!("  ...(truncated)");
                     }
-                    println!("  ─────────────────────────────────────────────\n");
+                    println!("  {}\n", style.rule(45));
                 } else {
                     let stderr = String::from_utf8_lossy(&output.stderr);
-                    println!("\n  ✗ Failed to summarize: {}\n", stderr);
+                    println!("\n  {} Failed to summarize: {}\n", style.icon(Icon::Failure), stderr);
                 }
             }
             // Approve
             ["a", num] | ["approve", num] => {
                 let idx = num.parse::<usize>().unwrap_or(0);
-                if idx == 0 || idx > articles.len() {
-                    println!("\n  ⚠ Invalid article number\n");
+                if idx == 0 || idx > visible.len() {
+                    println!("\n  {} Invalid article number\n", style.icon(Icon::Warning));
                     continue;
                 }
-                let article = &articles[idx - 1];
+                let article = &visible[idx - 1];
                 
                 // Check if article has a room
                 let room_id = if let Some(rid) = article.room_id {
@@ -747,7 +2232,10 @@ fn handle_review_dashboard(store: &Store, db_path: &PathBuf) -> anyhow::Result<(
                     // Ask for room
                     let rooms = store.list_rooms()?;
                     if rooms.is_empty() {
-                        println!("\n  ⚠ No rooms available. Create one first: mm room create <name>\n");
+                        println!(
+                            "\n  {} No rooms available. Create one first: mm room create <name>\n",
+                            style.icon(Icon::Warning)
+                        );
                         continue;
                     }
                     println!("\n  Select a room for this note:");
@@ -762,52 +2250,43 @@ fn handle_review_dashboard(store: &Store, db_path: &PathBuf) -> anyhow::Result<(
                     let room_idx = room_input.trim().parse::<usize>().unwrap_or(0);
                     
                     if room_idx == 0 || room_idx > rooms.len() {
-                        println!("\n  ⚠ Invalid room number\n");
+                        println!("\n  {} Invalid room number\n", style.icon(Icon::Warning));
                         continue;
                     }
                     rooms[room_idx - 1].id
                 };
 
-                // Create note from article
-                let content = if let Some(summary) = &article.summary {
-                    format!(
-                        "## Summary\n\n{}\n\n## Source\n\n{}\n\n## Full Content\n\n{}",
-                        summary, article.url, article.raw_content
-                    )
-                } else {
-                    format!(
-                        "## Source\n\n{}\n\n## Content\n\n{}",
-                        article.url, article.raw_content
-                    )
-                };
-
-                let note = Note::new(room_id, &article.title, NoteType::Reference)
-                    .with_content(content);
+                let options = ArticleNoteOptions::default();
+                let note = article.to_note(room_id, NoteType::Reference, &options);
                 store.create_note(&note)?;
 
                 let mut updated_article = article.clone();
                 updated_article.mark_reviewed();
-                store.update_article(&updated_article)?;
+                store.update_article_with_source(&updated_article, audit_sources::DASHBOARD)?;
 
-                println!("\n  ✓ Approved! Created note: {}\n", &note.id.to_string()[..8]);
+                println!(
+                    "\n  {} Approved! Created note: {}\n",
+                    style.icon(Icon::Success),
+                    &note.id.to_string()[..8]
+                );
             }
             // Archive
             ["x", num] | ["archive", num] => {
                 let idx = num.parse::<usize>().unwrap_or(0);
-                if idx == 0 || idx > articles.len() {
-                    println!("\n  ⚠ Invalid article number\n");
+                if idx == 0 || idx > visible.len() {
+                    println!("\n  {} Invalid article number\n", style.icon(Icon::Warning));
                     continue;
                 }
-                let article = &articles[idx - 1];
+                let article = &visible[idx - 1];
                 
                 let mut updated_article = article.clone();
                 updated_article.archive();
-                store.update_article(&updated_article)?;
+                store.update_article_with_source(&updated_article, audit_sources::DASHBOARD)?;
                 
-                println!("\n  ✓ Archived: {}\n", article.title);
+                println!("\n  {} Archived: {}\n", style.icon(Icon::Success), article.title);
             }
             _ => {
-                println!("\n  ⚠ Unknown command. Try 'q' to quit.\n");
+                println!("\n  {} Unknown command. Try 'q' to quit.\n", style.icon(Icon::Warning));
             }
         }
     }
@@ -815,32 +2294,93 @@ fn handle_review_dashboard(store: &Store, db_path: &PathBuf) -> anyhow::Result<(
     Ok(())
 }
 
-fn show_article_detail(article: &Article, store: &Store) -> anyhow::Result<()> {
-    println!("\n┌────────────────────────────────────────────────────────────────┐");
-    println!("│ {}", truncate_string(&article.title, 60));
-    println!("├────────────────────────────────────────────────────────────────┤");
-    println!("│ URL: {}", truncate_string(&article.url, 55));
-    println!("│ Status: {:?}", article.status);
+/// Ordering of the review dashboard's article list
+#[derive(Clone, Copy)]
+enum DashboardSort {
+    /// Summarized first, then pending, each most recently updated first
+    Status,
+    /// Most recently updated first
+    Date,
+    /// Alphabetical by title
+    Title,
+}
+
+impl DashboardSort {
+    fn apply(self, articles: &mut [Article]) {
+        match self {
+            // Articles are fetched in status order already
+            DashboardSort::Status => {}
+            DashboardSort::Date => articles.sort_by_key(|a| std::cmp::Reverse(a.updated_at)),
+            DashboardSort::Title => articles.sort_by_cached_key(|a| a.title.to_lowercase()),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            DashboardSort::Status => "status",
+            DashboardSort::Date => "date",
+            DashboardSort::Title => "title",
+        }
+    }
+}
+
+impl std::str::FromStr for DashboardSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "status" => Ok(DashboardSort::Status),
+            "date" => Ok(DashboardSort::Date),
+            "title" => Ok(DashboardSort::Title),
+            _ => Err(format!("Unknown sort '{}' (use date, title or status)", s)),
+        }
+    }
+}
+
+fn show_article_detail(article: &Article, store: &Store, style: Style) -> anyhow::Result<()> {
+    let mut details = vec![
+        format!("URL: {}", truncate_string(&article.url, 55)),
+        format!("Status: {:?}", article.status),
+    ];
     if let Some(room_id) = article.room_id {
         if let Some(room) = store.get_room(room_id)? {
-            println!("│ Room: {}", room.name);
+            details.push(format!("Room: {}", room.name));
+        }
+    }
+    let title = vec![truncate_string(&article.title, 60)];
+    println!("\n{}", style.boxed(&[title, details]));
+
+    let similar = store.find_similar_articles(article.id)?;
+    if !similar.is_empty() {
+        let metadata = &article.source_metadata;
+        let same_site = |a: &Article| match (&a.source_metadata.site_name, &metadata.site_name) {
+            (Some(theirs), Some(ours)) => theirs.trim().eq_ignore_ascii_case(ours.trim()),
+            _ => false,
+        };
+        let from = match (&metadata.site_name, &metadata.author) {
+            (Some(site), _) if similar.iter().any(same_site) => site.as_str(),
+            (_, Some(author)) => author.as_str(),
+            _ => "the same source",
+        };
+        println!("\n  {} See {} more from {}:", style.icon(Icon::Link), similar.len(), from);
+        for other in similar.iter().take(5) {
+            println!("     [{}] {}", other.status, truncate_string(&other.title, 50));
         }
     }
-    println!("└────────────────────────────────────────────────────────────────┘");
     
     if let Some(summary) = &article.summary {
-        println!("\n  📝 Summary:");
-        println!("  ─────────────────────────────────────────────");
+        println!("\n  {} Summary:", style.icon(Icon::Summary));
+        println!("  {}", style.rule(45));
         for line in summary.lines() {
             println!("  {}", line);
         }
-        println!("  ─────────────────────────────────────────────");
+        println!("  {}", style.rule(45));
     } else {
-        println!("\n  ⏳ No summary yet. Use 's <n>' to summarize.");
+        println!("\n  {} No summary yet. Use 's <n>' to summarize.", style.icon(Icon::Pending));
     }
     
-    println!("\n  📄 Content Preview (first 500 chars):");
-    println!("  ─────────────────────────────────────────────");
+    println!("\n  {} Content Preview (first 500 chars):", style.icon(Icon::Preview));
+    println!("  {}", style.rule(45));
     let preview = if article.raw_content.len() > 500 {
         format!("{}...", &article.raw_content[..500])
     } else {
@@ -849,76 +2389,663 @@ fn show_article_detail(article: &Article, store: &Store) -> anyhow::Result<()> {
     for line in preview.lines().take(15) {
         println!("  {}", line);
     }
-    println!("  ─────────────────────────────────────────────\n");
+    println!("  {}\n", style.rule(45));
     
     Ok(())
 }
 
-fn truncate_string(s: &str, max_len: usize) -> String {
-    if s.len() > max_len {
-        format!("{}...", &s[..max_len - 3])
-    } else {
-        s.to_string()
-    }
-}
-
-fn handle_todo_command(store: &Store, action: TodoCommands) -> anyhow::Result<()> {
-    match action {
-        TodoCommands::List { status, plan } => {
-            let actions = match (Option::<ActionStatus>::from(status), plan) {
-                (Some(s), Some(p)) => {
-                    // Filter by both status and plan
-                    store
-                        .list_user_actions_by_source(&p)?
-                        .into_iter()
-                        .filter(|a| a.status == s)
-                        .collect()
-                }
-                (Some(s), None) => store.list_user_actions_by_status(s)?,
-                (None, Some(p)) => store.list_user_actions_by_source(&p)?,
-                (None, None) => store.list_user_actions()?,
-            };
+/// Run the Python extractor and parse its JSON output
+fn run_extractor(args: &[&str], timeout: Duration) -> anyhow::Result<serde_json::Value> {
+    let mut command = Command::new("python");
+    command.args(["-m", "minmind.cli"]).args(args).current_dir(find_python_dir()?);
+    let output = output_with_timeout(&mut command, timeout)?;
 
-            if actions.is_empty() {
-                println!("No todos found. Sync from plans with: mm todo sync");
-            } else {
-                println!(
-                    "{:<12}  {:<12}  {:<30}  {}",
-                    "ID", "STATUS", "SOURCE", "TITLE"
-                );
-                println!("{}", "-".repeat(90));
-                for action in actions {
-                    let short_id = &action.id.to_string()[..8];
-                    let source = action
-                        .source_file
-                        .as_deref()
-                        .unwrap_or("-")
-                        .trim_start_matches("plans/");
-                    let source = if source.len() > 28 {
-                        format!("{}...", &source[..25])
-                    } else {
-                        source.to_string()
-                    };
-                    let title = if action.title.len() > 40 {
-                        format!("{}...", &action.title[..37])
-                    } else {
-                        action.title.clone()
-                    };
-                    println!(
-                        "{:<12}  {:<12}  {:<30}  {}",
-                        short_id, action.status, source, title
-                    );
-                }
+    if !output.status.success() {
+        if let Some(err) = ExtractError::from_output(&output.stdout) {
+            match err.advice() {
+                Some(advice) => anyhow::bail!("Failed to extract article: {}\n{}", err, advice),
+                None => anyhow::bail!("Failed to extract article: {}", err),
             }
         }
-        TodoCommands::Sync { dir } => {
-            let plans_dir = expand_path(&dir);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Failed to extract article: {}", stderr);
+    }
 
-            if !plans_dir.exists() {
-                anyhow::bail!("Plans directory not found: {}", plans_dir.display());
-            }
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
 
-            println!("Scanning {} for [USER] markers...", plans_dir.display());
+/// The extractor's JSON for an article: read from `file` (a saved .html page
+/// or a .md file) when given, otherwise fetched from `url`
+fn extract_article_json(
+    url: &str,
+    file: Option<&Path>,
+    timeout: Duration,
+) -> anyhow::Result<serde_json::Value> {
+    match file {
+        Some(path) if is_markdown_file(path) => markdown_article_json(path),
+        Some(path) => {
+            println!("Extracting article from {}...", path.display());
+            let path = absolute_path(path)?;
+            run_extractor(&["extract", url, "--file", &path.to_string_lossy()], timeout)
+        }
+        None => {
+            println!("Fetching article from {}...", url);
+            run_extractor(&["extract", url], timeout)
+        }
+    }
+}
+
+/// `path` resolved against the current directory, for handing to the extractor,
+/// which runs from the Python directory
+fn absolute_path(path: &Path) -> std::io::Result<PathBuf> {
+    if path.is_absolute() {
+        Ok(path.to_path_buf())
+    } else {
+        Ok(std::env::current_dir()?.join(path))
+    }
+}
+
+/// Why an article has nothing to summarize, if it doesn't: a stub saved from
+/// a failed fetch, or one whose content came back empty
+fn missing_content(article: &Article) -> Option<String> {
+    if article.last_error.is_none() && !article.raw_content.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "Article has no content to summarize; fetch it with: mm article refetch {}",
+        &article.id.to_string()[..8]
+    ))
+}
+
+/// Keep a URL that couldn't be fetched as an article stub holding the error,
+/// so it shows up in the list and can be refetched
+fn save_failed_fetch(
+    store: &Store,
+    url: &str,
+    room_id: Option<uuid::Uuid>,
+    err: &anyhow::Error,
+) -> anyhow::Result<Article> {
+    let mut article = Article::failed_fetch(url, format!("{:#}", err));
+    if let Some(room_id) = room_id {
+        article = article.with_room(room_id);
+    }
+    store.create_article(&article)?;
+    Ok(article)
+}
+
+/// Build an Article from the extractor's JSON output
+fn article_from_extracted(url: &str, json: &serde_json::Value) -> Article {
+    let title = json["title"].as_str().unwrap_or("Untitled");
+    let content = json["content"].as_str().unwrap_or("");
+    let mut article = Article::new(url, title, content);
+
+    if let Some(metadata) = json.get("metadata") {
+        let mut source_metadata = minmind_core::SourceMetadata::default();
+        let authors: Vec<&str> = match metadata["authors"].as_array() {
+            Some(authors) => authors.iter().filter_map(|a| a.as_str()).collect(),
+            None => metadata["author"].as_str().into_iter().collect(),
+        };
+        source_metadata = source_metadata.with_authors(authors);
+        if let Some(site) = metadata["site_name"].as_str() {
+            source_metadata.site_name = Some(site.to_string());
+        }
+        if let Some(desc) = metadata["description"].as_str() {
+            source_metadata.description = Some(desc.to_string());
+        }
+        source_metadata.published_at =
+            metadata["published_at"].as_str().and_then(parse_flexible_datetime);
+        article = article.with_metadata(source_metadata);
+    }
+    article
+}
+
+/// The URLs in a `--urls` list: one per line, ignoring blank lines, `#`
+/// comments, and repeats
+fn urls_in(text: &str) -> Vec<&str> {
+    let mut seen = HashSet::new();
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter(|url| seen.insert(*url))
+        .collect()
+}
+
+/// Add every URL listed in `list`, extracting up to `jobs` at once
+///
+/// Extraction runs on worker threads; results come back over a channel so
+/// only this thread touches the store. URLs that are already saved are
+/// skipped; with `save_failures`, ones that can't be fetched are kept as
+/// stubs to refetch later. Fails after the tally if any URL couldn't be added.
+fn add_articles_from_list(
+    store: &Store,
+    list: &Path,
+    room: Option<&str>,
+    jobs: usize,
+    save_failures: bool,
+    timeout: Duration,
+) -> anyhow::Result<()> {
+    let text = if list.as_os_str() == "-" {
+        let mut buf = String::new();
+        std::io::stdin().read_to_string(&mut buf)?;
+        buf
+    } else {
+        std::fs::read_to_string(list)
+            .map_err(|e| anyhow::anyhow!("Could not read {}: {}", list.display(), e))?
+    };
+    let room_id = room.map(|room| find_room_id(store, room)).transpose()?;
+
+    let mut skipped = 0;
+    let mut pending = Vec::new();
+    for url in urls_in(&text) {
+        if store.get_article_by_url(url)?.is_some() {
+            println!("  skip  {} (already added)", url);
+            skipped += 1;
+        } else {
+            pending.push(url.to_string());
+        }
+    }
+
+    let total = pending.len();
+    let workers = jobs.clamp(1, total.max(1));
+    if total > 0 {
+        println!("Fetching {} article(s), {} at a time...", total, workers);
+    }
+    let queue = Mutex::new(pending.into_iter());
+    let (tx, rx) = mpsc::channel();
+    let (mut added, mut failed) = (0, 0);
+
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            let tx = tx.clone();
+            let queue = &queue;
+            scope.spawn(move || loop {
+                let Some(url) = queue.lock().expect("url queue lock poisoned").next() else {
+                    break;
+                };
+                let result = run_extractor(&["extract", &url], timeout);
+                if tx.send((url, result)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+
+        for (url, result) in rx {
+            let result = match result {
+                Err(err) if save_failures => match save_failed_fetch(store, &url, room_id, &err) {
+                    Ok(_) => Err(anyhow::anyhow!("{:#} (saved; retry with mm article refetch)", err)),
+                    Err(save_err) => Err(save_err),
+                },
+                result => result,
+            };
+            let saved = result.and_then(|json| {
+                let mut article = article_from_extracted(&url, &json);
+                if let Some(room_id) = room_id {
+                    article = article.with_room(room_id);
+                }
+                store.create_article(&article)?;
+                Ok(article)
+            });
+            match saved {
+                Ok(article) => {
+                    added += 1;
+                    println!("  ok    {} ({})", url, article.title);
+                }
+                Err(err) => {
+                    failed += 1;
+                    println!("  fail  {}: {}", url, err);
+                }
+            }
+        }
+    });
+
+    println!("\nAdded {}, skipped {} already saved, failed {}", added, skipped, failed);
+    if failed > 0 {
+        anyhow::bail!("{} of {} URL(s) could not be added", failed, total);
+    }
+    Ok(())
+}
+
+/// Pass a genius's temperature and token limit on to the Python summarizer
+fn add_genius_sampling_args(command: &mut Command, genius: &Genius) {
+    if let Some(temperature) = genius.temperature() {
+        command.args(["--temperature", &temperature.to_string()]);
+    }
+    if let Some(max_tokens) = genius.max_tokens() {
+        command.args(["--max-tokens", &max_tokens.to_string()]);
+    }
+}
+
+/// A helper process that was killed for running past its time limit
+#[derive(Debug, thiserror::Error)]
+#[error("{program} did not finish within {}s and was stopped", .timeout.as_secs())]
+struct SubprocessTimeout {
+    program: String,
+    timeout: Duration,
+}
+
+/// Like [`Command::output`], but kills the child and fails with
+/// [`SubprocessTimeout`] once `timeout` has passed
+fn output_with_timeout(command: &mut Command, timeout: Duration) -> anyhow::Result<Output> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Drain both pipes while waiting so a chatty child can't block on a full pipe
+    fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> std::thread::JoinHandle<Vec<u8>> {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buf);
+            }
+            buf
+        })
+    }
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(SubprocessTimeout {
+                program: command.get_program().to_string_lossy().into_owned(),
+                timeout,
+            }
+            .into());
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+fn is_markdown_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown"))
+}
+
+fn is_html_file(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"))
+}
+
+/// Build extractor-shaped JSON for a markdown file, which is stored as-is.
+/// The title is the first `# ` heading, falling back to the file name.
+fn markdown_article_json(path: &Path) -> anyhow::Result<serde_json::Value> {
+    let content = std::fs::read_to_string(path)?;
+    let title = content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("# "))
+        .map(|title| title.trim().to_string())
+        .or_else(|| path.file_stem().map(|stem| stem.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "Untitled".to_string());
+    Ok(serde_json::json!({ "title": title, "content": content }))
+}
+
+/// A failure reported by the Python extractor as
+/// `{"error": {"kind": "...", "message": "..."}}`
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+enum ExtractError {
+    #[error("{0}")]
+    Paywall(String),
+    #[error("{0}")]
+    Timeout(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    NoContent(String),
+    #[error("{message}")]
+    Other { kind: String, message: String },
+}
+
+#[derive(serde::Deserialize)]
+struct ExtractFailure {
+    error: ExtractErrorBody,
+}
+
+#[derive(serde::Deserialize)]
+struct ExtractErrorBody {
+    kind: String,
+    message: String,
+}
+
+impl From<ExtractErrorBody> for ExtractError {
+    fn from(body: ExtractErrorBody) -> Self {
+        match body.kind.as_str() {
+            "paywall" => ExtractError::Paywall(body.message),
+            "timeout" => ExtractError::Timeout(body.message),
+            "not_found" => ExtractError::NotFound(body.message),
+            "no_content" => ExtractError::NoContent(body.message),
+            _ => ExtractError::Other {
+                kind: body.kind,
+                message: body.message,
+            },
+        }
+    }
+}
+
+impl ExtractError {
+    /// Parse the extractor's stdout, if it reported a structured failure
+    fn from_output(stdout: &[u8]) -> Option<Self> {
+        serde_json::from_slice::<ExtractFailure>(stdout)
+            .ok()
+            .map(|failure| failure.error.into())
+    }
+
+    /// What the user can do about it
+    fn advice(&self) -> Option<&'static str> {
+        match self {
+            ExtractError::Paywall(_) => Some(
+                "This site is paywalled; save the page and add it with `mm article add --file`.",
+            ),
+            ExtractError::Timeout(_) => Some("The site was too slow to respond; try again later."),
+            ExtractError::NotFound(_) => Some("Check the URL; the page doesn't seem to exist."),
+            ExtractError::NoContent(_) => {
+                Some("No readable article text was found; the page may be rendered by JavaScript.")
+            }
+            ExtractError::Other { .. } => None,
+        }
+    }
+}
+
+/// Read token usage from the summarizer's JSON output, if it reported any
+///
+/// Accepts either a nested `usage` object or top-level token fields.
+fn parse_summary_usage(result: &serde_json::Value) -> Option<SummaryUsage> {
+    let usage = result.get("usage").filter(|u| u.is_object()).unwrap_or(result);
+    let input_tokens = usage["input_tokens"].as_u64()?;
+    let output_tokens = usage["output_tokens"].as_u64()?;
+
+    let usage = SummaryUsage::new(input_tokens, output_tokens);
+    Some(match result["model"].as_str() {
+        Some(model) => usage.with_model(model),
+        None => usage,
+    })
+}
+
+/// Format usage as "1200 in / 300 out tokens (~$0.0081)"
+fn format_usage(usage: &SummaryUsage) -> String {
+    let mut text = format!(
+        "{} in / {} out tokens",
+        usage.input_tokens, usage.output_tokens
+    );
+    if let Some(cost) = usage.estimated_cost_usd {
+        text.push_str(&format!(" (~${:.4})", cost));
+    }
+    text
+}
+
+fn handle_stats(store: &Store, room: Option<&str>) -> anyhow::Result<()> {
+    let room_id = room.map(|room| find_room_id(store, room)).transpose()?;
+    let graph = store.graph_stats(room_id)?;
+    println!("Links");
+    println!("  Notes: {}", graph.notes);
+    println!("  Links: {}", graph.links);
+    println!("  Average links per note: {:.2}", graph.average_links_per_note);
+    println!("  Unlinked notes: {}", graph.isolated_notes);
+    if let Some((_, title, links)) = &graph.most_connected {
+        println!("  Most connected: {} ({} links)", title, links);
+    }
+    if room_id.is_some() {
+        return Ok(());
+    }
+
+    println!();
+    let usage = store.total_summary_usage()?;
+    println!("Summarization");
+    println!("  Tokens: {} in / {} out", usage.input_tokens, usage.output_tokens);
+    println!(
+        "  Estimated spend: ${:.4}",
+        usage.estimated_cost_usd.unwrap_or(0.0)
+    );
+    Ok(())
+}
+
+fn handle_db_command(store: &Store, action: DbCommands) -> anyhow::Result<()> {
+    match action {
+        DbCommands::Stats => {
+            let stats = store.table_stats()?;
+            println!("{:<28}  {:>10}  {:>10}", "TABLE", "ROWS", "SIZE");
+            println!("{}", "-".repeat(52));
+            for stat in &stats {
+                println!("{:<28}  {:>10}  {:>10}", stat.name, stat.rows, format_bytes(stat.bytes));
+            }
+            let total: u64 = stats.iter().map(|stat| stat.bytes).sum();
+            println!("{}", "-".repeat(52));
+            println!("{:<28}  {:>10}  {:>10}", "Total", "", format_bytes(total));
+            if stats.iter().any(|stat| stat.size_estimated) {
+                println!("\nSizes are estimated from row contents; this SQLite has no dbstat.");
+            }
+        }
+        DbCommands::Check => {
+            let health = store.search_index_health()?;
+            for count in &health.indexes {
+                let mark = if count.in_sync() { "ok" } else { "MISMATCH" };
+                println!(
+                    "{:<18}  {:>8} row(s)  {:>8} indexed  {}",
+                    count.index, count.rows, count.indexed, mark
+                );
+            }
+            if !health.is_healthy() {
+                println!("\nSearch will miss or misreport some rows. Fix it with: mm db reindex");
+                anyhow::bail!("Search indexes are out of sync");
+            }
+            println!("\nSearch indexes are in sync");
+        }
+        DbCommands::Reindex => {
+            store.rebuild_search_index_with_progress(&mut print_progress)?;
+            println!("Rebuilt the search indexes");
+        }
+    }
+    Ok(())
+}
+
+/// A byte count in the largest unit that keeps it above 1, e.g. "1.5 MiB"
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// `s` cut to at most `max_len` characters, ending in "..." when shortened
+fn truncate_string(s: &str, max_len: usize) -> String {
+    if s.chars().count() <= max_len {
+        return s.to_string();
+    }
+    let end = s.char_indices().nth(max_len.saturating_sub(3)).map_or(s.len(), |(i, _)| i);
+    format!("{}...", &s[..end])
+}
+
+fn handle_genius_command(store: &Store, action: GeniusCommands, yes: bool) -> anyhow::Result<()> {
+    match action {
+        GeniusCommands::List => {
+            let geniuses = store.list_geniuses()?;
+            if geniuses.is_empty() {
+                println!(
+                    "No geniuses. Add one with: mm genius add <name> -p <provider> -m <model>"
+                );
+            } else {
+                println!("{:<12}  {:<15}  {:<10}  MODEL", "ID", "NAME", "PROVIDER");
+                println!("{}", "-".repeat(70));
+                let all_ids: Vec<_> = geniuses.iter().map(|g| g.id).collect();
+                for genius in geniuses {
+                    let short_id = store.shortest_unique_prefix(genius.id, &all_ids);
+                    println!(
+                        "{:<12}  {:<15}  {:<10}  {}",
+                        short_id,
+                        genius.name,
+                        genius.provider.to_string(),
+                        genius.model
+                    );
+                }
+            }
+        }
+        GeniusCommands::Add {
+            name,
+            provider,
+            model,
+            system_prompt,
+            temperature,
+            max_tokens,
+        } => {
+            let provider: Provider = provider.parse().map_err(CliError::InvalidInput)?;
+            warn_on_unlikely_model(provider, &model);
+            let mut genius = Genius::new(&name, provider, &model);
+            if let Some(prompt) = system_prompt {
+                genius = genius.with_system_prompt(prompt);
+            }
+            if let Some(temperature) = temperature {
+                genius = genius.with_temperature(temperature)?;
+            }
+            if let Some(max_tokens) = max_tokens {
+                genius = genius.with_max_tokens(max_tokens)?;
+            }
+            store.create_genius(&genius)?;
+            println!("Added genius: {} ({})", name, genius.id);
+        }
+        GeniusCommands::Delete { id } => {
+            let genius = find_genius(store, &id)?;
+            if !confirm(&format!("Delete genius {}?", genius.name), yes)? {
+                println!("Kept genius: {}", genius.name);
+                return Ok(());
+            }
+            store.delete_genius(genius.id)?;
+            println!("Deleted genius: {}", genius.name);
+        }
+    }
+    Ok(())
+}
+
+/// Print todos as an ID / STATUS / SOURCE / TITLE table
+fn print_todo_table(store: &Store, actions: &[UserAction], style: Style) -> anyhow::Result<()> {
+    println!(
+        "{:<12}  {:<12}  {:<30}  TITLE",
+        "ID", "STATUS", "SOURCE"
+    );
+    println!("{}", "-".repeat(90));
+    let all_ids = store.user_action_ids()?;
+    for action in actions {
+        let short_id = store.shortest_unique_prefix(action.id, &all_ids);
+        let source = action
+            .source_file
+            .as_deref()
+            .unwrap_or("-")
+            .trim_start_matches("plans/");
+        let source = truncate_string(source, 28);
+        let title = truncate_string(&action.title, 40);
+        let title = if action.is_overdue() {
+            format!("{} {}", style.icon(Icon::Warning), title)
+        } else {
+            title
+        };
+        println!(
+            "{:<12}  {:<12}  {:<30}  {}",
+            short_id, action.status, source, title
+        );
+    }
+    Ok(())
+}
+
+fn handle_todo_command(store: &Store, action: TodoCommands, style: Style) -> anyhow::Result<()> {
+    match action {
+        TodoCommands::List {
+            status,
+            plan,
+            priority,
+            due_before,
+            overdue,
+            due_today,
+            search,
+        } => {
+            let now = chrono::Utc::now();
+            let due_before = if overdue {
+                Some(now)
+            } else if due_today {
+                let today = now.date_naive().and_time(chrono::NaiveTime::MIN).and_utc();
+                Some(today + chrono::Duration::days(1))
+            } else {
+                due_before
+            };
+            let open_only = overdue || due_today;
+            if let Some(plan) = &plan {
+                print_plan_progress(store, plan)?;
+            }
+            let filter = ActionFilter {
+                status: if open_only { None } else { status.into() },
+                source_file: plan,
+                priority: priority
+                    .map(|p| p.parse::<Priority>())
+                    .transpose()
+                    .map_err(CliError::InvalidInput)?,
+                due_before,
+                open_only,
+                search,
+            };
+            let actions = store.query_user_actions(&filter)?;
+
+            if actions.is_empty() {
+                println!("No todos found. Sync from plans with: mm todo sync");
+            } else {
+                print_todo_table(store, &actions, style)?;
+            }
+        }
+        TodoCommands::Search { query } => {
+            if is_blank_search_query(&query) {
+                println!("Please provide a search term.");
+                return Ok(());
+            }
+            let actions = store.search_user_actions(&query)?;
+            if actions.is_empty() {
+                println!("No todos found matching: {}", query);
+            } else {
+                print_todo_table(store, &actions, style)?;
+            }
+        }
+        TodoCommands::Report { since, format } => {
+            let report = store.generate_todo_report(since)?;
+            match format {
+                ReportFormat::Markdown => print!("{}", report.to_markdown()),
+                ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            }
+        }
+        TodoCommands::Sync {
+            dir,
+            include_checkboxes,
+        } => {
+            let plans_dir = expand_path(&dir);
+
+            if !plans_dir.exists() {
+                anyhow::bail!(CliError::NotFound(format!(
+                    "Plans directory not found: {}",
+                    plans_dir.display()
+                )));
+            }
+
+            if include_checkboxes {
+                println!("Scanning {} for [USER] markers and checkboxes...", plans_dir.display());
+            } else {
+                println!("Scanning {} for [USER] markers...", plans_dir.display());
+            }
+            let options = PlanParseOptions { include_checkboxes };
 
             let mut total_found = 0;
             let mut total_new = 0;
@@ -928,33 +3055,49 @@ fn handle_todo_command(store: &Store, action: TodoCommands) -> anyhow::Result<()
                 let entry = entry?;
                 let path = entry.path();
 
-                if !path.is_file() || path.extension().map_or(true, |ext| ext != "md") {
+                if !path.is_file() || path.extension().is_none_or(|ext| ext != "md") {
                     continue;
                 }
 
                 let content = std::fs::read_to_string(&path)?;
                 let source_file = path.to_string_lossy().to_string();
-                let result = parse_plan_content(&content, &source_file);
+                let result = parse_plan_content_with(&content, &source_file, options);
 
-                if result.actions.is_empty() {
+                for warning in &result.warnings {
+                    eprintln!("Warning: {}", warning);
+                }
+                let mut actions = result.actions;
+                actions.extend(result.checkboxes.into_iter().map(ParsedAction::from));
+                if actions.is_empty() {
                     continue;
                 }
 
                 println!(
                     "  {} - {} action(s)",
                     path.file_name().unwrap_or_default().to_string_lossy(),
-                    result.actions.len()
+                    actions.len()
                 );
-                total_found += result.actions.len();
+                total_found += actions.len();
 
                 // Get existing actions for this file
                 let existing = store.list_user_actions_by_source(&source_file)?;
-                let existing_map: std::collections::HashMap<u32, UserAction> = existing
-                    .into_iter()
-                    .filter_map(|a| a.line_number.map(|ln| (ln, a)))
-                    .collect();
+                let mut existing_map: std::collections::HashMap<u32, UserAction> =
+                    std::collections::HashMap::new();
+                for action in existing {
+                    let Some(line_number) = action.line_number else {
+                        continue;
+                    };
+                    if let Some(kept) = existing_map.get(&line_number) {
+                        eprintln!(
+                            "Warning: {}:{} has more than one todo ({} and {}); syncing only {}",
+                            source_file, line_number, kept.id, action.id, kept.id
+                        );
+                        continue;
+                    }
+                    existing_map.insert(line_number, action);
+                }
 
-                for parsed in result.actions {
+                for parsed in actions {
                     if let Some(existing_action) = existing_map.get(&parsed.line_number) {
                         // Update if title or status changed (but don't override completed status)
                         if existing_action.title != parsed.title
@@ -966,7 +3109,7 @@ fn handle_todo_command(store: &Store, action: TodoCommands) -> anyhow::Result<()
                             if !updated.is_done() {
                                 updated.status = parsed.status;
                             }
-                            store.update_user_action(&updated)?;
+                            store.update_user_action_with_source(&updated, audit_sources::PLAN_SYNC)?;
                             total_updated += 1;
                         }
                     } else {
@@ -977,7 +3120,7 @@ fn handle_todo_command(store: &Store, action: TodoCommands) -> anyhow::Result<()
                             parsed.line_number,
                         );
                         action.status = parsed.status;
-                        store.create_user_action(&action)?;
+                        store.create_user_action_with_source(&action, audit_sources::PLAN_SYNC)?;
                         total_new += 1;
                     }
                 }
@@ -988,6 +3131,31 @@ fn handle_todo_command(store: &Store, action: TodoCommands) -> anyhow::Result<()
             println!("  New: {} action(s)", total_new);
             println!("  Updated: {} action(s)", total_updated);
         }
+        TodoCommands::Import { file } => {
+            let path = expand_path(&file);
+            let content = std::fs::read_to_string(&path).map_err(|err| {
+                CliError::NotFound(format!("Cannot read {}: {}", path.display(), err))
+            })?;
+            let source_file = path.to_string_lossy().to_string();
+
+            // Re-importing the same list only adds tasks it didn't have before
+            let existing: std::collections::HashSet<String> = store
+                .list_user_actions_by_source(&source_file)?
+                .into_iter()
+                .map(|action| action.title)
+                .collect();
+            let (imported, skipped): (Vec<_>, Vec<_>) = parse_todo_list(&content, &source_file)
+                .into_iter()
+                .partition(|action| !existing.contains(&action.title));
+
+            for action in &imported {
+                store.create_user_action_with_source(action, audit_sources::IMPORT)?;
+            }
+            println!("Imported {} todo(s) from {}", imported.len(), path.display());
+            if !skipped.is_empty() {
+                println!("Skipped {} already imported", skipped.len());
+            }
+        }
         TodoCommands::Complete { id } => {
             let mut action = find_user_action(store, &id)?;
             action.complete();
@@ -1030,17 +3198,48 @@ fn handle_todo_command(store: &Store, action: TodoCommands) -> anyhow::Result<()
 
             println!("Skipped: {}", action.title);
         }
-        TodoCommands::Add { title, plan } => {
-            let action = if let Some(plan_file) = plan {
-                // Associate with a plan file but don't add to the file itself
-                let mut a = UserAction::new(&title);
-                a.source_file = Some(plan_file);
-                a
-            } else {
-                UserAction::new(&title)
-            };
+        TodoCommands::Add {
+            title,
+            plan,
+            append,
+            priority,
+            due,
+        } => {
+            let mut action = UserAction::new(&title);
+            if let Some(priority) = priority {
+                let priority: Priority = priority.parse().map_err(CliError::InvalidInput)?;
+                action = action.with_priority(priority);
+            }
+            if let Some(due) = due {
+                action = action.with_due_date(due);
+            }
+            if let Some(plan_file) = plan {
+                // Record the path the way `mm todo sync` does, so both find
+                // the same todo
+                let path = expand_path(&plan_file);
+                // Only write to the plan file itself when asked to, and only
+                // once everything else has been checked, so a rejected todo
+                // never leaves a line behind without a row
+                if append {
+                    action.line_number = Some(append_to_plan_file(&path, &title)?);
+                }
+                action.source_file = Some(path.to_string_lossy().to_string());
+            }
             store.create_user_action(&action)?;
-            println!("Added todo: {} ({})", title, &action.id.to_string()[..8]);
+            let short_id = store.shortest_unique_prefix(action.id, &store.user_action_ids()?);
+            match (&action.source_file, action.line_number) {
+                (Some(plan_file), Some(line)) => {
+                    println!("Added todo: {} ({}) at {}:{}", title, short_id, plan_file, line)
+                }
+                _ => println!("Added todo: {} ({})", title, short_id),
+            }
+        }
+        TodoCommands::Relink { from, to } => {
+            let moved = store.rename_source_file(&from, &to)?;
+            if moved == 0 {
+                anyhow::bail!(CliError::NotFound(format!("No todos from plan file: {}", from)));
+            }
+            println!("Relinked {} todo(s) from {} to {}", moved, from, to);
         }
     }
     Ok(())
@@ -1048,22 +3247,29 @@ fn handle_todo_command(store: &Store, action: TodoCommands) -> anyhow::Result<()
 
 /// Find a user action by ID or short ID
 fn find_user_action(store: &Store, id: &str) -> anyhow::Result<UserAction> {
-    // Try full UUID first
-    if let Ok(uuid) = uuid::Uuid::parse_str(id) {
-        if let Some(action) = store.get_user_action(uuid)? {
-            return Ok(action);
-        }
-    }
-
-    // Try short ID match
     let actions = store.list_user_actions()?;
-    for action in actions {
-        if action.id.to_string().starts_with(id) {
-            return Ok(action);
-        }
-    }
+    let action = resolve_short_id(id, &actions, store.min_id_prefix(), |a| (a.id, a.title.clone()))
+        .map_err(|e| lookup_error("Todo", e))?;
+    Ok(action.clone())
+}
 
-    anyhow::bail!("Todo not found: {}", id)
+/// Print a one-line progress header for a plan file's todos, e.g. "3/7 done"
+/// or "3/7 done, 1 skipped". Only completed todos count as done.
+fn print_plan_progress(store: &Store, plan: &str) -> anyhow::Result<()> {
+    let counts = store.user_action_counts_by_source(plan)?;
+    let total: u64 = counts.values().sum();
+    if total == 0 {
+        return Ok(());
+    }
+    let count = |status| counts.get(&status).copied().unwrap_or(0);
+    let done = count(ActionStatus::Completed);
+    let skipped = count(ActionStatus::Skipped);
+    if skipped > 0 {
+        println!("{}: {}/{} done, {} skipped\n", plan, done, total, skipped);
+    } else {
+        println!("{}: {}/{} done\n", plan, done, total);
+    }
+    Ok(())
 }
 
 /// Update a plan file's marker on a specific line
@@ -1076,11 +3282,43 @@ fn update_plan_file(source_file: &str, line_number: u32, new_status: ActionStatu
 
     let content = std::fs::read_to_string(path)?;
     let updated = update_plan_markers(&content, &[(line_number, new_status)]);
-    std::fs::write(path, updated)?;
+    write_atomically(path, &updated)?;
     Ok(())
 }
 
-fn handle_config_command(store: &Store, action: ConfigCommands) -> anyhow::Result<()> {
+/// Append a `- [USER] <title>` line to a plan file, creating the file if
+/// needed, and return the new line's number
+fn append_to_plan_file(path: &Path, title: &str) -> anyhow::Result<u32> {
+    if title.contains(['\n', '\r']) {
+        anyhow::bail!(CliError::InvalidInput("A plan file todo must fit on one line".into()));
+    }
+    let mut content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err.into()),
+    };
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&format!("- [USER] {}\n", title));
+
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    write_atomically(path, &content)?;
+    Ok(content.lines().count() as u32)
+}
+
+/// Replace a file's contents by writing a sibling temp file and renaming it
+/// over the original, so readers never see it half written
+fn write_atomically(path: &Path, contents: &str) -> std::io::Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    std::fs::write(&temp, contents)?;
+    std::fs::rename(&temp, path)
+}
+
+fn handle_config_command(store: &Store, action: ConfigCommands, yes: bool) -> anyhow::Result<()> {
     match action {
         ConfigCommands::List => {
             let configs = store.list_summary_configs()?;
@@ -1088,10 +3326,11 @@ fn handle_config_command(store: &Store, action: ConfigCommands) -> anyhow::Resul
                 println!("No summary configurations. Using default prompt.");
                 println!("Create one with: mm config create <name>");
             } else {
-                println!("{:<12}  {:<15}  {:<10}  {}", "ID", "NAME", "SCOPE", "ACTIVE");
-                println!("{}", "-".repeat(60));
+                println!("{:<12}  {:<15}  {:<10}  {:<15}  ACTIVE", "ID", "NAME", "SCOPE", "GENIUS");
+                println!("{}", "-".repeat(77));
+                let all_ids: Vec<_> = configs.iter().map(|c| c.id).collect();
                 for config in configs {
-                    let short_id = &config.id.to_string()[..8];
+                    let short_id = store.shortest_unique_prefix(config.id, &all_ids);
                     let scope = if config.is_global() {
                         "global".to_string()
                     } else if let Some(room_id) = config.room_id {
@@ -1103,31 +3342,83 @@ fn handle_config_command(store: &Store, action: ConfigCommands) -> anyhow::Resul
                     } else {
                         "unknown".to_string()
                     };
+                    let genius = match config.genius_id {
+                        Some(genius_id) => store
+                            .get_genius(genius_id)?
+                            .map_or_else(|| "unknown".to_string(), |g| g.name),
+                        None => "-".to_string(),
+                    };
                     let active = if config.active { "yes" } else { "no" };
-                    println!("{:<12}  {:<15}  {:<10}  {}", short_id, config.name, scope, active);
+                    println!(
+                        "{:<12}  {:<15}  {:<10}  {:<15}  {}",
+                        short_id, config.name, scope, genius, active
+                    );
                 }
             }
         }
-        ConfigCommands::Create { name, prompt, room } => {
-            let prompt = prompt.unwrap_or_else(|| DEFAULT_SUMMARY_PROMPT.to_string());
-            
-            let config = if let Some(room_name) = room {
+        ConfigCommands::Create {
+            name,
+            prompt,
+            room,
+            genius,
+        } => {
+            let prompt = match prompt {
+                Some(prompt) => prompt,
+                None if std::io::stdin().is_terminal() => {
+                    let prompt = edit_in_editor(DEFAULT_SUMMARY_PROMPT)?;
+                    if prompt.trim().is_empty() {
+                        anyhow::bail!("Empty prompt; summary config not created");
+                    }
+                    prompt
+                }
+                None => DEFAULT_SUMMARY_PROMPT.to_string(),
+            };
+
+            let mut config = if let Some(room_name) = room {
                 let room_id = find_room_id(store, &room_name)?;
-                SummaryConfig::new_for_room(&name, &prompt, room_id)
+                SummaryConfig::try_new_for_room(&name, &prompt, room_id)?
             } else {
-                SummaryConfig::new_global(&name, &prompt)
+                SummaryConfig::try_new_global(&name, &prompt)?
             };
+            if let Some(genius) = genius {
+                config = config.with_genius(find_genius(store, &genius)?.id);
+            }
+            if let Some(warning) = config.prompt_length_warning(DEFAULT_MAX_PROMPT_LEN) {
+                eprintln!("Warning: {}", warning);
+            }
 
             store.create_summary_config(&config)?;
-            println!("Created summary config: {} ({})", name, &config.id.to_string()[..8]);
+            let short_id = store.shortest_unique_prefix(config.id, &store.summary_config_ids()?);
+            println!("Created summary config: {} ({})", name, short_id);
+        }
+        ConfigCommands::Edit { id } => {
+            let config_id = find_config_by_short_id(store, &id)?;
+            let mut config = store
+                .get_summary_config(config_id)?
+                .ok_or_else(|| CliError::NotFound(format!("Summary config not found: {}", id)))?;
+            let prompt = edit_in_editor(&config.system_prompt)?;
+            let previous = config.system_prompt.clone();
+            config.set_prompt(&prompt)?;
+            if config.system_prompt == previous {
+                println!("No changes to summary config: {}", config.name);
+            } else {
+                if let Some(warning) = config.prompt_length_warning(DEFAULT_MAX_PROMPT_LEN) {
+                    eprintln!("Warning: {}", warning);
+                }
+                store.update_summary_config(&config)?;
+                println!("Updated summary config: {}", config.name);
+            }
         }
         ConfigCommands::Default => {
             println!("Default summary prompt:\n");
             println!("{}", DEFAULT_SUMMARY_PROMPT);
         }
         ConfigCommands::Delete { id } => {
-            let config_id = uuid::Uuid::parse_str(&id)
-                .or_else(|_| find_config_by_short_id(store, &id))?;
+            let config_id = find_config_by_short_id(store, &id)?;
+            if !confirm(&format!("Delete summary config {}?", id), yes)? {
+                println!("Kept summary config: {}", id);
+                return Ok(());
+            }
             store.delete_summary_config(config_id)?;
             println!("Deleted summary config: {}", id);
         }
@@ -1135,6 +3426,61 @@ fn handle_config_command(store: &Store, action: ConfigCommands) -> anyhow::Resul
     Ok(())
 }
 
+/// Ask before a destructive command. Goes ahead without asking when `yes`
+/// (--yes) is set or there's no terminal to answer at, e.g. in a script.
+fn confirm(question: &str, yes: bool) -> anyhow::Result<bool> {
+    if yes || !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return Ok(true);
+    }
+    ask(question)
+}
+
+/// Ask whether to delete a note that still has links to other notes
+fn confirm_link_removal(links: usize) -> anyhow::Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!(
+            "This note has {} link(s) to other notes; pass --force to delete it anyway",
+            links
+        );
+    }
+    ask(&format!("This note has {} link(s) to other notes. Delete anyway?", links))
+}
+
+/// Print a yes/no question and read the answer; anything but y/yes is no
+fn ask(question: &str) -> anyhow::Result<bool> {
+    use std::io::Write;
+
+    print!("{} [y/N] ", question);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Open `text` in the user's editor ($VISUAL, then $EDITOR, then vi) and
+/// return the saved result with trailing whitespace trimmed
+fn edit_in_editor(text: &str) -> anyhow::Result<String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut parts = editor.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("$EDITOR is set but empty"))?;
+
+    let path = std::env::temp_dir().join(format!("minmind-{}.md", uuid::Uuid::new_v4()));
+    std::fs::write(&path, text)?;
+    let status = Command::new(program).args(parts).arg(&path).status();
+    let edited = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+
+    let status = status.map_err(|e| anyhow::anyhow!("Could not start editor '{}': {}", editor, e))?;
+    if !status.success() {
+        anyhow::bail!("Editor '{}' exited with {}; nothing saved", editor, status);
+    }
+    Ok(edited?.trim_end().to_string())
+}
+
 /// Find a room by ID or name
 fn find_room_id(store: &Store, room: &str) -> anyhow::Result<uuid::Uuid> {
     // Try to parse as UUID first
@@ -1150,82 +3496,638 @@ fn find_room_id(store: &Store, room: &str) -> anyhow::Result<uuid::Uuid> {
         }
     }
 
-    anyhow::bail!("Room not found: {}", room)
+    // Finally, try the room's slug
+    if let Some(r) = store.get_room_by_slug(room)? {
+        return Ok(r.id);
+    }
+
+    anyhow::bail!(CliError::NotFound(format!("Room not found: {}", room)))
 }
 
 /// Find an article by ID or short ID
 fn find_article(store: &Store, id: &str) -> anyhow::Result<Article> {
-    // Try full UUID first
-    if let Ok(uuid) = uuid::Uuid::parse_str(id) {
-        if let Some(article) = store.get_article(uuid)? {
-            return Ok(article);
-        }
-    }
-
-    // Try short ID match
     let articles = store.list_articles()?;
-    for article in articles {
-        if article.id.to_string().starts_with(id) {
-            return Ok(article);
+    let article = resolve_short_id(id, &articles, store.min_id_prefix(), |a| (a.id, a.title.clone()))
+        .map_err(|e| lookup_error("Article", e))?;
+    Ok(article.clone())
+}
+
+/// Find a note by ID, short ID or title (case-insensitive)
+fn find_note(store: &Store, id: &str) -> anyhow::Result<Note> {
+    resolve_note(id, &store.list_notes()?, store.min_id_prefix())
+}
+
+/// Resolve `id` against `notes`: a full UUID or unique prefix first, then an
+/// exact title. Several notes sharing the title is reported as ambiguous.
+fn resolve_note(id: &str, notes: &[Note], min_len: usize) -> anyhow::Result<Note> {
+    let err = match resolve_short_id(id, notes, min_len, |n| (n.id, n.title.clone())) {
+        Ok(note) => return Ok(note.clone()),
+        Err(ShortIdError::Ambiguous { prefix, matches }) => {
+            return Err(ShortIdError::Ambiguous { prefix, matches }.into())
         }
+        Err(err) => err,
+    };
+    let titled: Vec<&Note> =
+        notes.iter().filter(|n| n.title.eq_ignore_ascii_case(id.trim())).collect();
+    match titled.as_slice() {
+        [] => Err(lookup_error("Note", err)),
+        [only] => Ok((*only).clone()),
+        _ => Err(ShortIdError::Ambiguous {
+            prefix: id.to_string(),
+            matches: titled.iter().map(|n| (n.id, n.title.clone())).collect(),
+        }
+        .into()),
     }
+}
 
-    anyhow::bail!("Article not found: {}", id)
+/// Find a genius by name (case-insensitive) or short ID
+fn find_genius(store: &Store, id: &str) -> anyhow::Result<Genius> {
+    let geniuses = store.list_geniuses()?;
+    if let Some(genius) = geniuses.iter().find(|g| g.name.eq_ignore_ascii_case(id)) {
+        return Ok(genius.clone());
+    }
+    let genius = resolve_short_id(id, &geniuses, store.min_id_prefix(), |g| (g.id, g.name.clone()))
+        .map_err(|e| lookup_error("Genius", e))?;
+    Ok(genius.clone())
 }
 
 /// Find a config by short ID
 fn find_config_by_short_id(store: &Store, id: &str) -> anyhow::Result<uuid::Uuid> {
     let configs = store.list_summary_configs()?;
-    for config in configs {
-        if config.id.to_string().starts_with(id) {
-            return Ok(config.id);
+    let config = resolve_short_id(id, &configs, store.min_id_prefix(), |c| (c.id, c.name.clone()))
+        .map_err(|e| lookup_error("Config", e))?;
+    Ok(config.id)
+}
+
+/// Turn a short id resolution failure into a CLI error, naming the entity kind
+fn lookup_error(kind: &str, err: ShortIdError) -> anyhow::Error {
+    match err {
+        ShortIdError::NotFound(id) => {
+            CliError::NotFound(format!("{} not found: {}", kind, id)).into()
         }
+        other => other.into(),
     }
-    anyhow::bail!("Config not found: {}", id)
 }
 
 /// Find the Python package directory
 fn find_python_dir() -> anyhow::Result<PathBuf> {
-    // Try relative to executable
-    if let Ok(exe) = std::env::current_exe() {
-        let project_root = exe
-            .parent()
-            .and_then(|p| p.parent())
-            .and_then(|p| p.parent())
-            .and_then(|p| p.parent());
-        
-        if let Some(root) = project_root {
-            let python_dir = root.join("python");
-            if python_dir.exists() {
-                return Ok(python_dir);
+    let exe = std::env::current_exe().ok();
+    let cwd = std::env::current_dir()?;
+    let env = std::env::var("MINMIND_PYTHON_DIR").ok();
+
+    resolve_python_dir(exe.as_deref(), &cwd, env.as_deref()).ok_or_else(|| {
+        anyhow::anyhow!("Could not find Python directory. Set MINMIND_PYTHON_DIR environment variable.")
+    })
+}
+
+/// Resolve the Python package directory from the given locations
+///
+/// Checked in order:
+/// 1. `env` (the `MINMIND_PYTHON_DIR` override), if it points at a directory
+/// 2. Every ancestor of the executable, looking for `python/` or `src/python/`
+/// 3. Every ancestor of `cwd`, with the same candidates
+///
+/// A candidate only counts if it contains the `minmind` package, so an
+/// unrelated `python/` directory higher up the tree isn't picked by accident.
+fn resolve_python_dir(exe: Option<&Path>, cwd: &Path, env: Option<&str>) -> Option<PathBuf> {
+    if let Some(dir) = env.filter(|d| !d.is_empty()) {
+        let path = PathBuf::from(dir);
+        if path.is_dir() {
+            return Some(path);
+        }
+    }
+
+    let exe_dir = exe.and_then(Path::parent);
+    for start in exe_dir.into_iter().chain(std::iter::once(cwd)) {
+        for ancestor in start.ancestors() {
+            for candidate in [ancestor.join("python"), ancestor.join("src").join("python")] {
+                if candidate.join("minmind").is_dir() {
+                    return Some(candidate);
+                }
             }
         }
     }
 
-    // Try current directory
-    let cwd = std::env::current_dir()?;
-    let python_dir = cwd.join("python");
-    if python_dir.exists() {
-        return Ok(python_dir);
+    None
+}
+
+/// Warn (without failing) when `model` doesn't look like one of `provider`'s
+/// models, to catch typos before they turn into confusing API errors
+fn warn_on_unlikely_model(provider: Provider, model: &str) {
+    if !provider.is_valid_model(model) {
+        eprintln!(
+            "Warning: '{}' doesn't look like an {} model; check the name for typos",
+            model, provider
+        );
     }
+}
 
-    // Try MINMIND_PYTHON_DIR env var
-    if let Ok(dir) = std::env::var("MINMIND_PYTHON_DIR") {
-        let path = PathBuf::from(dir);
-        if path.exists() {
-            return Ok(path);
+/// Fail early, naming the variable, when `provider` needs an API key that
+/// isn't set; otherwise the summarizer dies with a Python traceback
+fn require_api_key(provider: Provider) -> Result<(), CliError> {
+    check_api_key(provider, |name| std::env::var(name).ok())
+}
+
+/// [`require_api_key`] with the environment lookup passed in
+fn check_api_key(
+    provider: Provider,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<(), CliError> {
+    match provider.api_key_env() {
+        Some(name) if lookup(name).is_none_or(|key| key.trim().is_empty()) => {
+            Err(CliError::InvalidInput(format!(
+                "{} is not set; export it to summarize with {}",
+                name, provider
+            )))
         }
+        _ => Ok(()),
     }
+}
 
-    anyhow::bail!("Could not find Python directory. Set MINMIND_PYTHON_DIR environment variable.")
+/// Print one direction of a note's links, e.g. "Links to:"
+fn print_linked_notes(label: &str, links: &[(Link, Note)]) {
+    if links.is_empty() {
+        return;
+    }
+    println!("{}:", label);
+    for (link, note) in links {
+        match &link.link_type {
+            Some(link_type) => println!("  {} ({}) [{}]", note.title, note.id, link_type),
+            None => println!("  {} ({})", note.title, note.id),
+        }
+    }
 }
 
 /// Expand ~ in paths
 fn expand_path(path: &str) -> PathBuf {
-    if path.starts_with("~/") {
+    if let Some(rest) = path.strip_prefix("~/") {
         if let Some(home) = dirs::home_dir() {
-            return home.join(&path[2..]);
+            return home.join(rest);
         }
     }
     PathBuf::from(path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_failed_fetch() {
+        let store = Store::in_memory().unwrap();
+        let mut room = Room::new("Reading");
+        store.create_room(&mut room).unwrap();
+        let url = "https://example.com/gone";
+        let err = anyhow::anyhow!("HTTP 404").context("Failed to extract article");
+
+        let stub = save_failed_fetch(&store, url, Some(room.id), &err).unwrap();
+
+        let saved = store.get_article_by_url(url).unwrap().expect("stub should be saved");
+        assert_eq!(saved.id, stub.id);
+        assert_eq!(saved.status, ArticleStatus::Pending);
+        assert_eq!(saved.room_id, Some(room.id));
+        assert_eq!(saved.last_error.as_deref(), Some("Failed to extract article: HTTP 404"));
+        assert!(saved.raw_content.is_empty());
+    }
+
+    #[test]
+    fn test_missing_content() {
+        let article = Article::new("https://example.com/a", "A", "Some text");
+        assert_eq!(missing_content(&article), None);
+
+        let stub = Article::failed_fetch("https://example.com/gone", "HTTP 404");
+        let reason = missing_content(&stub).expect("stub has no content");
+        assert!(reason.contains(&format!("mm article refetch {}", &stub.id.to_string()[..8])));
+
+        let empty = Article::new("https://example.com/empty", "Empty", "");
+        assert!(missing_content(&empty).is_some());
+    }
+
+    #[test]
+    fn test_exit_code() {
+        let store = Store::in_memory().unwrap();
+        let mut first = Room::new("Alpha");
+        let mut second = Room::new("Beta");
+        store.create_room(&mut first).unwrap();
+        store.create_room(&mut second).unwrap();
+
+        let missing = find_room_id(&store, "Nowhere").unwrap_err();
+        assert_eq!(exit_code(&missing), EXIT_NOT_FOUND);
+
+        let unknown = find_user_action(&store, "abcd1234").unwrap_err();
+        assert_eq!(exit_code(&unknown), EXIT_NOT_FOUND);
+
+        let bad_uuid = anyhow::Error::from(uuid::Uuid::parse_str("nope").unwrap_err());
+        assert_eq!(exit_code(&bad_uuid), EXIT_INVALID_INPUT);
+
+        let ambiguous = ShortIdError::Ambiguous {
+            prefix: "ab".to_string(),
+            matches: vec![(first.id, first.name), (second.id, second.name)],
+        };
+        assert_eq!(exit_code(&ambiguous.into()), EXIT_AMBIGUOUS_ID);
+
+        let wrapped = anyhow::Error::from(StoreError::NotFound("Note".to_string()))
+            .context("Could not move note");
+        assert_eq!(exit_code(&wrapped), EXIT_NOT_FOUND);
+        assert_eq!(exit_code(&anyhow::anyhow!("boom")), EXIT_ERROR);
+    }
+
+    #[test]
+    fn test_check_api_key() {
+        let unset = |_: &str| None;
+        let err = check_api_key(Provider::Anthropic, unset).unwrap_err();
+        assert!(err.to_string().contains("ANTHROPIC_API_KEY"));
+        assert_eq!(exit_code(&err.into()), EXIT_INVALID_INPUT);
+
+        let blank = check_api_key(Provider::OpenAI, |_| Some("  ".to_string())).unwrap_err();
+        assert!(blank.to_string().contains("OPENAI_API_KEY"));
+
+        let set = |name: &str| (name == "OPENAI_API_KEY").then(|| "sk-test".to_string());
+        assert!(check_api_key(Provider::OpenAI, set).is_ok());
+        assert!(check_api_key(Provider::Ollama, unset).is_ok());
+        assert!(check_api_key(Provider::Custom, unset).is_ok());
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout("120s").unwrap(), Duration::from_secs(120));
+        assert_eq!(parse_timeout("5m").unwrap(), Duration::from_secs(300));
+        assert!(parse_timeout("0s").is_err());
+        assert!(parse_timeout("-5s").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_output_with_timeout() {
+        let output = output_with_timeout(Command::new("echo").arg("hi"), Duration::from_secs(5))
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"hi\n");
+
+        let started = Instant::now();
+        let err = output_with_timeout(Command::new("sleep").arg("10"), Duration::from_millis(200))
+            .unwrap_err();
+        assert!(err.downcast_ref::<SubprocessTimeout>().is_some());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_dashboard_sort() {
+        let mut older = Article::new("https://example.com/b", "banana", "");
+        older.updated_at = chrono::Utc::now() - chrono::Duration::days(1);
+        let newer = Article::new("https://example.com/a", "Apple", "");
+        let mut articles = vec![older, newer];
+
+        "date".parse::<DashboardSort>().unwrap().apply(&mut articles);
+        assert_eq!(articles[0].title, "Apple");
+        "title".parse::<DashboardSort>().unwrap().apply(&mut articles);
+        assert_eq!(articles[0].title, "Apple");
+        articles.reverse();
+        "title".parse::<DashboardSort>().unwrap().apply(&mut articles);
+        assert_eq!(articles[0].title, "Apple");
+        assert!("size".parse::<DashboardSort>().is_err());
+    }
+
+    #[test]
+    fn test_database_flag_resolution() {
+        // MINMIND_DB is read by clap; check the wiring rather than mutating
+        // the process environment, which other tests share
+        let command = <Cli as clap::CommandFactory>::command();
+        let database = command.get_arguments().find(|arg| arg.get_id() == "database").unwrap();
+        assert_eq!(database.get_env(), Some(std::ffi::OsStr::new("MINMIND_DB")));
+
+        let cli = Cli::try_parse_from(["mm", "--database", "/tmp/flag.db", "stats"]).unwrap();
+        assert_eq!(cli.database.as_deref(), Some("/tmp/flag.db"));
+    }
+
+    #[test]
+    fn test_parse_summary_usage() {
+        let nested = serde_json::json!({
+            "summary": "s",
+            "model": "gpt-4o",
+            "usage": {"input_tokens": 100, "output_tokens": 20}
+        });
+        let usage = parse_summary_usage(&nested).unwrap();
+        assert_eq!(usage.input_tokens, 100);
+        assert_eq!(usage.output_tokens, 20);
+        assert!(usage.estimated_cost_usd.is_some());
+
+        let flat = serde_json::json!({"summary": "s", "input_tokens": 5, "output_tokens": 1});
+        assert_eq!(parse_summary_usage(&flat).unwrap().total_tokens(), 6);
+
+        let missing = serde_json::json!({"summary": "s", "tokens_used": null});
+        assert!(parse_summary_usage(&missing).is_none());
+    }
+
+    fn make_python_dir(root: &Path) -> PathBuf {
+        let python_dir = root.join("src").join("python");
+        std::fs::create_dir_all(python_dir.join("minmind")).unwrap();
+        python_dir
+    }
+
+    #[test]
+    fn test_resolve_python_dir_prefers_env() {
+        let project = tempfile::tempdir().unwrap();
+        make_python_dir(project.path());
+        let override_dir = tempfile::tempdir().unwrap();
+
+        let resolved = resolve_python_dir(
+            None,
+            project.path(),
+            Some(override_dir.path().to_str().unwrap()),
+        );
+        assert_eq!(resolved.as_deref(), Some(override_dir.path()));
+    }
+
+    #[test]
+    fn test_resolve_python_dir_ignores_missing_env() {
+        let project = tempfile::tempdir().unwrap();
+        let python_dir = make_python_dir(project.path());
+
+        let resolved = resolve_python_dir(None, project.path(), Some("/does/not/exist"));
+        assert_eq!(resolved, Some(python_dir));
+    }
+
+    #[test]
+    fn test_resolve_python_dir_from_exe() {
+        let project = tempfile::tempdir().unwrap();
+        let python_dir = make_python_dir(project.path());
+        let exe = project.path().join("target").join("debug").join("mm");
+        let elsewhere = tempfile::tempdir().unwrap();
+
+        let resolved = resolve_python_dir(Some(&exe), elsewhere.path(), None);
+        assert_eq!(resolved, Some(python_dir));
+    }
+
+    #[test]
+    fn test_resolve_python_dir_from_cwd() {
+        let project = tempfile::tempdir().unwrap();
+        let python_dir = make_python_dir(project.path());
+        let nested = project.path().join("plans");
+        std::fs::create_dir_all(&nested).unwrap();
+        let installed = tempfile::tempdir().unwrap();
+        let exe = installed.path().join("bin").join("mm");
+
+        let resolved = resolve_python_dir(Some(&exe), &nested, None);
+        assert_eq!(resolved, Some(python_dir));
+    }
+
+    #[test]
+    fn test_resolve_python_dir_requires_package() {
+        let project = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(project.path().join("python")).unwrap();
+
+        assert_eq!(resolve_python_dir(None, project.path(), None), None);
+    }
+
+    #[test]
+    fn test_should_render() {
+        let md = ContentFormat::Markdown;
+        assert!(should_render(md, false, false, true));
+        assert!(should_render(md, true, false, false));
+        assert!(!should_render(md, false, false, false));
+        assert!(!should_render(md, false, true, true));
+        assert!(!should_render(ContentFormat::PlainText, true, false, true));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30d"), Ok(chrono::Duration::days(30)));
+        assert_eq!(parse_duration("12h"), Ok(chrono::Duration::hours(12)));
+        assert_eq!(parse_duration("45m"), Ok(chrono::Duration::minutes(45)));
+        assert!(parse_duration("30").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("3w").is_err());
+        assert!(parse_duration("3é").is_err());
+        assert_eq!(parse_duration("-5d"), Err("Invalid duration '-5d': must be positive".into()));
+        assert!(parse_duration("0h").is_err());
+        assert!(parse_duration("99999999999w").is_err());
+        assert_eq!(
+            parse_duration("99999999999d"),
+            Err("Invalid duration '99999999999d': too long".into())
+        );
+        assert!(parse_duration("9999999999999s").is_err());
+    }
+
+    #[test]
+    fn test_cleanup_rejects_bad_older_than() {
+        let cleanup = |older_than: &str| {
+            let arg = format!("--older-than={}", older_than);
+            Cli::try_parse_from(["mm", "article", "cleanup", arg.as_str()])
+        };
+        assert!(cleanup("90d").is_ok());
+        for bad in ["-5d", "0d", "99999999999w", "99999999999d"] {
+            let err = cleanup(bad).err().unwrap_or_else(|| panic!("accepted {}", bad));
+            assert_eq!(err.kind(), clap::error::ErrorKind::ValueValidation, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_extract_error_from_output() {
+        let paywall = br#"{"error": {"kind": "paywall", "message": "login required"}}"#;
+        let err = ExtractError::from_output(paywall).unwrap();
+        assert_eq!(err, ExtractError::Paywall("login required".to_string()));
+        assert!(err.advice().is_some());
+
+        let other = br#"{"error": {"kind": "dns", "message": "no such host"}}"#;
+        let err = ExtractError::from_output(other).unwrap();
+        assert_eq!(err.to_string(), "no such host");
+        assert!(matches!(err, ExtractError::Other { ref kind, .. } if kind == "dns"));
+
+        assert!(ExtractError::from_output(b"Traceback (most recent call last)").is_none());
+    }
+
+    #[test]
+    fn test_markdown_article_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let with_heading = dir.path().join("draft.md");
+        std::fs::write(&with_heading, "intro\n# Real Title\nbody").unwrap();
+        let json = markdown_article_json(&with_heading).unwrap();
+        assert_eq!(json["title"], "Real Title");
+        assert_eq!(json["content"], "intro\n# Real Title\nbody");
+
+        let no_heading = dir.path().join("notes-on-rust.md");
+        std::fs::write(&no_heading, "just text").unwrap();
+        assert_eq!(markdown_article_json(&no_heading).unwrap()["title"], "notes-on-rust");
+
+        assert!(is_markdown_file(Path::new("a.MD")));
+        assert!(is_html_file(Path::new("saved.htm")));
+        assert!(!is_html_file(Path::new("notes.txt")));
+    }
+
+    #[test]
+    fn test_append_to_plan_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let plan = dir.path().join("plans").join("001.md");
+
+        assert_eq!(append_to_plan_file(&plan, "Book the venue").unwrap(), 1);
+        std::fs::write(&plan, "# Launch
+
+- [USER:done] Pick a date").unwrap();
+        let line = append_to_plan_file(&plan, "Send invites").unwrap();
+
+        let content = std::fs::read_to_string(&plan).unwrap();
+        assert_eq!(content, "# Launch
+
+- [USER:done] Pick a date
+- [USER] Send invites
+");
+        assert_eq!(line, 4);
+        let parsed = parse_plan_content_with(&content, "001.md", PlanParseOptions::default());
+        let appended = parsed.actions.last().unwrap();
+        assert_eq!((appended.line_number, appended.title.as_str()), (line, "Send invites"));
+        assert_eq!(appended.status, ActionStatus::Pending);
+
+        assert!(append_to_plan_file(&plan, "Two\nlines").is_err());
+        assert!(!dir.path().join("plans").join("001.md.tmp").exists());
+    }
+
+    #[test]
+    fn test_truncate_string() {
+        assert_eq!(truncate_string("Short", 10), "Short");
+        assert_eq!(truncate_string("Exactly 10", 10), "Exactly 10");
+        assert_eq!(truncate_string("A longer title", 10), "A longe...");
+        // Cuts on character boundaries, counting characters rather than bytes
+        assert_eq!(truncate_string("Café crème brûlée", 10), "Café cr...");
+        assert_eq!(truncate_string("東京の天気予報を見る", 8), "東京の天気...");
+        assert_eq!(truncate_string("🦀🦀🦀🦀", 4), "🦀🦀🦀🦀");
+        assert_eq!(truncate_string("🦀🦀🦀🦀🦀", 4), "🦀...");
+    }
+
+    #[test]
+    fn test_absolute_path() {
+        let cwd = std::env::current_dir().unwrap();
+        let relative = absolute_path(Path::new("saved/page.html")).unwrap();
+        assert!(relative.is_absolute());
+        assert_eq!(relative, cwd.join("saved").join("page.html"));
+
+        let absolute = cwd.join("page.html");
+        assert_eq!(absolute_path(&absolute).unwrap(), absolute);
+    }
+
+    #[test]
+    fn test_todo_add_append_then_sync() {
+        let store = Store::in_memory().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let plans = dir.path().join("plans");
+        let plan = plans.join("launch.md");
+        let style = Style::new(ColorMode::Never);
+
+        let add = TodoCommands::Add {
+            title: "Book the venue".into(),
+            plan: Some(plan.to_string_lossy().to_string()),
+            append: true,
+            priority: None,
+            due: None,
+        };
+        handle_todo_command(&store, add, style).unwrap();
+        let sync = TodoCommands::Sync {
+            dir: plans.to_string_lossy().to_string(),
+            include_checkboxes: false,
+        };
+        handle_todo_command(&store, sync, style).unwrap();
+
+        let actions = store.list_user_actions_by_source(&plan.to_string_lossy()).unwrap();
+        assert_eq!(actions.len(), 1, "sync duplicated the appended todo");
+        assert_eq!(actions[0].line_number, Some(1));
+        assert_eq!(actions[0].title, "Book the venue");
+    }
+
+    #[test]
+    fn test_todo_table_truncates_multibyte_text() {
+        let store = Store::in_memory().unwrap();
+        let source = format!("plans/{}.md", "ü".repeat(30));
+        let action = UserAction::from_plan("é".repeat(45), source, 1);
+        store.create_user_action(&action).unwrap();
+
+        print_todo_table(&store, &[action], Style::new(ColorMode::Never)).unwrap();
+    }
+
+    #[test]
+    fn test_todo_add_bad_priority_leaves_plan_alone() {
+        let store = Store::in_memory().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let plan = dir.path().join("launch.md");
+
+        let add = TodoCommands::Add {
+            title: "Book the venue".into(),
+            plan: Some(plan.to_string_lossy().to_string()),
+            append: true,
+            priority: Some("urgent".into()),
+            due: None,
+        };
+        let err = handle_todo_command(&store, add, Style::new(ColorMode::Never)).unwrap_err();
+
+        assert_eq!(exit_code(&err), EXIT_INVALID_INPUT);
+        assert!(!plan.exists(), "the plan file was written for a rejected todo");
+        assert!(store.list_user_actions().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let today = chrono::Utc::now().date_naive().and_time(chrono::NaiveTime::MIN).and_utc();
+        assert_eq!(parse_time("today").unwrap(), today);
+        assert_eq!(parse_time("Yesterday").unwrap(), today - chrono::Duration::days(1));
+        let week = parse_time("7d").unwrap();
+        assert!(week < chrono::Utc::now() - chrono::Duration::days(6));
+        assert_eq!(parse_time("2026-03-01").unwrap().to_rfc3339(), "2026-03-01T00:00:00+00:00");
+        assert!(parse_time("last tuesday").is_err());
+    }
+
+    #[test]
+    fn test_urls_in() {
+        let list = "https://a.example/1\n\n  # reading list\n https://b.example/2 \n\
+                    https://a.example/1\n";
+        assert_eq!(urls_in(list), ["https://a.example/1", "https://b.example/2"]);
+    }
+
+    #[test]
+    fn test_within() {
+        let at = parse_time("2026-03-01T12:00:00Z").unwrap();
+        let day = parse_time("2026-03-01").unwrap();
+        let next_day = parse_time("2026-03-02").unwrap();
+        assert!(within(at, None, None));
+        assert!(within(at, Some(day), Some(next_day)));
+        assert!(within(day, Some(day), None));
+        assert!(!within(next_day, None, Some(next_day)));
+        assert!(!within(at, Some(next_day), None));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let date = parse_date("2026-03-01").unwrap();
+        assert_eq!(date.to_rfc3339(), "2026-03-01T00:00:00+00:00");
+        let stamp = parse_date("2026-03-01T12:30:00+02:00").unwrap();
+        assert_eq!(stamp.to_rfc3339(), "2026-03-01T10:30:00+00:00");
+        assert!(parse_date("next tuesday").is_err());
+    }
+
+    #[test]
+    fn test_resolve_note() {
+        let room = uuid::Uuid::new_v4();
+        let mut notes: Vec<Note> = ["Inbox", "Reading list", "Reading list"]
+            .into_iter()
+            .map(|title| Note::new(room, title, NoteType::Idea))
+            .collect();
+        notes[0].id = uuid::Uuid::parse_str("abcd1111-0000-0000-0000-000000000000").unwrap();
+        notes[1].id = uuid::Uuid::parse_str("abcd2222-0000-0000-0000-000000000000").unwrap();
+
+        assert_eq!(resolve_note(&notes[2].id.to_string(), &notes, 4).unwrap().id, notes[2].id);
+        assert_eq!(resolve_note("abcd1", &notes, 4).unwrap().title, "Inbox");
+        assert_eq!(resolve_note("inbox", &notes, 4).unwrap().id, notes[0].id);
+
+        assert_eq!(exit_code(&resolve_note("abcd", &notes, 4).unwrap_err()), EXIT_AMBIGUOUS_ID);
+        assert_eq!(
+            exit_code(&resolve_note("Reading list", &notes, 4).unwrap_err()),
+            EXIT_AMBIGUOUS_ID
+        );
+        assert_eq!(exit_code(&resolve_note("Outbox", &notes, 4).unwrap_err()), EXIT_NOT_FOUND);
+    }
+}
@@ -0,0 +1,201 @@
+//! Style - Color and symbols for terminal output
+//!
+//! Status output goes through [`Style`] so that it can fall back to plain
+//! ASCII: no escape codes, emoji or box-drawing characters. That happens with
+//! `--color never`, and in the default `--color auto` when `NO_COLOR` is set,
+//! `TERM` is `dumb` or stdout isn't a terminal.
+
+use std::fmt::Display;
+use std::io::IsTerminal;
+
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorMode {
+    /// Only on a capable terminal, unless NO_COLOR is set
+    Auto,
+    Always,
+    Never,
+}
+
+/// A status symbol, drawn as emoji or as ASCII
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Icon {
+    Success,
+    Failure,
+    Warning,
+    Pending,
+    Summary,
+    Link,
+    Preview,
+}
+
+impl Icon {
+    fn fancy(self) -> &'static str {
+        match self {
+            Icon::Success => "✓",
+            Icon::Failure => "✗",
+            Icon::Warning => "⚠",
+            Icon::Pending => "⏳",
+            Icon::Summary => "📝",
+            Icon::Link => "🔗",
+            Icon::Preview => "📄",
+        }
+    }
+
+    fn plain(self) -> &'static str {
+        match self {
+            Icon::Success => "ok",
+            Icon::Failure => "x",
+            Icon::Warning => "!",
+            Icon::Pending => "-",
+            Icon::Summary => "*",
+            Icon::Link => ">",
+            Icon::Preview => "#",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Green,
+}
+
+impl Color {
+    fn code(self) -> &'static str {
+        match self {
+            Color::Red => "\x1b[31m",
+            Color::Green => "\x1b[32m",
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+/// Inner width of the boxes drawn by [`Style::boxed`]
+const BOX_WIDTH: usize = 64;
+
+/// Whether output gets color and symbols, decided once from `--color` and
+/// the environment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+    fancy: bool,
+}
+
+impl Style {
+    /// The style for stdout under `mode`
+    pub fn new(mode: ColorMode) -> Self {
+        Self::resolve(
+            mode,
+            std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()),
+            std::env::var("TERM").is_ok_and(|term| term == "dumb"),
+            std::io::stdout().is_terminal(),
+        )
+    }
+
+    /// `--color always` and `never` win over the environment; `auto` wants a
+    /// terminal that isn't dumb and no `NO_COLOR`
+    fn resolve(mode: ColorMode, no_color: bool, dumb_term: bool, tty: bool) -> Self {
+        let fancy = match mode {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => tty && !no_color && !dumb_term,
+        };
+        Self { fancy }
+    }
+
+    /// Whether color and symbols are on
+    pub fn is_fancy(&self) -> bool {
+        self.fancy
+    }
+
+    pub fn icon(&self, icon: Icon) -> &'static str {
+        if self.fancy {
+            icon.fancy()
+        } else {
+            icon.plain()
+        }
+    }
+
+    /// `text` in `color`, or unchanged when color is off
+    pub fn paint(&self, color: Color, text: impl Display) -> String {
+        if self.fancy {
+            format!("{}{}{}", color.code(), text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// A horizontal line `width` characters long
+    pub fn rule(&self, width: usize) -> String {
+        (if self.fancy { "─" } else { "-" }).repeat(width)
+    }
+
+    /// `sections` of lines in a box, with a divider between sections
+    pub fn boxed(&self, sections: &[Vec<String>]) -> String {
+        let (top, divider, bottom, side) = if self.fancy {
+            (['┌', '┐'], ['├', '┤'], ['└', '┘'], '│')
+        } else {
+            (['+', '+'], ['+', '+'], ['+', '+'], '|')
+        };
+        let edge = |[left, right]: [char; 2]| format!("{}{}{}", left, self.rule(BOX_WIDTH), right);
+
+        let mut lines = vec![edge(top)];
+        for (i, section) in sections.iter().enumerate() {
+            if i > 0 {
+                lines.push(edge(divider));
+            }
+            for line in section {
+                let pad = (BOX_WIDTH - 1).saturating_sub(line.chars().count());
+                lines.push(format!("{} {}{}{}", side, line, " ".repeat(pad), side));
+            }
+        }
+        lines.push(edge(bottom));
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_color_mode() {
+        let auto =
+            |no_color, dumb_term, tty| Style::resolve(ColorMode::Auto, no_color, dumb_term, tty);
+        assert!(auto(false, false, true).is_fancy());
+        assert!(!auto(true, false, true).is_fancy());
+        assert!(!auto(false, true, true).is_fancy());
+        assert!(!auto(false, false, false).is_fancy());
+        assert!(Style::resolve(ColorMode::Always, true, true, false).is_fancy());
+        assert!(!Style::resolve(ColorMode::Never, false, false, true).is_fancy());
+    }
+
+    #[test]
+    fn test_no_color_output_is_plain_ascii() {
+        let style = Style::resolve(ColorMode::Auto, true, false, true);
+
+        let icons = [
+            Icon::Success,
+            Icon::Failure,
+            Icon::Warning,
+            Icon::Pending,
+            Icon::Summary,
+            Icon::Link,
+            Icon::Preview,
+        ];
+        let mut output: Vec<String> = icons.iter().map(|&icon| style.icon(icon).into()).collect();
+        output.push(style.paint(Color::Red, "- removed"));
+        output.push(style.rule(10));
+        output.push(style.boxed(&[vec!["Title".into()], vec!["URL: x".into()]]));
+
+        for text in &output {
+            assert!(text.is_ascii(), "not ASCII: {:?}", text);
+            assert!(!text.contains('\x1b'), "escape code in {:?}", text);
+        }
+        assert_eq!(style.paint(Color::Green, "+ added"), "+ added");
+        let boxed = style.boxed(&[vec!["Hi".into()]]);
+        assert!(boxed.lines().all(|line| line.len() == BOX_WIDTH + 2));
+    }
+}
@@ -1,14 +1,19 @@
 //! Article - Content captured from external sources for summarization and review
 
+use std::collections::BTreeMap;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::{new_id, EntitySource, Note, NoteType, DEFAULT_SUMMARY_STYLE};
+
 /// The status of an Article in the processing pipeline
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ArticleStatus {
     /// Fetched, awaiting summarization
+    #[default]
     Pending,
     /// Has summary, awaiting review
     Summarized,
@@ -18,12 +23,6 @@ pub enum ArticleStatus {
     Archived,
 }
 
-impl Default for ArticleStatus {
-    fn default() -> Self {
-        Self::Pending
-    }
-}
-
 impl std::fmt::Display for ArticleStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -51,8 +50,11 @@ impl std::str::FromStr for ArticleStatus {
 
 /// Metadata about the source of an article
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "StoredSourceMetadata")]
 pub struct SourceMetadata {
-    /// Author of the article
+    /// Authors of the article, in byline order
+    pub authors: Vec<String>,
+    /// First author; deprecated alias of `authors`, kept for older readers
     pub author: Option<String>,
     /// Publication date
     pub published_at: Option<DateTime<Utc>>,
@@ -64,6 +66,136 @@ pub struct SourceMetadata {
     pub image_url: Option<String>,
 }
 
+impl SourceMetadata {
+    /// Set the authors, keeping the `author` alias in sync
+    pub fn with_authors<I, S>(mut self, authors: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.authors = authors.into_iter().map(Into::into).collect();
+        self.author = self.authors.first().cloned();
+        self
+    }
+}
+
+/// Serialized form of [`SourceMetadata`]; metadata stored before `authors`
+/// existed only has `author`
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct StoredSourceMetadata {
+    authors: Vec<String>,
+    author: Option<String>,
+    published_at: Option<DateTime<Utc>>,
+    site_name: Option<String>,
+    description: Option<String>,
+    image_url: Option<String>,
+}
+
+impl From<StoredSourceMetadata> for SourceMetadata {
+    fn from(stored: StoredSourceMetadata) -> Self {
+        let authors = if stored.authors.is_empty() {
+            stored.author.into_iter().collect()
+        } else {
+            stored.authors
+        };
+        SourceMetadata {
+            author: authors.first().cloned(),
+            authors,
+            published_at: stored.published_at,
+            site_name: stored.site_name,
+            description: stored.description,
+            image_url: stored.image_url,
+        }
+    }
+}
+
+/// Token usage and estimated cost of generating a summary
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SummaryUsage {
+    /// Model that produced the summary, if known
+    pub model: Option<String>,
+    /// Prompt tokens consumed
+    pub input_tokens: u64,
+    /// Completion tokens produced
+    pub output_tokens: u64,
+    /// Estimated cost in US dollars, if the model's pricing is known
+    pub estimated_cost_usd: Option<f64>,
+}
+
+impl SummaryUsage {
+    /// Create a usage record from token counts
+    pub fn new(input_tokens: u64, output_tokens: u64) -> Self {
+        Self {
+            input_tokens,
+            output_tokens,
+            ..Self::default()
+        }
+    }
+
+    /// Set the model, estimating the cost from its published pricing
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        let model = model.into();
+        self.estimated_cost_usd = estimate_cost_usd(&model, self.input_tokens, self.output_tokens);
+        self.model = Some(model);
+        self
+    }
+
+    /// Total tokens consumed
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+}
+
+/// Per-million-token prices (input, output) in USD, matched by model prefix.
+/// More specific prefixes come first.
+const MODEL_PRICING: &[(&str, f64, f64)] = &[
+    ("claude-opus-4", 15.0, 75.0),
+    ("claude-3-opus", 15.0, 75.0),
+    ("claude-sonnet-4", 3.0, 15.0),
+    ("claude-3-7-sonnet", 3.0, 15.0),
+    ("claude-3-5-sonnet", 3.0, 15.0),
+    ("claude-3-5-haiku", 0.8, 4.0),
+    ("claude-3-haiku", 0.25, 1.25),
+    ("gpt-4o-mini", 0.15, 0.6),
+    ("gpt-4o", 2.5, 10.0),
+];
+
+/// Estimate the USD cost of a call, or `None` if the model's pricing is unknown
+pub fn estimate_cost_usd(model: &str, input_tokens: u64, output_tokens: u64) -> Option<f64> {
+    MODEL_PRICING
+        .iter()
+        .find(|(prefix, _, _)| model.starts_with(prefix))
+        .map(|(_, input_price, output_price)| {
+            (input_tokens as f64 * input_price + output_tokens as f64 * output_price) / 1_000_000.0
+        })
+}
+
+/// How an approved Article is turned into a Note
+#[derive(Debug, Clone, Default)]
+pub struct ArticleNoteOptions {
+    /// Leave the article's full content out of the note
+    pub summary_only: bool,
+    /// Markdown template for the note body. `{{title}}`, `{{url}}`,
+    /// `{{summary}}` and `{{content}}` are filled in; `None` uses the
+    /// built-in Summary / Source / Full Content layout.
+    pub template: Option<String>,
+}
+
+impl ArticleNoteOptions {
+    /// Keep only the summary and source in the note
+    pub fn summary_only(mut self) -> Self {
+        self.summary_only = true;
+        self
+    }
+
+    /// Lay the note out with a template
+    pub fn with_template(mut self, template: impl Into<String>) -> Self {
+        self.template = Some(template.into());
+        self
+    }
+}
+
 /// An Article represents content captured from an external source for processing.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Article {
@@ -72,9 +204,27 @@ pub struct Article {
     pub title: String,
     pub raw_content: String,
     pub summary: Option<String>,
+    /// Every summary by style name (`tldr`, `bullet`, ...), including the
+    /// primary one under [`DEFAULT_SUMMARY_STYLE`]
+    #[serde(default)]
+    pub summaries: BTreeMap<String, String>,
     pub room_id: Option<Uuid>,
     pub status: ArticleStatus,
     pub source_metadata: SourceMetadata,
+    /// Token usage of the call that produced the current summary
+    #[serde(default)]
+    pub summary_usage: Option<SummaryUsage>,
+    /// When the article was actually read, independent of its review status
+    #[serde(default)]
+    pub read_at: Option<DateTime<Utc>>,
+    /// Whether `raw_content` was dropped to save space; the summary and URL
+    /// are kept so the article can be re-fetched
+    #[serde(default)]
+    pub content_purged: bool,
+    /// Why the last attempt to fetch the content failed; cleared once a
+    /// fetch succeeds
+    #[serde(default)]
+    pub last_error: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -84,19 +234,40 @@ impl Article {
     pub fn new(url: impl Into<String>, title: impl Into<String>, content: impl Into<String>) -> Self {
         let now = Utc::now();
         Self {
-            id: Uuid::new_v4(),
+            id: new_id(),
             url: url.into(),
             title: title.into(),
             raw_content: content.into(),
             summary: None,
+            summaries: BTreeMap::new(),
             room_id: None,
             status: ArticleStatus::Pending,
             source_metadata: SourceMetadata::default(),
+            summary_usage: None,
+            read_at: None,
+            content_purged: false,
+            last_error: None,
             created_at: now,
             updated_at: now,
         }
     }
 
+    /// A placeholder for a URL whose content couldn't be fetched, so the
+    /// failure is kept and can be retried. It has no content and is titled
+    /// after the URL.
+    pub fn failed_fetch(url: impl Into<String>, error: impl Into<String>) -> Self {
+        let url = url.into();
+        let mut article = Self::new(url.clone(), url, "");
+        article.last_error = Some(error.into());
+        article
+    }
+
+    /// Queue the article under `id` instead of a fresh one (see the `id` module)
+    pub fn with_id(mut self, id: Uuid) -> Self {
+        self.id = id;
+        self
+    }
+
     /// Set the room this article belongs to
     pub fn with_room(mut self, room_id: Uuid) -> Self {
         self.room_id = Some(room_id);
@@ -110,29 +281,228 @@ impl Article {
     }
 
     /// Add a summary to this article
+    ///
+    /// Clears any usage recorded for a previous summary.
     pub fn set_summary(&mut self, summary: impl Into<String>) {
-        self.summary = Some(summary.into());
+        self.set_summary_at(summary, Utc::now());
+    }
+
+    /// Add a summary, recording `now` as the update time
+    pub fn set_summary_at(&mut self, summary: impl Into<String>, now: DateTime<Utc>) {
+        self.set_styled_summary_at(DEFAULT_SUMMARY_STYLE, summary, now);
+    }
+
+    /// Add a summary in `style`; the default style replaces the primary
+    /// summary, any other is kept alongside it
+    pub fn set_styled_summary(&mut self, style: &str, summary: impl Into<String>) {
+        self.set_styled_summary_at(style, summary, Utc::now());
+    }
+
+    /// Add a summary in `style`, recording `now` as the update time
+    ///
+    /// Usage is only tracked for the primary summary, so it is cleared when
+    /// that one is replaced and left alone otherwise.
+    pub fn set_styled_summary_at(
+        &mut self,
+        style: &str,
+        summary: impl Into<String>,
+        now: DateTime<Utc>,
+    ) {
+        let summary = summary.into();
+        if style == DEFAULT_SUMMARY_STYLE {
+            self.summary = Some(summary.clone());
+            self.summary_usage = None;
+        }
+        self.summaries.insert(style.to_string(), summary);
         self.status = ArticleStatus::Summarized;
-        self.updated_at = Utc::now();
+        self.updated_at = now;
+    }
+
+    /// The summary in `style`, if one was made
+    pub fn summary_in(&self, style: &str) -> Option<&str> {
+        if style == DEFAULT_SUMMARY_STYLE {
+            return self.summary.as_deref();
+        }
+        self.summaries.get(style).map(String::as_str)
+    }
+
+    /// Add a summary along with the usage of the call that produced it
+    pub fn set_summary_with_usage(&mut self, summary: impl Into<String>, usage: SummaryUsage) {
+        self.set_summary(summary);
+        self.summary_usage = Some(usage);
+    }
+
+    /// Whether the article has been read
+    pub fn is_read(&self) -> bool {
+        self.read_at.is_some()
+    }
+
+    /// Record that the article has been read. This leaves the review status
+    /// (and `updated_at`) alone; reading an article doesn't approve it.
+    pub fn mark_read(&mut self) {
+        self.mark_read_at(Utc::now());
+    }
+
+    /// Record that the article was read at `now`, unless it already was
+    pub fn mark_read_at(&mut self, now: DateTime<Utc>) {
+        if self.read_at.is_none() {
+            self.read_at = Some(now);
+        }
     }
 
     /// Mark as reviewed
     pub fn mark_reviewed(&mut self) {
+        self.mark_reviewed_at(Utc::now());
+    }
+
+    /// Mark as reviewed at `now`
+    pub fn mark_reviewed_at(&mut self, now: DateTime<Utc>) {
         self.status = ArticleStatus::Reviewed;
-        self.updated_at = Utc::now();
+        self.updated_at = now;
     }
 
     /// Archive this article
     pub fn archive(&mut self) {
+        self.archive_at(Utc::now());
+    }
+
+    /// Archive this article at `now`
+    pub fn archive_at(&mut self, now: DateTime<Utc>) {
         self.status = ArticleStatus::Archived;
-        self.updated_at = Utc::now();
+        self.updated_at = now;
     }
+
+    /// Start this article's processing over: drop the summaries and usage
+    /// and put it back in the Pending queue. The content and read state stay.
+    pub fn requeue(&mut self) {
+        self.requeue_at(Utc::now());
+    }
+
+    /// Requeue this article at `now`
+    pub fn requeue_at(&mut self, now: DateTime<Utc>) {
+        self.summary = None;
+        self.summaries.clear();
+        self.summary_usage = None;
+        self.status = ArticleStatus::Pending;
+        self.updated_at = now;
+    }
+
+    /// Replace the content with a fresh fetch of the same URL
+    ///
+    /// Takes the title, content and metadata of `fetched` and clears any
+    /// fetch error or purge. Summaries, status and room are kept.
+    pub fn refetched(&mut self, fetched: Article) {
+        self.refetched_at(fetched, Utc::now());
+    }
+
+    /// Replace the content with a fresh fetch, recording `now` as the
+    /// update time
+    pub fn refetched_at(&mut self, fetched: Article, now: DateTime<Utc>) {
+        self.title = fetched.title;
+        self.raw_content = fetched.raw_content;
+        self.source_metadata = fetched.source_metadata;
+        self.content_purged = false;
+        self.last_error = None;
+        self.updated_at = now;
+    }
+
+    /// Record that fetching the content failed again
+    pub fn record_fetch_error(&mut self, error: impl Into<String>) {
+        self.record_fetch_error_at(error, Utc::now());
+    }
+
+    /// Record a failed fetch, with `now` as the update time
+    pub fn record_fetch_error_at(&mut self, error: impl Into<String>, now: DateTime<Utc>) {
+        self.last_error = Some(error.into());
+        self.updated_at = now;
+    }
+
+    /// Build the Note created when this article is approved into a room
+    pub fn to_note(
+        &self,
+        room_id: Uuid,
+        note_type: NoteType,
+        options: &ArticleNoteOptions,
+    ) -> Note {
+        let content = if options.summary_only { "" } else { self.raw_content.as_str() };
+        let body = match &options.template {
+            Some(template) => fill_template(template, |name| match name {
+                "title" => Some(self.title.as_str()),
+                "url" => Some(self.url.as_str()),
+                "summary" => Some(self.summary.as_deref().unwrap_or("")),
+                "content" => Some(content),
+                _ => None,
+            }),
+            None => {
+                let mut sections = Vec::new();
+                if let Some(summary) = &self.summary {
+                    sections.push(format!("## Summary\n\n{}", summary));
+                }
+                sections.push(format!("## Source\n\n{}", self.url));
+                if !options.summary_only {
+                    let heading = if self.summary.is_some() { "Full Content" } else { "Content" };
+                    sections.push(format!("## {}\n\n{}", heading, content));
+                }
+                sections.join("\n\n")
+            }
+        };
+
+        Note::new(room_id, &self.title, note_type)
+            .with_content(body)
+            .with_source(EntitySource::ArticleApproval)
+    }
+}
+
+/// Replace each `{{name}}` in `template` with `value(name)`, in one pass so
+/// text substituted in is never scanned for placeholders again. Unknown
+/// placeholders are left as they are.
+fn fill_template<'a>(template: &str, value: impl Fn(&str) -> Option<&'a str>) -> String {
+    let mut filled = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        let after = &rest[open + 2..];
+        let Some(close) = after.find("}}") else {
+            break;
+        };
+        filled.push_str(&rest[..open]);
+        match value(&after[..close]) {
+            Some(text) => filled.push_str(text),
+            None => filled.push_str(&rest[open..open + 2 + close + 2]),
+        }
+        rest = &after[close + 2..];
+    }
+    filled.push_str(rest);
+    filled
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_source_metadata_authors() {
+        let metadata = SourceMetadata::default().with_authors(["Ada", "Grace"]);
+        assert_eq!(metadata.author.as_deref(), Some("Ada"));
+
+        let json = serde_json::to_string(&metadata).unwrap();
+        let parsed: SourceMetadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.authors, vec!["Ada", "Grace"]);
+        assert_eq!(parsed.author.as_deref(), Some("Ada"));
+    }
+
+    #[test]
+    fn test_source_metadata_legacy_author() {
+        let parsed: SourceMetadata =
+            serde_json::from_str(r#"{"author": "Ada", "site_name": "Every"}"#).unwrap();
+        assert_eq!(parsed.authors, vec!["Ada"]);
+        assert_eq!(parsed.author.as_deref(), Some("Ada"));
+        assert_eq!(parsed.site_name.as_deref(), Some("Every"));
+
+        let empty: SourceMetadata = serde_json::from_str("{}").unwrap();
+        assert!(empty.authors.is_empty());
+        assert!(empty.author.is_none());
+    }
+
     #[test]
     fn test_new_article() {
         let article = Article::new(
@@ -155,4 +525,176 @@ mod tests {
         assert_eq!(article.status, ArticleStatus::Summarized);
         assert_eq!(article.summary.as_deref(), Some("This is a summary"));
     }
+
+    #[test]
+    fn test_summary_usage_cost() {
+        let usage = SummaryUsage::new(1_000_000, 100_000).with_model("claude-sonnet-4-20250514");
+        assert_eq!(usage.total_tokens(), 1_100_000);
+        let cost = usage.estimated_cost_usd.unwrap();
+        assert!((cost - 4.5).abs() < 1e-9);
+
+        let mini = SummaryUsage::new(1_000_000, 0).with_model("gpt-4o-mini");
+        assert!((mini.estimated_cost_usd.unwrap() - 0.15).abs() < 1e-9);
+
+        let unknown = SummaryUsage::new(10, 10).with_model("llama3");
+        assert!(unknown.estimated_cost_usd.is_none());
+    }
+
+    #[test]
+    fn test_resummarize_clears_usage() {
+        let mut article = Article::new("https://example.com/a", "A", "Content");
+        article.set_summary_with_usage("First", SummaryUsage::new(10, 5));
+        assert!(article.summary_usage.is_some());
+
+        article.set_summary("Second");
+        assert!(article.summary_usage.is_none());
+    }
+
+    #[test]
+    fn test_requeue_clears_derived_fields() {
+        let mut article = Article::new("https://example.com/a", "A", "Content");
+        article.set_summary_with_usage("Summary", SummaryUsage::new(10, 5).with_model("gpt-4o"));
+        article.set_styled_summary("tldr", "Short");
+        article.mark_read();
+        article.mark_reviewed();
+
+        article.requeue();
+        assert_eq!(article.status, ArticleStatus::Pending);
+        assert!(article.summary.is_none());
+        assert!(article.summary_usage.is_none());
+        assert!(article.summaries.is_empty());
+        assert_eq!(article.raw_content, "Content");
+        assert!(article.is_read());
+    }
+
+    #[test]
+    fn test_failed_fetch_and_refetch() {
+        let mut article = Article::failed_fetch("https://example.com/a", "HTTP 503");
+        assert_eq!(article.title, "https://example.com/a");
+        assert_eq!(article.status, ArticleStatus::Pending);
+        assert_eq!(article.last_error.as_deref(), Some("HTTP 503"));
+        assert!(article.raw_content.is_empty());
+
+        let fetched = Article::new("https://example.com/a", "Real title", "Content")
+            .with_metadata(SourceMetadata::default().with_authors(["Ada"]));
+        article.refetched(fetched);
+        assert_eq!(article.title, "Real title");
+        assert_eq!(article.raw_content, "Content");
+        assert_eq!(article.source_metadata.authors, ["Ada"]);
+        assert!(article.last_error.is_none());
+
+        let now = article.updated_at + chrono::Duration::hours(1);
+        article.record_fetch_error_at("HTTP 404", now);
+        assert_eq!(article.last_error.as_deref(), Some("HTTP 404"));
+        assert_eq!(article.updated_at, now);
+    }
+
+    #[test]
+    fn test_styled_summaries() {
+        let mut article = Article::new("https://example.com/a", "A", "Content");
+        article.set_summary_with_usage("Long", SummaryUsage::new(10, 5));
+        article.set_styled_summary("tldr", "Short");
+
+        assert_eq!(article.summary.as_deref(), Some("Long"));
+        assert!(article.summary_usage.is_some());
+        assert_eq!(article.summary_in("tldr"), Some("Short"));
+        assert_eq!(article.summary_in(DEFAULT_SUMMARY_STYLE), Some("Long"));
+        assert_eq!(article.summary_in("tweet"), None);
+
+        article.set_styled_summary(DEFAULT_SUMMARY_STYLE, "Longer");
+        assert_eq!(article.summary.as_deref(), Some("Longer"));
+        assert_eq!(article.summaries.len(), 2);
+        assert!(article.summary_usage.is_none());
+    }
+
+    #[test]
+    fn test_mark_read_keeps_review_status() {
+        let mut article = Article::new("https://example.com/a", "A", "Content");
+        assert!(!article.is_read());
+
+        article.mark_read();
+        let read_at = article.read_at;
+        assert!(article.is_read());
+        assert_eq!(article.status, ArticleStatus::Pending);
+
+        article.mark_read();
+        assert_eq!(article.read_at, read_at);
+    }
+
+    #[test]
+    fn test_mutators_use_given_time() {
+        use crate::{Clock, FixedClock};
+
+        let clock = FixedClock::new(Utc::now());
+        let mut article = Article::new("https://example.com/a", "A", "Content");
+
+        article.set_summary_at("Summary", clock.now());
+        assert_eq!(article.updated_at, clock.now());
+
+        clock.advance(chrono::Duration::hours(1));
+        article.mark_read_at(clock.now());
+        article.mark_reviewed_at(clock.now());
+        assert_eq!(article.read_at, Some(clock.now()));
+        assert_eq!(article.updated_at, clock.now());
+
+        clock.advance(chrono::Duration::days(30));
+        article.archive_at(clock.now());
+        assert_eq!(article.status, ArticleStatus::Archived);
+        assert_eq!(article.updated_at - article.read_at.unwrap(), chrono::Duration::days(30));
+    }
+
+    #[test]
+    fn test_to_note_default_layout() {
+        let room = Uuid::new_v4();
+        let mut article = Article::new("https://example.com/a", "A", "Body text");
+
+        let note = article.to_note(room, NoteType::Reference, &ArticleNoteOptions::default());
+        assert_eq!(note.content, "## Source\n\nhttps://example.com/a\n\n## Content\n\nBody text");
+        assert_eq!(note.note_type, NoteType::Reference);
+        assert_eq!(note.source, EntitySource::ArticleApproval);
+
+        article.set_summary("Short version");
+        let note = article.to_note(room, NoteType::Idea, &ArticleNoteOptions::default());
+        assert_eq!(
+            note.content,
+            "## Summary\n\nShort version\n\n## Source\n\nhttps://example.com/a\n\n\
+             ## Full Content\n\nBody text"
+        );
+        assert_eq!(note.note_type, NoteType::Idea);
+
+        let lean = ArticleNoteOptions::default().summary_only();
+        let note = article.to_note(room, NoteType::Reference, &lean);
+        assert_eq!(
+            note.content,
+            "## Summary\n\nShort version\n\n## Source\n\nhttps://example.com/a"
+        );
+    }
+
+    #[test]
+    fn test_to_note_with_template() {
+        let mut article = Article::new("https://example.com/a", "A", "Body text");
+        article.set_summary("Short version");
+        let options = ArticleNoteOptions::default()
+            .with_template("# {{title}}\n> {{summary}}\n{{url}}\n{{content}}");
+
+        let note = article.to_note(Uuid::new_v4(), NoteType::Log, &options);
+        assert_eq!(note.content, "# A\n> Short version\nhttps://example.com/a\nBody text");
+
+        let note = article.to_note(Uuid::new_v4(), NoteType::Log, &options.summary_only());
+        assert_eq!(note.content, "# A\n> Short version\nhttps://example.com/a\n");
+    }
+
+    #[test]
+    fn test_to_note_template_ignores_placeholders_in_values() {
+        let mut article = Article::new("https://example.com/j", "Jinja {{url}}", "Raw body");
+        article.set_summary("Write {{content}} or {{summary}} in a template");
+        let template = "{{title}}|{{summary}}|{{other}}|{{content";
+        let options = ArticleNoteOptions::default().with_template(template);
+
+        let note = article.to_note(Uuid::new_v4(), NoteType::Log, &options);
+        assert_eq!(
+            note.content,
+            "Jinja {{url}}|Write {{content}} or {{summary}} in a template|{{other}}|{{content"
+        );
+    }
 }
@@ -0,0 +1,25 @@
+//! Attachment - A file (image, PDF, ...) attached to a Note
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A file attached to a Note.
+///
+/// The bytes live in a content-addressed blob store, so attaching the same
+/// file twice (to one note or several) stores it only once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attachment {
+    pub id: Uuid,
+    pub note_id: Uuid,
+    /// Original file name, without directories
+    pub filename: String,
+    /// Hex-encoded SHA-256 of the file contents
+    pub content_hash: String,
+    pub mime_type: String,
+    /// Size in bytes
+    pub size: u64,
+    /// Location of the blob, relative to the attachment store
+    pub stored_path: String,
+    pub created_at: DateTime<Utc>,
+}
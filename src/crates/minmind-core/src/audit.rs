@@ -0,0 +1,71 @@
+//! Audit - Append-only record of status transitions
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// The kind of entity an audit entry refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEntity {
+    Article,
+    UserAction,
+}
+
+impl std::fmt::Display for AuditEntity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditEntity::Article => write!(f, "article"),
+            AuditEntity::UserAction => write!(f, "user_action"),
+        }
+    }
+}
+
+impl std::str::FromStr for AuditEntity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "article" => Ok(AuditEntity::Article),
+            "user_action" => Ok(AuditEntity::UserAction),
+            _ => Err(format!("Unknown audit entity: {}", s)),
+        }
+    }
+}
+
+/// A single recorded status transition.
+///
+/// `from_status` is `None` for the entry written when the entity is created.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub entity_type: AuditEntity,
+    pub entity_id: Uuid,
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub at: DateTime<Utc>,
+    /// What triggered the transition (see [`audit_sources`])
+    pub source: String,
+}
+
+/// Common sources recorded on audit entries
+pub mod audit_sources {
+    pub const MANUAL: &str = "manual";
+    pub const DASHBOARD: &str = "dashboard";
+    pub const SUMMARIZER: &str = "summarizer";
+    pub const PLAN_SYNC: &str = "plan_sync";
+    pub const IMPORT: &str = "import";
+    pub const CLEANUP: &str = "cleanup";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_entity_round_trip() {
+        for entity in [AuditEntity::Article, AuditEntity::UserAction] {
+            assert_eq!(entity.to_string().parse::<AuditEntity>().unwrap(), entity);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Clock - Where "now" comes from, so time-dependent logic can be tested
+
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Duration, Utc};
+
+/// A source of the current time
+pub trait Clock {
+    /// The current time
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The real wall clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to.
+///
+/// Clones share the same time, so a test can keep one handle and advance
+/// the clock it gave to a store.
+#[derive(Debug, Clone)]
+pub struct FixedClock {
+    now: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl FixedClock {
+    /// Create a clock stopped at `now`
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+
+    /// Jump to `now`
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().expect("clock lock poisoned") = now;
+    }
+
+    /// Move the clock forward (or back, for a negative duration)
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().expect("clock lock poisoned") += by;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().expect("clock lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_clock_clones_share_time() {
+        let start = DateTime::parse_from_rfc3339("2024-03-01T09:00:00Z").unwrap().to_utc();
+        let clock = FixedClock::new(start);
+        let handle = clock.clone();
+
+        assert_eq!(clock.now(), start);
+        handle.advance(Duration::hours(2));
+        assert_eq!(clock.now(), start + Duration::hours(2));
+        handle.set(start);
+        assert_eq!(clock.now(), start);
+    }
+}
@@ -0,0 +1,214 @@
+//! Date - Lenient parsing of timestamps found in article metadata and typed
+//! on the command line
+//!
+//! `<meta>` tags carry publication dates in many shapes. Parsing tries the
+//! unambiguous formats first and gives up with `None` rather than guessing.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+/// Epoch values above this are taken to be milliseconds rather than seconds
+const EPOCH_MILLIS_THRESHOLD: i64 = 100_000_000_000;
+
+/// Offset-aware formats that RFC 3339 parsing rejects (e.g. `+0000`)
+const OFFSET_FORMATS: &[&str] = &["%Y-%m-%dT%H:%M:%S%.f%z", "%Y-%m-%d %H:%M:%S%.f%z"];
+
+/// Formats without an offset; these are assumed to be UTC
+const NAIVE_DATETIME_FORMATS: &[&str] = &["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"];
+
+/// Date-only formats; these become midnight UTC
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%B %d, %Y", "%b %d, %Y"];
+
+/// Parse a timestamp in any of the formats commonly found in article metadata
+///
+/// Tries, in order: RFC 3339, RFC 2822, ISO 8601 with a compact offset,
+/// ISO 8601 without an offset, bare dates and epoch seconds (or milliseconds).
+/// Values without an offset are taken as UTC. Returns `None` for anything else.
+pub fn parse_flexible_datetime(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    if s.is_empty() {
+        return None;
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc2822(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    for format in OFFSET_FORMATS {
+        if let Ok(dt) = DateTime::parse_from_str(s, format) {
+            return Some(dt.with_timezone(&Utc));
+        }
+    }
+    for format in NAIVE_DATETIME_FORMATS {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(s, format) {
+            return Some(dt.and_utc());
+        }
+    }
+    for format in DATE_FORMATS {
+        if let Ok(date) = NaiveDate::parse_from_str(s, format) {
+            return date.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc());
+        }
+    }
+
+    if s.bytes().all(|b| b.is_ascii_digit()) {
+        let epoch: i64 = s.parse().ok()?;
+        return if epoch > EPOCH_MILLIS_THRESHOLD {
+            Utc.timestamp_millis_opt(epoch).single()
+        } else {
+            Utc.timestamp_opt(epoch, 0).single()
+        };
+    }
+
+    None
+}
+
+/// Parse a point in time given on the command line, relative to now
+///
+/// See [`parse_time_expr_at`].
+pub fn parse_time_expr(s: &str) -> Option<DateTime<Utc>> {
+    parse_time_expr_at(s, Utc::now())
+}
+
+/// Parse a point in time given on the command line, relative to `now`
+///
+/// Accepts `now`, `today` and `yesterday` (midnight UTC), an amount of time
+/// before `now` such as `30m`, `12h`, `7d` or `2w`, and anything
+/// [`parse_flexible_datetime`] understands. Bare dates mean the start of
+/// that day.
+pub fn parse_time_expr_at(s: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    let today = now.date_naive().and_time(NaiveTime::MIN).and_utc();
+    match s.to_lowercase().as_str() {
+        "now" => return Some(now),
+        "today" => return Some(today),
+        "yesterday" => return Some(today - Duration::days(1)),
+        _ => {}
+    }
+    if let Some(ago) = parse_relative(s) {
+        return now.checked_sub_signed(ago);
+    }
+    parse_flexible_datetime(s)
+}
+
+/// Parse an amount of time such as `7d` or `2w` (s, m, h, d or w)
+fn parse_relative(s: &str) -> Option<Duration> {
+    let unit = s.chars().last()?;
+    let amount: i64 = s[..s.len() - unit.len_utf8()].parse().ok()?;
+    if amount < 0 {
+        return None;
+    }
+    match unit.to_ascii_lowercase() {
+        's' => Duration::try_seconds(amount),
+        'm' => Duration::try_minutes(amount),
+        'h' => Duration::try_hours(amount),
+        'd' => Duration::try_days(amount),
+        'w' => Duration::try_weeks(amount),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(y: i32, mo: u32, d: u32, h: u32, mi: u32, s: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, s).unwrap()
+    }
+
+    #[test]
+    fn test_rfc3339() {
+        assert_eq!(
+            parse_flexible_datetime("2024-03-05T10:30:00+02:00"),
+            Some(utc(2024, 3, 5, 8, 30, 0))
+        );
+        assert_eq!(
+            parse_flexible_datetime("2024-03-05T10:30:00.123Z").map(|d| d.timestamp()),
+            Some(utc(2024, 3, 5, 10, 30, 0).timestamp())
+        );
+    }
+
+    #[test]
+    fn test_rfc2822() {
+        assert_eq!(
+            parse_flexible_datetime("Tue, 5 Mar 2024 10:30:00 -0500"),
+            Some(utc(2024, 3, 5, 15, 30, 0))
+        );
+    }
+
+    #[test]
+    fn test_compact_offset() {
+        assert_eq!(
+            parse_flexible_datetime("2024-03-05T10:30:00+0100"),
+            Some(utc(2024, 3, 5, 9, 30, 0))
+        );
+    }
+
+    #[test]
+    fn test_naive_datetime_is_utc() {
+        assert_eq!(
+            parse_flexible_datetime("2024-03-05T10:30:00"),
+            Some(utc(2024, 3, 5, 10, 30, 0))
+        );
+        assert_eq!(
+            parse_flexible_datetime("2024-03-05 10:30:00"),
+            Some(utc(2024, 3, 5, 10, 30, 0))
+        );
+    }
+
+    #[test]
+    fn test_bare_dates_are_midnight_utc() {
+        let midnight = Some(utc(2024, 3, 5, 0, 0, 0));
+        assert_eq!(parse_flexible_datetime("2024-03-05"), midnight);
+        assert_eq!(parse_flexible_datetime(" 2024/03/05 "), midnight);
+        assert_eq!(parse_flexible_datetime("March 5, 2024"), midnight);
+        assert_eq!(parse_flexible_datetime("Mar 05, 2024"), midnight);
+    }
+
+    #[test]
+    fn test_epoch() {
+        assert_eq!(parse_flexible_datetime("1709634600"), Some(utc(2024, 3, 5, 10, 30, 0)));
+        assert_eq!(parse_flexible_datetime("1709634600000"), Some(utc(2024, 3, 5, 10, 30, 0)));
+    }
+
+    #[test]
+    fn test_garbage_is_none() {
+        assert_eq!(parse_flexible_datetime(""), None);
+        assert_eq!(parse_flexible_datetime("yesterday"), None);
+        assert_eq!(parse_flexible_datetime("2024-13-45"), None);
+        assert_eq!(parse_flexible_datetime("99999999999999999999999"), None);
+    }
+
+    #[test]
+    fn test_time_expr_keywords() {
+        let now = utc(2024, 3, 5, 10, 30, 0);
+        assert_eq!(parse_time_expr_at("now", now), Some(now));
+        assert_eq!(parse_time_expr_at("Today", now), Some(utc(2024, 3, 5, 0, 0, 0)));
+        assert_eq!(parse_time_expr_at(" yesterday ", now), Some(utc(2024, 3, 4, 0, 0, 0)));
+    }
+
+    #[test]
+    fn test_time_expr_relative() {
+        let now = utc(2024, 3, 5, 10, 30, 0);
+        assert_eq!(parse_time_expr_at("30m", now), Some(utc(2024, 3, 5, 10, 0, 0)));
+        assert_eq!(parse_time_expr_at("12h", now), Some(utc(2024, 3, 4, 22, 30, 0)));
+        assert_eq!(parse_time_expr_at("7d", now), Some(utc(2024, 2, 27, 10, 30, 0)));
+        assert_eq!(parse_time_expr_at("2w", now), Some(utc(2024, 2, 20, 10, 30, 0)));
+        assert_eq!(parse_time_expr_at("0d", now), Some(now));
+        assert_eq!(parse_time_expr_at("-3d", now), None);
+        assert_eq!(parse_time_expr_at("7y", now), None);
+        assert_eq!(parse_time_expr_at("d", now), None);
+    }
+
+    #[test]
+    fn test_time_expr_dates_start_at_midnight() {
+        let now = utc(2024, 3, 5, 10, 30, 0);
+        assert_eq!(parse_time_expr_at("2024-03-01", now), Some(utc(2024, 3, 1, 0, 0, 0)));
+        assert_eq!(
+            parse_time_expr_at("2024-03-01T18:00:00Z", now),
+            Some(utc(2024, 3, 1, 18, 0, 0))
+        );
+        assert_eq!(parse_time_expr_at("last tuesday", now), None);
+        assert_eq!(parse_time_expr_at("", now), None);
+    }
+}
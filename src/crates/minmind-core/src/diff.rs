@@ -0,0 +1,115 @@
+//! Diff - What changed between two versions of a Note
+
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+
+use crate::Note;
+
+/// One line of a content diff
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "change", content = "line", rename_all = "lowercase")]
+pub enum DiffLine {
+    Unchanged(String),
+    Added(String),
+    Removed(String),
+}
+
+/// How a Note's title and content changed from one version to another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentDiff {
+    pub old_title: String,
+    pub new_title: String,
+    /// Every line of the content, old and new, in order
+    pub lines: Vec<DiffLine>,
+}
+
+impl ContentDiff {
+    /// Diff `old_content` against `new_content` line by line
+    pub fn between(
+        old_title: &str,
+        old_content: &str,
+        new_title: &str,
+        new_content: &str,
+    ) -> Self {
+        let lines = TextDiff::from_lines(old_content, new_content)
+            .iter_all_changes()
+            .map(|change| {
+                let line = change.value().trim_end_matches(['\r', '\n']).to_string();
+                match change.tag() {
+                    ChangeTag::Equal => DiffLine::Unchanged(line),
+                    ChangeTag::Insert => DiffLine::Added(line),
+                    ChangeTag::Delete => DiffLine::Removed(line),
+                }
+            })
+            .collect();
+
+        Self {
+            old_title: old_title.to_string(),
+            new_title: new_title.to_string(),
+            lines,
+        }
+    }
+
+    /// Whether the title is different
+    pub fn title_changed(&self) -> bool {
+        self.old_title != self.new_title
+    }
+
+    /// Whether any line of the content was added or removed
+    pub fn content_changed(&self) -> bool {
+        self.lines.iter().any(|line| !matches!(line, DiffLine::Unchanged(_)))
+    }
+
+    /// Whether the two versions are identical
+    pub fn is_empty(&self) -> bool {
+        !self.title_changed() && !self.content_changed()
+    }
+}
+
+impl Note {
+    /// What changed from `other`, an earlier version of this Note, to this one
+    pub fn diff(&self, other: &Note) -> ContentDiff {
+        ContentDiff::between(&other.title, &other.content, &self.title, &self.content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NoteType;
+
+    #[test]
+    fn test_diff_content_lines() {
+        let old = Note::new(uuid::Uuid::new_v4(), "Plan", NoteType::Idea)
+            .with_content("Goals\nShip it\nCelebrate\n");
+        let mut new = old.clone();
+        new.content = "Goals\nShip it carefully\nCelebrate\nRest\n".to_string();
+
+        let diff = new.diff(&old);
+        assert!(!diff.title_changed());
+        assert!(diff.content_changed());
+        assert_eq!(
+            diff.lines,
+            [
+                DiffLine::Unchanged("Goals".into()),
+                DiffLine::Removed("Ship it".into()),
+                DiffLine::Added("Ship it carefully".into()),
+                DiffLine::Unchanged("Celebrate".into()),
+                DiffLine::Added("Rest".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_title_only() {
+        let old = Note::new(uuid::Uuid::new_v4(), "Draft", NoteType::Idea).with_content("Body");
+        let mut new = old.clone();
+        new.title = "Final".to_string();
+
+        let diff = new.diff(&old);
+        assert!(diff.title_changed());
+        assert!(!diff.content_changed());
+        assert_eq!((diff.old_title.as_str(), diff.new_title.as_str()), ("Draft", "Final"));
+        assert!(old.diff(&old).is_empty());
+    }
+}
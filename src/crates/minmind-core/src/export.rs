@@ -0,0 +1,195 @@
+//! Export - A portable JSON snapshot of (part of) the Mind Palace
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{Article, CoreError, CoreResult, Link, Note, Room};
+
+/// Version of the export format, bumped on incompatible changes
+///
+/// - 1: the original format, with the version under `version`
+/// - 2: renamed it to `schema_version` and added `minmind_version`
+pub const EXPORT_FORMAT_VERSION: u32 = 2;
+
+/// Rooms with their notes, the links between those notes, and their articles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PalaceExport {
+    /// Version of MinMind that wrote the export ("unknown" before schema 2)
+    #[serde(default = "unknown_version")]
+    pub minmind_version: String,
+    pub schema_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub rooms: Vec<Room>,
+    pub notes: Vec<Note>,
+    /// Links whose source and target are both exported notes
+    pub links: Vec<Link>,
+    /// Links from an exported note to a note outside the export, kept only
+    /// when asked for; the other end's note is not included
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub external_links: Vec<Link>,
+    pub articles: Vec<Article>,
+}
+
+impl PalaceExport {
+    /// Create an empty export stamped with the current time
+    pub fn new() -> Self {
+        Self {
+            minmind_version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_version: EXPORT_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            rooms: Vec::new(),
+            notes: Vec::new(),
+            links: Vec::new(),
+            external_links: Vec::new(),
+            articles: Vec::new(),
+        }
+    }
+
+    /// Parse an export written by this or an earlier version of MinMind,
+    /// upgrading older schemas.
+    ///
+    /// Exports from a newer schema, or without a schema version at all, are
+    /// refused rather than read with fields silently dropped or misread.
+    pub fn from_json(json: &str) -> CoreResult<Self> {
+        let mut value: Value = serde_json::from_str(json)?;
+        let fields = value
+            .as_object_mut()
+            .ok_or_else(|| CoreError::InvalidInput("Export is not a JSON object".to_string()))?;
+        let version = fields
+            .get("schema_version")
+            .or_else(|| fields.get("version"))
+            .and_then(Value::as_u64)
+            .ok_or_else(|| {
+                CoreError::InvalidInput(
+                    "Export has no schema_version; is it a MinMind export?".to_string(),
+                )
+            })?;
+        if version == 0 || version > u64::from(EXPORT_FORMAT_VERSION) {
+            return Err(CoreError::InvalidInput(format!(
+                "Export uses schema version {}, but this MinMind reads versions 1 to {}; \
+                 upgrade MinMind to import it",
+                version, EXPORT_FORMAT_VERSION
+            )));
+        }
+
+        if version == 1 {
+            fields.remove("version");
+            fields.insert("schema_version".to_string(), EXPORT_FORMAT_VERSION.into());
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+/// What importing an export added; entities already present are not counted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub rooms: usize,
+    pub notes: usize,
+    pub links: usize,
+    pub articles: usize,
+}
+
+fn unknown_version() -> String {
+    "unknown".to_string()
+}
+
+impl Default for PalaceExport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_json_upgrades_schema_1() {
+        let json = r#"{
+            "version": 1,
+            "exported_at": "2025-01-10T08:00:00Z",
+            "rooms": [{
+                "id": "6f1c1f9e-3c8e-4d8e-9a57-8f0c1d2e3f40",
+                "name": "Old",
+                "slug": "old",
+                "description": null,
+                "parent_id": null,
+                "created_at": "2025-01-01T00:00:00Z",
+                "updated_at": "2025-01-01T00:00:00Z"
+            }],
+            "notes": [],
+            "links": [],
+            "articles": []
+        }"#;
+
+        let export = PalaceExport::from_json(json).unwrap();
+        assert_eq!(export.schema_version, EXPORT_FORMAT_VERSION);
+        assert_eq!(export.minmind_version, "unknown");
+        assert_eq!(export.rooms[0].name, "Old");
+    }
+
+    #[test]
+    fn test_from_json_round_trip() {
+        let export = PalaceExport::new();
+        let parsed = PalaceExport::from_json(&serde_json::to_string(&export).unwrap()).unwrap();
+        assert_eq!(parsed.minmind_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(parsed.exported_at, export.exported_at);
+    }
+
+    /// Enums must serialize to their `Display` form, which is how they are
+    /// stored, so exported JSON and the database agree
+    #[test]
+    fn test_enum_json_matches_display() {
+        use crate::{
+            ActionStatus, ArticleStatus, AuditEntity, ContentFormat, EntitySource, NoteType,
+            Priority, Provider, Status,
+        };
+
+        fn check<T: Serialize + std::fmt::Display>(values: &[T]) {
+            for value in values {
+                assert_eq!(serde_json::to_value(value).unwrap(), Value::String(value.to_string()));
+            }
+        }
+        check(&[
+            ActionStatus::Pending,
+            ActionStatus::InProgress,
+            ActionStatus::Completed,
+            ActionStatus::Skipped,
+        ]);
+        check(&[Priority::Low, Priority::Medium, Priority::High]);
+        check(&[
+            ArticleStatus::Pending,
+            ArticleStatus::Summarized,
+            ArticleStatus::Reviewed,
+            ArticleStatus::Archived,
+        ]);
+        check(&[AuditEntity::Article, AuditEntity::UserAction]);
+        check(&[ContentFormat::PlainText, ContentFormat::Markdown]);
+        check(&[NoteType::Idea, NoteType::Task, NoteType::Reference, NoteType::Log]);
+        check(&[Status::Active, Status::Completed, Status::Archived]);
+        check(&[Provider::Anthropic, Provider::OpenAI, Provider::Ollama, Provider::Custom]);
+        check(&[
+            EntitySource::Manual,
+            EntitySource::PlanSync,
+            EntitySource::ArticleApproval,
+            EntitySource::Import,
+            EntitySource::Api,
+        ]);
+    }
+
+    #[test]
+    fn test_from_json_refuses_unknown_schemas() {
+        let newer = format!(
+            r#"{{"schema_version": {}, "exported_at": "2030-01-01T00:00:00Z"}}"#,
+            EXPORT_FORMAT_VERSION + 1
+        );
+        let err = PalaceExport::from_json(&newer).unwrap_err();
+        assert!(matches!(err, CoreError::InvalidInput(_)));
+        assert!(err.to_string().contains("upgrade MinMind"));
+
+        let unversioned = r#"{"rooms": [], "notes": []}"#;
+        assert!(matches!(PalaceExport::from_json(unversioned), Err(CoreError::InvalidInput(_))));
+        assert!(matches!(PalaceExport::from_json("[]"), Err(CoreError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,61 @@
+//! Fuzzy - Approximate title matching for "did you mean" suggestions
+
+/// Edit distance between two strings, counted in characters
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// How far `title` is from `query`, or `None` if it's too far to suggest
+///
+/// Each query word is matched against its closest title word, ignoring case,
+/// and the distances are summed. A word may be off by about a third of its
+/// length (at least one edit) before the title stops counting as a near miss.
+pub fn fuzzy_title_distance(query: &str, title: &str) -> Option<usize> {
+    let title_words: Vec<String> = title.split_whitespace().map(str::to_lowercase).collect();
+    let mut total = 0;
+    let mut any = false;
+
+    for word in query.split_whitespace().map(str::to_lowercase) {
+        any = true;
+        let best = title_words.iter().map(|t| levenshtein(&word, t)).min()?;
+        if best > (word.chars().count() / 3).max(1) {
+            return None;
+        }
+        total += best;
+    }
+    any.then_some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("summary", "summary"), 0);
+        assert_eq!(levenshtein("summry", "summary"), 1);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("café", "cafe"), 1);
+    }
+
+    #[test]
+    fn test_fuzzy_title_distance() {
+        assert_eq!(fuzzy_title_distance("summry", "Weekly Summary"), Some(1));
+        assert_eq!(fuzzy_title_distance("weekly summry", "Weekly Summary"), Some(1));
+        assert_eq!(fuzzy_title_distance("banana", "Weekly Summary"), None);
+        assert_eq!(fuzzy_title_distance("", "Weekly Summary"), None);
+    }
+}
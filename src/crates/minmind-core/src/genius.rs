@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::{new_id, CoreError, CoreResult};
+
 /// The AI provider for a Genius
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -38,6 +40,56 @@ impl std::str::FromStr for Provider {
     }
 }
 
+/// Model families Anthropic names its models after
+const ANTHROPIC_FAMILIES: &[&str] = &["opus", "sonnet", "haiku", "instant"];
+
+/// Name prefixes of OpenAI's chat and reasoning models
+const OPENAI_PREFIXES: &[&str] = &["gpt-", "chatgpt-", "o1", "o3", "o4"];
+
+impl Provider {
+    /// Whether `model` plausibly names one of this provider's models.
+    ///
+    /// This is a heuristic based on naming conventions, meant for warnings
+    /// rather than errors since new models appear all the time. Ollama and
+    /// custom providers can serve anything, so any non-empty name passes.
+    pub fn is_valid_model(&self, model: &str) -> bool {
+        let model = model.trim().to_lowercase();
+        if model.is_empty() {
+            return false;
+        }
+        match self {
+            Provider::Anthropic => model.strip_prefix("claude-").is_some_and(|rest| {
+                // Either a named family (claude-3-5-sonnet-...) or a bare
+                // version (claude-2.1)
+                rest.split(['-', '.'])
+                    .any(|part| ANTHROPIC_FAMILIES.contains(&part))
+                    || rest.chars().all(|c| c.is_ascii_digit() || c == '.')
+            }),
+            Provider::OpenAI => OPENAI_PREFIXES.iter().any(|prefix| model.starts_with(prefix)),
+            Provider::Ollama | Provider::Custom => true,
+        }
+    }
+
+    /// The environment variable holding this provider's API key, or `None`
+    /// for providers that don't need one (a local Ollama, a custom endpoint)
+    pub fn api_key_env(&self) -> Option<&'static str> {
+        match self {
+            Provider::Anthropic => Some("ANTHROPIC_API_KEY"),
+            Provider::OpenAI => Some("OPENAI_API_KEY"),
+            Provider::Ollama | Provider::Custom => None,
+        }
+    }
+}
+
+/// Config key for the sampling temperature
+pub const TEMPERATURE_KEY: &str = "temperature";
+
+/// Config key for the response length limit
+pub const MAX_TOKENS_KEY: &str = "max_tokens";
+
+/// Highest temperature the supported providers accept
+pub const MAX_TEMPERATURE: f32 = 2.0;
+
 /// A Genius is an AI agent that can be consulted for help.
 /// Geniuses live in the "basement" of the Mind Palace.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,7 +106,7 @@ impl Genius {
     /// Create a new Genius with the given name, provider, and model
     pub fn new(name: impl Into<String>, provider: Provider, model: impl Into<String>) -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: new_id(),
             name: name.into(),
             provider,
             model: model.into(),
@@ -74,6 +126,47 @@ impl Genius {
         self.config = config;
         self
     }
+
+    /// Sampling temperature from the config, if set to a number
+    pub fn temperature(&self) -> Option<f32> {
+        self.config.get(TEMPERATURE_KEY)?.as_f64().map(|t| t as f32)
+    }
+
+    /// Response length limit from the config, if set to a positive integer
+    pub fn max_tokens(&self) -> Option<u32> {
+        let tokens = self.config.get(MAX_TOKENS_KEY)?.as_u64()?;
+        u32::try_from(tokens).ok().filter(|&tokens| tokens > 0)
+    }
+
+    /// Set the sampling temperature, between 0 and [`MAX_TEMPERATURE`]
+    pub fn with_temperature(self, temperature: f32) -> CoreResult<Self> {
+        if !(0.0..=MAX_TEMPERATURE).contains(&temperature) {
+            return Err(CoreError::InvalidInput(format!(
+                "temperature must be between 0 and {}, got {}",
+                MAX_TEMPERATURE, temperature
+            )));
+        }
+        // Widen through the decimal form so 0.7 is stored as 0.7, not 0.699999988
+        let temperature: f64 = temperature.to_string().parse().unwrap_or(temperature.into());
+        Ok(self.with_config_value(TEMPERATURE_KEY, serde_json::json!(temperature)))
+    }
+
+    /// Set the response length limit, which must be at least one token
+    pub fn with_max_tokens(self, max_tokens: u32) -> CoreResult<Self> {
+        if max_tokens == 0 {
+            return Err(CoreError::InvalidInput("max_tokens must be at least 1".to_string()));
+        }
+        Ok(self.with_config_value(MAX_TOKENS_KEY, serde_json::json!(max_tokens)))
+    }
+
+    /// Set one config key, keeping the others
+    fn with_config_value(mut self, key: &str, value: serde_json::Value) -> Self {
+        if !self.config.is_object() {
+            self.config = serde_json::json!({});
+        }
+        self.config[key] = value;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -88,6 +181,30 @@ mod tests {
         assert_eq!(genius.model, "claude-3-opus-20240229");
     }
 
+    #[test]
+    fn test_is_valid_model() {
+        for model in [
+            "claude-3-opus-20240229",
+            "claude-3-5-sonnet-latest",
+            "claude-sonnet-4-20250514",
+            "claude-haiku-4-5",
+            "claude-2.1",
+        ] {
+            assert!(Provider::Anthropic.is_valid_model(model), "{model}");
+        }
+        for model in ["gpt-4o", "gpt-4o-mini", "o1-preview", "o3-mini", "chatgpt-4o-latest"] {
+            assert!(Provider::OpenAI.is_valid_model(model), "{model}");
+        }
+        assert!(Provider::Ollama.is_valid_model("llama3.1:8b"));
+        assert!(Provider::Custom.is_valid_model("anything"));
+
+        assert!(!Provider::Anthropic.is_valid_model("gpt-4o"));
+        assert!(!Provider::Anthropic.is_valid_model("claude-3-opue"));
+        assert!(!Provider::OpenAI.is_valid_model("claude-3-opus-20240229"));
+        assert!(!Provider::OpenAI.is_valid_model("gtp-4"));
+        assert!(!Provider::Ollama.is_valid_model("  "));
+    }
+
     #[test]
     fn test_genius_with_system_prompt() {
         let genius = Genius::new("Helper", Provider::OpenAI, "gpt-4")
@@ -98,4 +215,35 @@ mod tests {
             Some("You are a helpful assistant.")
         );
     }
+
+    #[test]
+    fn test_typed_config() {
+        let genius = Genius::new("Tuned", Provider::OpenAI, "gpt-4o")
+            .with_config(serde_json::json!({"top_p": 0.9}))
+            .with_temperature(0.7)
+            .unwrap()
+            .with_max_tokens(1024)
+            .unwrap();
+
+        assert_eq!(genius.temperature(), Some(0.7));
+        assert_eq!(genius.config[TEMPERATURE_KEY], 0.7);
+        assert_eq!(genius.max_tokens(), Some(1024));
+        assert_eq!(genius.config["top_p"], 0.9);
+
+        let plain = Genius::new("Plain", Provider::OpenAI, "gpt-4o");
+        assert_eq!(plain.temperature(), None);
+        assert_eq!(plain.max_tokens(), None);
+    }
+
+    #[test]
+    fn test_typed_config_rejects_invalid_values() {
+        let genius = || Genius::new("Tuned", Provider::Anthropic, "claude-sonnet-4-20250514");
+        assert!(genius().with_temperature(2.5).is_err());
+        assert!(genius().with_temperature(-0.1).is_err());
+        assert!(genius().with_temperature(f32::NAN).is_err());
+        assert!(genius().with_max_tokens(0).is_err());
+
+        let negative = genius().with_config(serde_json::json!({"max_tokens": -5}));
+        assert_eq!(negative.max_tokens(), None);
+    }
 }
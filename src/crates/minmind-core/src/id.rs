@@ -0,0 +1,61 @@
+//! Id - How new entities get their ids
+//!
+//! Ids are random UUIDv4s by default. Building with the `time-ordered-ids`
+//! feature switches to UUIDv7s, whose leading 48 bits are the creation time
+//! in milliseconds, so `ORDER BY id` roughly follows creation order and
+//! index inserts stay append-mostly. The tradeoffs:
+//!
+//! - A v7 id reveals when its entity was created, e.g. in a shared export.
+//! - Ids created around the same time share their leading characters, so
+//!   short ids (which are prefixes) have to be longer to be unique.
+//! - Order is only approximate: ids made within one millisecond, or on
+//!   machines with skewed clocks, can sort out of creation order.
+//!
+//! Either way, every entity constructor has a `with_id` builder that
+//! replaces the generated id with a fixed one. Generated ids differ on every
+//! run in both modes, so tests should use `with_id` whenever they compare
+//! ids, short ids or ordering, to get exact, repeatable output.
+
+use uuid::Uuid;
+
+/// A fresh id for a new entity: UUIDv7 with the `time-ordered-ids` feature,
+/// UUIDv4 otherwise
+pub fn new_id() -> Uuid {
+    #[cfg(feature = "time-ordered-ids")]
+    {
+        Uuid::now_v7()
+    }
+    #[cfg(not(feature = "time-ordered-ids"))]
+    {
+        Uuid::new_v4()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Link, Note, NoteType, Room};
+
+    #[test]
+    fn test_with_id_overrides_generated_id() {
+        let room_id = Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap();
+        let note_id = Uuid::parse_str("00000000-0000-0000-0000-000000000002").unwrap();
+
+        let room = Room::new("Fixed").with_id(room_id);
+        let note = Note::new(room.id, "Fixed", NoteType::Idea).with_id(note_id);
+        let link = Link::new(note.id, new_id()).with_id(room_id);
+
+        assert_eq!((room.id, note.id, note.room_id, link.id), (room_id, note_id, room_id, room_id));
+        assert_ne!(new_id(), new_id());
+    }
+
+    #[cfg(feature = "time-ordered-ids")]
+    #[test]
+    fn test_time_ordered_ids_sort_by_creation() {
+        let first = new_id();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let second = new_id();
+        assert_eq!(second.get_version_num(), 7);
+        assert!(first < second);
+    }
+}
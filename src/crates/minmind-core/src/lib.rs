@@ -4,21 +4,47 @@
 //! the MinMind system: Rooms, Notes, Links, Geniuses, Articles, and UserActions.
 
 mod article;
+mod attachment;
+mod audit;
+mod clock;
+mod date;
+mod diff;
 mod error;
+mod export;
+mod fuzzy;
 mod genius;
+mod id;
 mod link;
 mod note;
 mod plan_parser;
+mod provenance;
+mod render;
 mod room;
+mod short_id;
 mod summary_config;
+mod todo_list;
 mod user_action;
+mod wikilink;
 
 pub use article::*;
+pub use attachment::*;
+pub use audit::*;
+pub use clock::*;
+pub use date::*;
+pub use diff::*;
 pub use error::*;
+pub use export::*;
+pub use fuzzy::*;
 pub use genius::*;
+pub use id::*;
 pub use link::*;
 pub use note::*;
 pub use plan_parser::*;
+pub use provenance::*;
+pub use render::*;
 pub use room::*;
+pub use short_id::*;
 pub use summary_config::*;
+pub use todo_list::*;
 pub use user_action::*;
+pub use wikilink::*;
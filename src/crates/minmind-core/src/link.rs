@@ -4,6 +4,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::{new_id, CoreError, CoreResult, Note};
+
 /// A Link represents a bidirectional connection between two Notes.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Link {
@@ -18,7 +20,7 @@ impl Link {
     /// Create a new Link between two Notes
     pub fn new(source_id: Uuid, target_id: Uuid) -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: new_id(),
             source_id,
             target_id,
             link_type: None,
@@ -26,6 +28,27 @@ impl Link {
         }
     }
 
+    /// Create a new Link, rejecting a link from a note to itself
+    pub fn try_new(source_id: Uuid, target_id: Uuid) -> CoreResult<Self> {
+        let link = Self::new(source_id, target_id);
+        link.validate()?;
+        Ok(link)
+    }
+
+    /// Check that this Link connects two different notes
+    pub fn validate(&self) -> CoreResult<()> {
+        if self.source_id == self.target_id {
+            return Err(CoreError::InvalidInput("cannot link a note to itself".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Record the link under `id` instead of a fresh one (see the `id` module)
+    pub fn with_id(mut self, id: Uuid) -> Self {
+        self.id = id;
+        self
+    }
+
     /// Set the type of this Link (e.g., "related", "blocks", "supports")
     pub fn with_type(mut self, link_type: impl Into<String>) -> Self {
         self.link_type = Some(link_type.into());
@@ -33,6 +56,40 @@ impl Link {
     }
 }
 
+/// A Note together with the Notes it links to and from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteWithLinks {
+    pub note: Note,
+    /// Links where this note is the source, paired with the target note
+    pub outgoing: Vec<(Link, Note)>,
+    /// Links where this note is the target, paired with the source note
+    pub incoming: Vec<(Link, Note)>,
+}
+
+/// What permanently deleting a Note took with it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteDeletionReport {
+    /// Number of links removed along with the note
+    pub removed_links: usize,
+    /// Titles of the notes on the other end of those links
+    pub linked_titles: Vec<String>,
+}
+
+/// How interconnected the notes are, as shown by `mm stats`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphStats {
+    /// Notes counted, excluding trashed ones
+    pub notes: u64,
+    /// Links touching at least one of those notes
+    pub links: u64,
+    /// Notes with no links to other live notes
+    pub isolated_notes: u64,
+    /// Average number of links per note
+    pub average_links_per_note: f64,
+    /// The note with the most links, as (id, title, link count)
+    pub most_connected: Option<(Uuid, String, u64)>,
+}
+
 /// Common link types for semantic connections
 pub mod link_types {
     pub const RELATED: &str = "related";
@@ -57,6 +114,16 @@ mod tests {
         assert!(link.link_type.is_none());
     }
 
+    #[test]
+    fn test_self_link_rejected() {
+        let note = Uuid::new_v4();
+        assert!(matches!(
+            Link::try_new(note, note),
+            Err(CoreError::InvalidInput(_))
+        ));
+        assert!(Link::try_new(note, Uuid::new_v4()).is_ok());
+    }
+
     #[test]
     fn test_link_with_type() {
         let source = Uuid::new_v4();
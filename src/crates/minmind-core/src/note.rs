@@ -4,8 +4,14 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::{new_id, CoreError, CoreResult, EntitySource};
+
 /// The type of a Note, determining its purpose and behavior
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// Only actionable types (tasks) have a status lifecycle: they start
+/// `Active` and move to `Completed` or `Archived`. Ideas, references and logs
+/// have no status and can't be completed or archived.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum NoteType {
     /// A thought to develop
@@ -18,6 +24,13 @@ pub enum NoteType {
     Log,
 }
 
+impl NoteType {
+    /// Whether notes of this type are worked through to completion
+    pub fn is_actionable(self) -> bool {
+        matches!(self, NoteType::Task)
+    }
+}
+
 impl std::fmt::Display for NoteType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -75,6 +88,39 @@ impl std::str::FromStr for Status {
     }
 }
 
+/// How a Note's content should be interpreted when displayed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentFormat {
+    /// Plain text, shown verbatim
+    #[serde(rename = "plain")]
+    PlainText,
+    /// Markdown, rendered when shown in a terminal
+    #[default]
+    Markdown,
+}
+
+impl std::fmt::Display for ContentFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContentFormat::PlainText => write!(f, "plain"),
+            ContentFormat::Markdown => write!(f, "markdown"),
+        }
+    }
+}
+
+impl std::str::FromStr for ContentFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "plain" | "text" => Ok(ContentFormat::PlainText),
+            "markdown" | "md" => Ok(ContentFormat::Markdown),
+            _ => Err(format!("Unknown content format: {}", s)),
+        }
+    }
+}
+
 /// A Note is the atomic unit of thought/information in MinMind.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Note {
@@ -82,34 +128,63 @@ pub struct Note {
     pub room_id: Uuid,
     pub title: String,
     pub content: String,
+    #[serde(default)]
+    pub content_format: ContentFormat,
     pub note_type: NoteType,
     pub status: Option<Status>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// When the Note was moved to the trash, if it has been
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// How the Note was created
+    #[serde(default)]
+    pub source: EntitySource,
 }
 
 impl Note {
-    /// Create a new Note in a Room
+    /// Create a new Note in a Room. Actionable notes start out `Active`.
     pub fn new(room_id: Uuid, title: impl Into<String>, note_type: NoteType) -> Self {
         let now = Utc::now();
         Self {
-            id: Uuid::new_v4(),
+            id: new_id(),
             room_id,
             title: title.into(),
             content: String::new(),
+            content_format: ContentFormat::default(),
             note_type,
-            status: None,
+            status: note_type.is_actionable().then_some(Status::Active),
             created_at: now,
             updated_at: now,
+            deleted_at: None,
+            source: EntitySource::default(),
         }
     }
 
+    /// Store the note under `id` instead of a fresh one (see the `id` module)
+    pub fn with_id(mut self, id: Uuid) -> Self {
+        self.id = id;
+        self
+    }
+
     /// Set the content of this Note
     pub fn with_content(mut self, content: impl Into<String>) -> Self {
         self.content = content.into();
         self
     }
 
+    /// Set how the content should be interpreted
+    pub fn with_content_format(mut self, format: ContentFormat) -> Self {
+        self.content_format = format;
+        self
+    }
+
+    /// Record how this Note was created
+    pub fn with_source(mut self, source: EntitySource) -> Self {
+        self.source = source;
+        self
+    }
+
     /// Set the status of this Note (for actionable notes)
     pub fn with_status(mut self, status: Status) -> Self {
         self.status = Some(status);
@@ -122,17 +197,49 @@ impl Note {
         self.updated_at = Utc::now();
     }
 
-    /// Mark a task as completed
-    pub fn complete(&mut self) {
-        self.status = Some(Status::Completed);
-        self.updated_at = Utc::now();
+    /// Mark a task as completed. Fails for notes that aren't actionable.
+    pub fn complete(&mut self) -> CoreResult<()> {
+        self.set_status(Status::Completed)
+    }
+
+    /// Archive a task. Fails for notes that aren't actionable.
+    pub fn archive(&mut self) -> CoreResult<()> {
+        self.set_status(Status::Archived)
     }
 
-    /// Archive this Note
-    pub fn archive(&mut self) {
-        self.status = Some(Status::Archived);
+    fn set_status(&mut self, status: Status) -> CoreResult<()> {
+        if !self.note_type.is_actionable() {
+            return Err(CoreError::InvalidInput(format!(
+                "{} notes have no status; only tasks can be {}",
+                self.note_type, status
+            )));
+        }
+        self.status = Some(status);
         self.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// A one-line gist of the content: the first non-empty line, without
+    /// markdown heading markers, truncated to at most `max` characters
+    pub fn content_summary(&self, max: usize) -> String {
+        self.content
+            .lines()
+            .map(|line| line.trim().trim_start_matches('#').trim())
+            .find(|line| !line.is_empty())
+            .map(|line| line.chars().take(max).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Normalize a tag name: trimmed, lowercased, without a leading `#`, and with
+/// inner whitespace collapsed to hyphens
+pub fn normalize_tag(tag: &str) -> CoreResult<String> {
+    let tag = tag.trim().trim_start_matches('#').to_lowercase();
+    let tag = tag.split_whitespace().collect::<Vec<_>>().join("-");
+    if tag.is_empty() {
+        return Err(CoreError::InvalidInput("Tag name cannot be empty".to_string()));
     }
+    Ok(tag)
 }
 
 #[cfg(test)]
@@ -151,11 +258,42 @@ mod tests {
     #[test]
     fn test_task_completion() {
         let room_id = Uuid::new_v4();
-        let mut note = Note::new(room_id, "Do something", NoteType::Task)
-            .with_status(Status::Active);
+        let mut note = Note::new(room_id, "Do something", NoteType::Task);
         
         assert_eq!(note.status, Some(Status::Active));
-        note.complete();
+        note.complete().unwrap();
         assert_eq!(note.status, Some(Status::Completed));
     }
+
+    #[test]
+    fn test_non_actionable_notes_have_no_lifecycle() {
+        let room_id = Uuid::new_v4();
+        for note_type in [NoteType::Idea, NoteType::Reference, NoteType::Log] {
+            assert!(!note_type.is_actionable());
+            let mut note = Note::new(room_id, "Thought", note_type);
+            assert_eq!(note.status, None);
+            assert!(matches!(note.complete(), Err(CoreError::InvalidInput(_))));
+            assert!(matches!(note.archive(), Err(CoreError::InvalidInput(_))));
+            assert_eq!(note.status, None);
+        }
+    }
+
+    #[test]
+    fn test_content_summary() {
+        let room_id = Uuid::new_v4();
+        let note = Note::new(room_id, "Gist", NoteType::Reference)
+            .with_content("\n  \n## Café notes\nsecond line");
+        assert_eq!(note.content_summary(80), "Café notes");
+        assert_eq!(note.content_summary(4), "Café");
+
+        let empty = Note::new(room_id, "Empty", NoteType::Idea);
+        assert_eq!(empty.content_summary(10), "");
+    }
+
+    #[test]
+    fn test_normalize_tag() {
+        assert_eq!(normalize_tag("  #Rust ").unwrap(), "rust");
+        assert_eq!(normalize_tag("Deep  Work").unwrap(), "deep-work");
+        assert!(normalize_tag(" # ").is_err());
+    }
 }
@@ -2,12 +2,19 @@
 //!
 //! Parses markdown files for `[USER]` markers and extracts them as UserActions.
 //! Supports various marker states: `[USER]`, `[USER:wip]`, `[USER:done]`, `[USER:skip]`
+//!
+//! Plain GitHub-style checkboxes (`- [ ]`, `- [x]`) are ignored unless asked
+//! for with [`PlanParseOptions::include_checkboxes`].
 
+use std::ops::Range;
 use std::path::Path;
 
 use crate::{ActionStatus, UserAction};
 
 /// A parsed user action from a markdown file
+///
+/// Spans are byte offsets into the whole parsed content, so
+/// `&content[action.span.clone()] == action.title`.
 #[derive(Debug, Clone)]
 pub struct ParsedAction {
     /// The action title (text after the marker)
@@ -16,6 +23,50 @@ pub struct ParsedAction {
     pub line_number: u32,
     /// The parsed status from the marker
     pub status: ActionStatus,
+    /// Where the title is in the source
+    pub span: Range<usize>,
+    /// Where the marker (`[USER:done]`, or `[x]` for a checkbox) is in the source
+    pub marker_span: Range<usize>,
+}
+
+/// A plain markdown checkbox (`- [ ] text` or `- [x] text`) from a plan file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedCheckbox {
+    /// The text after the checkbox
+    pub text: String,
+    /// Whether the box is ticked
+    pub checked: bool,
+    /// The line number in the source file (1-based)
+    pub line_number: u32,
+    /// Where the text is in the source, in bytes
+    pub span: Range<usize>,
+    /// Where the box itself (`[ ]` or `[x]`) is in the source, in bytes
+    pub box_span: Range<usize>,
+}
+
+impl From<ParsedCheckbox> for ParsedAction {
+    /// Ticked boxes become completed actions, empty ones pending
+    fn from(checkbox: ParsedCheckbox) -> Self {
+        Self {
+            title: checkbox.text,
+            line_number: checkbox.line_number,
+            status: if checkbox.checked {
+                ActionStatus::Completed
+            } else {
+                ActionStatus::Pending
+            },
+            span: checkbox.span,
+            marker_span: checkbox.box_span,
+        }
+    }
+}
+
+/// What to extract from a plan file besides `[USER]` markers
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlanParseOptions {
+    /// Also collect plain `- [ ]`/`- [x]` checkboxes into
+    /// [`ParseResult::checkboxes`]
+    pub include_checkboxes: bool,
 }
 
 /// Result of parsing a plan file
@@ -23,11 +74,41 @@ pub struct ParsedAction {
 pub struct ParseResult {
     /// The source file path
     pub source_file: String,
-    /// All parsed actions from the file
+    /// All parsed actions from the file, at most one per line
     pub actions: Vec<ParsedAction>,
+    /// Plain checkboxes, only collected with [`PlanParseOptions::include_checkboxes`]
+    pub checkboxes: Vec<ParsedCheckbox>,
+    /// Problems noticed while parsing, such as extra actions on a line
+    pub warnings: Vec<String>,
 }
 
 impl ParseResult {
+    /// Create an empty result for a source file
+    pub fn new(source_file: impl Into<String>) -> Self {
+        Self {
+            source_file: source_file.into(),
+            actions: Vec::new(),
+            checkboxes: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Add an action, keeping only the first one seen for each line.
+    ///
+    /// Extras are dropped with a warning so they can't become duplicate rows
+    /// on sync. Returns whether the action was kept.
+    pub fn push_action(&mut self, action: ParsedAction) -> bool {
+        if self.actions.iter().any(|a| a.line_number == action.line_number) {
+            self.warnings.push(format!(
+                "{}:{}: ignoring extra action '{}' on the same line",
+                self.source_file, action.line_number, action.title
+            ));
+            return false;
+        }
+        self.actions.push(action);
+        true
+    }
+
     /// Convert parsed actions to UserAction entities
     pub fn into_user_actions(self) -> Vec<UserAction> {
         self.actions
@@ -69,58 +150,158 @@ impl ParseResult {
 /// assert_eq!(result.actions.len(), 2);
 /// ```
 pub fn parse_plan_content(content: &str, source_file: impl Into<String>) -> ParseResult {
-    let source_file = source_file.into();
-    let mut actions = Vec::new();
+    parse_plan_content_with(content, source_file, PlanParseOptions::default())
+}
 
-    for (idx, line) in content.lines().enumerate() {
+/// Parse a markdown string for [USER] markers, and optionally plain checkboxes
+pub fn parse_plan_content_with(
+    content: &str,
+    source_file: impl Into<String>,
+    options: PlanParseOptions,
+) -> ParseResult {
+    let mut result = ParseResult::new(source_file);
+    let mut line_start = 0;
+
+    // Split like str::lines, but keep track of where each line starts
+    for (idx, raw) in content.split_inclusive('\n').enumerate() {
         let line_number = (idx + 1) as u32;
+        let line = raw.strip_suffix('\n').map_or(raw, |l| l.strip_suffix('\r').unwrap_or(l));
+        let offset = line_start + (line.len() - line.trim_start().len());
         let trimmed = line.trim();
-
-        // Match various [USER] patterns
-        if let Some(action) = parse_user_marker(trimmed, line_number) {
-            actions.push(action);
+        line_start += raw.len();
+
+        // Match various [USER] patterns; extras on one line are dropped with
+        // a warning by push_action
+        let actions = parse_user_markers(trimmed, line_number, offset);
+        if !actions.is_empty() {
+            for action in actions {
+                result.push_action(action);
+            }
+        } else if options.include_checkboxes {
+            if let Some(checkbox) = parse_checkbox(trimmed, line_number, offset) {
+                result.checkboxes.push(checkbox);
+            }
         }
     }
 
-    ParseResult {
-        source_file,
-        actions,
-    }
+    result
 }
 
-/// Parse a single line for a [USER] marker
-fn parse_user_marker(line: &str, line_number: u32) -> Option<ParsedAction> {
-    // Pattern: starts with "- [USER" (possibly after whitespace)
-    let line = line.trim_start_matches(|c: char| c == '-' || c.is_whitespace());
-    
-    if !line.starts_with("[USER") {
-        return None;
+/// Parse a single line, found at byte `offset` of the content, for [USER]
+/// markers: the one after the list bullet, then any later in the line, each
+/// of which ends the title before it
+fn parse_user_markers(line: &str, line_number: u32, offset: usize) -> Vec<ParsedAction> {
+    let Some(first) = user_marker_span(line) else {
+        return Vec::new();
+    };
+    let mut markers = vec![first];
+    while let Some(next) = inline_marker_span(line, markers[markers.len() - 1].end) {
+        markers.push(next);
     }
 
-    // Find the closing bracket
-    let close_bracket = line.find(']')?;
-    let marker = &line[..=close_bracket];
-    let title = line[close_bracket + 1..].trim().to_string();
-
-    if title.is_empty() {
-        return None;
-    }
+    let ends = markers.iter().skip(1).map(|next| next.start).chain([line.len()]);
+    markers
+        .iter()
+        .zip(ends)
+        .filter_map(|(marker_span, end)| {
+            let span = trimmed_span(&line[..end], marker_span.end);
+            if span.is_empty() {
+                return None;
+            }
+            Some(ParsedAction {
+                title: line[span.clone()].to_string(),
+                line_number,
+                status: marker_status(&line[marker_span.clone()]),
+                span: offset + span.start..offset + span.end,
+                marker_span: offset + marker_span.start..offset + marker_span.end,
+            })
+        })
+        .collect()
+}
 
-    let status = match marker.to_lowercase().as_str() {
+fn marker_status(marker: &str) -> ActionStatus {
+    match marker.to_lowercase().as_str() {
         "[user]" => ActionStatus::Pending,
         "[user:wip]" | "[user:inprogress]" | "[user:in_progress]" => ActionStatus::InProgress,
         "[user:done]" | "[user:completed]" => ActionStatus::Completed,
         "[user:skip]" | "[user:skipped]" => ActionStatus::Skipped,
         _ => ActionStatus::Pending,
-    };
+    }
+}
 
-    Some(ParsedAction {
-        title,
+/// Where `line[from..]` is once surrounding whitespace is trimmed off
+fn trimmed_span(line: &str, from: usize) -> Range<usize> {
+    let rest = &line[from..];
+    let start = from + (rest.len() - rest.trim_start().len());
+    start..start + rest.trim().len()
+}
+
+/// Where a line's action marker is: a `[USER]` or `[USER:...]` right after
+/// the list bullet. Look-alikes such as `[USERS]` are not markers, and a
+/// line without one has no actions even if it mentions `[USER]` later.
+fn user_marker_span(line: &str) -> Option<Range<usize>> {
+    let rest = line.trim_start_matches(|c: char| c == '-' || c.is_whitespace());
+    let start = line.len() - rest.len();
+    marker_len(rest).map(|len| start..start + len)
+}
+
+/// The next marker starting a word at or after byte `from`, on a line that
+/// already has an action
+fn inline_marker_span(line: &str, from: usize) -> Option<Range<usize>> {
+    let mut search = from;
+    while let Some(found) = line[search..].find("[USER") {
+        let start = search + found;
+        if line[..start].ends_with(char::is_whitespace) {
+            if let Some(len) = marker_len(&line[start..]) {
+                return Some(start..start + len);
+            }
+        }
+        search = start + "[USER".len();
+    }
+    None
+}
+
+/// The length of the `[USER]` or `[USER:...]` marker `text` starts with
+fn marker_len(text: &str) -> Option<usize> {
+    let tag = text.strip_prefix("[USER")?;
+    if !tag.starts_with([']', ':']) {
+        return None;
+    }
+    Some(text.find(']')? + 1)
+}
+
+/// Parse a single line, found at byte `offset` of the content, for a
+/// `- [ ]`/`- [x]` checkbox
+fn parse_checkbox(line: &str, line_number: u32, offset: usize) -> Option<ParsedCheckbox> {
+    let box_span = checkbox_span(line)?;
+    let checked = &line[box_span.clone()] != "[ ]";
+    let span = trimmed_span(line, box_span.end);
+    if span.is_empty() {
+        return None;
+    }
+
+    Some(ParsedCheckbox {
+        text: line[span.clone()].to_string(),
+        checked,
         line_number,
-        status,
+        span: offset + span.start..offset + span.end,
+        box_span: offset + box_span.start..offset + box_span.end,
     })
 }
 
+/// Where a line's `[ ]`/`[x]` box is, when the line is a `-`, `*` or `+`
+/// bullet starting with one
+fn checkbox_span(line: &str) -> Option<Range<usize>> {
+    let rest = line.trim_start();
+    let bullet = rest.get(..2)?;
+    if !matches!(bullet, "- " | "* " | "+ ") || !matches!(rest.get(2..5)?, "[ ]" | "[x]" | "[X]") {
+        return None;
+    }
+    // Every bullet is two bytes, so the box starts two bytes into `rest`
+    let start = line.len() - rest.len() + 2;
+    Some(start..start + 3)
+}
+
 /// Parse a plan file from the filesystem
 ///
 /// # Errors
@@ -158,7 +339,8 @@ pub fn update_plan_markers(
         .join("\n")
 }
 
-/// Update a single line's [USER] marker to a new status
+/// Update a single line's [USER] marker, or its `[ ]`/`[x]` box when it is a
+/// plain checkbox, to a new status
 fn update_line_marker(line: &str, new_status: ActionStatus) -> String {
     let new_marker = match new_status {
         ActionStatus::Pending => "[USER]",
@@ -167,16 +349,19 @@ fn update_line_marker(line: &str, new_status: ActionStatus) -> String {
         ActionStatus::Skipped => "[USER:skip]",
     };
 
-    // Find and replace the [USER...] pattern
-    if let Some(start) = line.find("[USER") {
-        if let Some(end) = line[start..].find(']') {
-            let before = &line[..start];
-            let after = &line[start + end + 1..];
-            return format!("{}{}{}", before, new_marker, after);
-        }
-    }
+    // A checkbox has no skipped state, so skipping ticks it off like completing
+    let new_box = match new_status {
+        ActionStatus::Pending | ActionStatus::InProgress => "[ ]",
+        ActionStatus::Completed | ActionStatus::Skipped => "[x]",
+    };
 
-    line.to_string()
+    match user_marker_span(line) {
+        Some(marker) => format!("{}{}{}", &line[..marker.start], new_marker, &line[marker.end..]),
+        None => match checkbox_span(line) {
+            Some(span) => format!("{}{}{}", &line[..span.start], new_box, &line[span.end..]),
+            None => line.to_string(),
+        },
+    }
 }
 
 /// Scan a directory for plan files and parse them all
@@ -194,7 +379,7 @@ pub fn scan_plans_directory(dir: impl AsRef<Path>) -> std::io::Result<Vec<ParseR
         let entry = entry?;
         let path = entry.path();
         
-        if path.is_file() && path.extension().map_or(false, |ext| ext == "md") {
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "md") {
             match parse_plan_file(&path) {
                 Ok(result) if !result.actions.is_empty() => {
                     results.push(result);
@@ -228,10 +413,10 @@ mod tests {
         ];
 
         for (line, expected) in cases {
-            let result = parse_user_marker(line, 1);
+            let result = parse_user_markers(line, 1, 0).into_iter().next();
             match expected {
                 Some((status, title)) => {
-                    let parsed = result.expect(&format!("Expected to parse: {}", line));
+                    let parsed = result.unwrap_or_else(|| panic!("Expected to parse: {}", line));
                     assert_eq!(parsed.status, status, "Status mismatch for: {}", line);
                     assert_eq!(parsed.title, title, "Title mismatch for: {}", line);
                 }
@@ -272,6 +457,24 @@ Some description
         assert_eq!(result.actions[2].status, ActionStatus::Pending);
     }
 
+    #[test]
+    fn test_spans_slice_back_to_source() {
+        let content = "# Plan\r\n\r\n  - [USER:done]   Review the désign  \r\n\
+                       - [ ] AI work\n- [USER] Ship it";
+
+        let result = parse_plan_content(content, "plans/spans.md");
+        let spans: Vec<_> = result
+            .actions
+            .iter()
+            .map(|a| (a.line_number, &content[a.marker_span.clone()], &content[a.span.clone()]))
+            .collect();
+        assert_eq!(
+            spans,
+            [(3, "[USER:done]", "Review the désign"), (5, "[USER]", "Ship it")]
+        );
+        assert!(result.actions.iter().all(|a| content[a.span.clone()] == a.title));
+    }
+
     #[test]
     fn test_update_plan_markers() {
         let content = r#"- [USER] Task one
@@ -290,6 +493,24 @@ Some description
         assert!(updated.contains("[USER:skip] Task three"));
     }
 
+    #[test]
+    fn test_update_plan_markers_ignores_prose_mentions() {
+        let content = "- [USER] Fill in the [USER:wip] column\n\
+                       See the [USERS] table - [USER] not an action\n\
+                       - [USERS] table";
+
+        let updates: Vec<_> = (1..=3).map(|line| (line, ActionStatus::Completed)).collect();
+        let updated = update_plan_markers(content, &updates);
+
+        assert_eq!(
+            updated,
+            "- [USER:done] Fill in the [USER:wip] column\n\
+             See the [USERS] table - [USER] not an action\n\
+             - [USERS] table"
+        );
+        assert_eq!(parse_plan_content(content, "test.md").actions.len(), 1);
+    }
+
     #[test]
     fn test_into_user_actions() {
         let content = "- [USER] Test action";
@@ -301,4 +522,84 @@ Some description
         assert_eq!(actions[0].source_file.as_deref(), Some("test.md"));
         assert_eq!(actions[0].line_number, Some(1));
     }
+
+    #[test]
+    fn test_parse_warns_on_extra_markers_in_a_line() {
+        let content = "- [USER] First [USER:done] First again\n\
+                       - [USER] Second\n\
+                       - [USER] Third [USERS] [USER]";
+        let result = parse_plan_content(content, "plans/dup.md");
+
+        let actions: Vec<_> =
+            result.actions.iter().map(|a| (a.line_number, a.title.as_str(), a.status)).collect();
+        assert_eq!(
+            actions,
+            [
+                (1, "First", ActionStatus::Pending),
+                (2, "Second", ActionStatus::Pending),
+                (3, "Third [USERS]", ActionStatus::Pending),
+            ]
+        );
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].contains("plans/dup.md:1"));
+        assert!(result.warnings[0].contains("First again"));
+    }
+
+    #[test]
+    fn test_checkboxes_are_opt_in() {
+        let content = "- [ ] Write docs\n- [x] Ship it\n* [X] Tag release\n\
+                       - [USER] Approve budget\n- [ ]\n- [link](url)";
+
+        let default = parse_plan_content(content, "plans/mixed.md");
+        assert_eq!(default.actions.len(), 1);
+        assert!(default.checkboxes.is_empty());
+
+        let options = PlanParseOptions { include_checkboxes: true };
+        let result = parse_plan_content_with(content, "plans/mixed.md", options);
+        assert_eq!(result.actions.len(), 1);
+        assert_eq!(result.actions[0].title, "Approve budget");
+        let checkboxes: Vec<_> = result
+            .checkboxes
+            .iter()
+            .map(|c| (c.text.as_str(), c.checked, c.line_number, &content[c.box_span.clone()]))
+            .collect();
+        assert_eq!(
+            checkboxes,
+            [
+                ("Write docs", false, 1, "[ ]"),
+                ("Ship it", true, 2, "[x]"),
+                ("Tag release", true, 3, "[X]"),
+            ]
+        );
+        assert!(result.checkboxes.iter().all(|c| content[c.span.clone()] == c.text));
+
+        let action = ParsedAction::from(result.checkboxes[1].clone());
+        assert_eq!(action.status, ActionStatus::Completed);
+    }
+
+    #[test]
+    fn test_update_plan_markers_ticks_checkboxes() {
+        let content = "- [ ] Write docs\n  * [x] Ship it\n\
+                       - [ ] Tag release\n- [USER] Approve budget";
+        let options = PlanParseOptions { include_checkboxes: true };
+
+        let updates = [
+            (1, ActionStatus::Completed),
+            (2, ActionStatus::Pending),
+            (3, ActionStatus::Skipped),
+            (4, ActionStatus::Completed),
+        ];
+        let updated = update_plan_markers(content, &updates);
+        assert_eq!(
+            updated,
+            "- [x] Write docs\n  * [ ] Ship it\n- [x] Tag release\n- [USER:done] Approve budget"
+        );
+
+        // Syncing the rewritten file again keeps the new statuses
+        let result = parse_plan_content_with(&updated, "plans/mixed.md", options);
+        let checked: Vec<_> =
+            result.checkboxes.iter().map(|c| (c.line_number, c.checked)).collect();
+        assert_eq!(checked, [(1, true), (2, false), (3, true)]);
+        assert_eq!(result.actions[0].status, ActionStatus::Completed);
+    }
 }
@@ -0,0 +1,66 @@
+//! Provenance - Where a Note or UserAction came from
+
+use serde::{Deserialize, Serialize};
+
+/// The path through which an entity was created
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntitySource {
+    /// Entered by hand (CLI, editor)
+    #[default]
+    Manual,
+    /// Extracted from a plan file by `mm todo sync`
+    PlanSync,
+    /// Created when an article was approved into a room
+    ArticleApproval,
+    /// Brought in from an export or another tool
+    Import,
+    /// Created by an external program through the API
+    Api,
+}
+
+impl std::fmt::Display for EntitySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EntitySource::Manual => write!(f, "manual"),
+            EntitySource::PlanSync => write!(f, "plan_sync"),
+            EntitySource::ArticleApproval => write!(f, "article_approval"),
+            EntitySource::Import => write!(f, "import"),
+            EntitySource::Api => write!(f, "api"),
+        }
+    }
+}
+
+impl std::str::FromStr for EntitySource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "manual" => Ok(EntitySource::Manual),
+            "plan_sync" | "plan" => Ok(EntitySource::PlanSync),
+            "article_approval" | "article" => Ok(EntitySource::ArticleApproval),
+            "import" => Ok(EntitySource::Import),
+            "api" => Ok(EntitySource::Api),
+            _ => Err(format!("Unknown source: {}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entity_source_round_trip() {
+        for source in [
+            EntitySource::Manual,
+            EntitySource::PlanSync,
+            EntitySource::ArticleApproval,
+            EntitySource::Import,
+            EntitySource::Api,
+        ] {
+            assert_eq!(source.to_string().parse::<EntitySource>().unwrap(), source);
+        }
+        assert_eq!("plan".parse::<EntitySource>().unwrap(), EntitySource::PlanSync);
+    }
+}
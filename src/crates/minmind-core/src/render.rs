@@ -0,0 +1,125 @@
+//! Render - Notes laid out as one markdown document, e.g. a whole Room
+
+use crate::Note;
+
+/// How [`render_room_markdown_with`] lays out the document
+#[derive(Debug, Clone, Default)]
+pub struct RoomMarkdownOptions {
+    /// Heading for the whole document, such as the Room's name
+    pub title: Option<String>,
+    /// Add a line with each note's type, status and last update under its
+    /// heading
+    pub include_metadata: bool,
+}
+
+impl RoomMarkdownOptions {
+    /// Open the document with a `#` heading
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Describe each note in a small line under its heading
+    pub fn with_metadata(mut self) -> Self {
+        self.include_metadata = true;
+        self
+    }
+}
+
+/// Concatenate `notes`, in the order given, into one markdown document with
+/// a `##` section per note
+pub fn render_room_markdown(notes: &[Note]) -> String {
+    render_room_markdown_with(notes, &RoomMarkdownOptions::default())
+}
+
+/// Concatenate `notes` into one markdown document, laid out per `options`.
+///
+/// Headings inside a note's content are pushed down two levels so they nest
+/// under the note's own `##` heading.
+pub fn render_room_markdown_with(notes: &[Note], options: &RoomMarkdownOptions) -> String {
+    let mut sections = Vec::new();
+    if let Some(title) = &options.title {
+        sections.push(format!("# {}", title));
+    }
+
+    for note in notes {
+        let mut section = format!("## {}", note.title);
+        if options.include_metadata {
+            let mut details = vec![note.note_type.to_string()];
+            if let Some(status) = note.status {
+                details.push(status.to_string());
+            }
+            details.push(format!("updated {}", note.updated_at.format("%Y-%m-%d")));
+            section.push_str(&format!("\n\n*{}*", details.join(" · ")));
+        }
+        let content = nest_headings(note.content.trim());
+        if !content.is_empty() {
+            section.push_str("\n\n");
+            section.push_str(&content);
+        }
+        sections.push(section);
+    }
+
+    let mut document = sections.join("\n\n");
+    document.push('\n');
+    document
+}
+
+/// Demote every ATX heading outside code fences by two levels (`#` becomes
+/// `###`), capped at markdown's deepest level
+fn nest_headings(content: &str) -> String {
+    let mut in_fence = false;
+    content
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+            }
+            let level = line.chars().take_while(|&c| c == '#').count();
+            let is_heading = (1..=6).contains(&level)
+                && line[level..].chars().next().is_none_or(char::is_whitespace);
+            if in_fence || !is_heading {
+                return line.to_string();
+            }
+            format!("{}{}", "#".repeat((level + 2).min(6)), &line[level..])
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NoteType;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_render_room_markdown() {
+        let room = Uuid::new_v4();
+        let notes = [
+            Note::new(room, "Plan", NoteType::Task).with_content("# Goals\nShip it\n"),
+            Note::new(room, "Empty", NoteType::Idea),
+            Note::new(room, "Snippet", NoteType::Reference)
+                .with_content("```sh\n# not a heading\n```\n#hashtag"),
+        ];
+
+        assert_eq!(
+            render_room_markdown(&notes),
+            "## Plan\n\n### Goals\nShip it\n\n## Empty\n\n\
+             ## Snippet\n\n```sh\n# not a heading\n```\n#hashtag\n"
+        );
+    }
+
+    #[test]
+    fn test_render_room_markdown_with_title_and_metadata() {
+        let note = Note::new(Uuid::new_v4(), "Plan", NoteType::Task).with_content("Ship it");
+        let options = RoomMarkdownOptions::default().with_title("Work").with_metadata();
+        let date = note.updated_at.format("%Y-%m-%d");
+
+        assert_eq!(
+            render_room_markdown_with(&[note], &options),
+            format!("# Work\n\n## Plan\n\n*task · active · updated {}*\n\nShip it\n", date)
+        );
+    }
+}
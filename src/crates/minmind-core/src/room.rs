@@ -4,25 +4,43 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::new_id;
+
 /// A Room is a conceptual space for organizing related thoughts and work.
 /// Rooms can nest to create hierarchical organization.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Room {
     pub id: Uuid,
     pub name: String,
+    /// URL-safe identifier derived from the name at creation time
+    #[serde(default)]
+    pub slug: String,
     pub description: Option<String>,
     pub parent_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// How much a Room contains, as shown by `mm room list --stats`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoomCounts {
+    /// Notes in the room, excluding trashed ones
+    pub notes: u64,
+    /// Articles filed under the room
+    pub articles: u64,
+    /// Active task notes in the room
+    pub todos: u64,
+}
+
 impl Room {
     /// Create a new Room with the given name
     pub fn new(name: impl Into<String>) -> Self {
         let now = Utc::now();
+        let name = name.into();
         Self {
-            id: Uuid::new_v4(),
-            name: name.into(),
+            id: new_id(),
+            slug: slugify(&name),
+            name,
             description: None,
             parent_id: None,
             created_at: now,
@@ -30,6 +48,12 @@ impl Room {
         }
     }
 
+    /// Create the room with `id` instead of a fresh one (see the `id` module)
+    pub fn with_id(mut self, id: Uuid) -> Self {
+        self.id = id;
+        self
+    }
+
     /// Set the description for this Room
     pub fn with_description(mut self, description: impl Into<String>) -> Self {
         self.description = Some(description.into());
@@ -43,12 +67,37 @@ impl Room {
     }
 
     /// Update the Room's name
+    ///
+    /// The slug is deliberately left unchanged: slugs back shareable links,
+    /// and renaming a room should not break links that are already out there.
     pub fn rename(&mut self, name: impl Into<String>) {
         self.name = name.into();
         self.updated_at = Utc::now();
     }
 }
 
+/// Derive a URL-safe slug from a name.
+///
+/// Lowercases the name, turns whitespace into hyphens and strips anything that
+/// isn't alphanumeric. Runs of hyphens are collapsed and trimmed from the ends.
+/// Falls back to `"room"` if nothing usable remains.
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.trim().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if (c.is_whitespace() || c == '-') && !slug.ends_with('-') && !slug.is_empty() {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "room".to_string()
+    } else {
+        slug.to_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -66,4 +115,21 @@ mod tests {
         let room = Room::new("Learning").with_description("A place for learning new things");
         assert_eq!(room.description.as_deref(), Some("A place for learning new things"));
     }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Work"), "work");
+        assert_eq!(slugify("Deep Work Ideas"), "deep-work-ideas");
+        assert_eq!(slugify("  Q&A: Notes!  "), "qa-notes");
+        assert_eq!(slugify("a - b"), "a-b");
+        assert_eq!(slugify("!!!"), "room");
+    }
+
+    #[test]
+    fn test_rename_keeps_slug() {
+        let mut room = Room::new("Reading List");
+        room.rename("Books");
+        assert_eq!(room.name, "Books");
+        assert_eq!(room.slug, "reading-list");
+    }
 }
@@ -0,0 +1,183 @@
+//! ShortId - Resolve abbreviated UUID prefixes to entities
+//!
+//! The CLI lets users type the first few characters of an id instead of the
+//! whole UUID. Resolution never guesses: a prefix matching several entities is
+//! reported as ambiguous rather than silently picking one.
+
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Minimum number of characters accepted for a prefix match
+pub const MIN_SHORT_ID_LEN: usize = 4;
+
+/// Why a short id couldn't be resolved to exactly one entity
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ShortIdError {
+    #[error("Id prefix '{prefix}' is too short (use at least {min} characters)")]
+    TooShort { prefix: String, min: usize },
+
+    #[error("No match for id '{0}'")]
+    NotFound(String),
+
+    #[error("Ambiguous id '{prefix}' matches: {}", format_matches(.matches))]
+    Ambiguous {
+        prefix: String,
+        /// Every matching entity as (id, label)
+        matches: Vec<(Uuid, String)>,
+    },
+}
+
+fn format_matches(matches: &[(Uuid, String)]) -> String {
+    matches
+        .iter()
+        .map(|(id, label)| format!("{} {}", id, label))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Resolve `input` against `candidates`.
+///
+/// A full UUID matches exactly. Otherwise `input` is treated as a
+/// case-insensitive prefix of the hyphenated UUID and must be at least
+/// `min_len` characters long. `key` returns each candidate's id and a label
+/// (e.g. its title) used when reporting ambiguity.
+pub fn resolve_short_id<'a, T>(
+    input: &str,
+    candidates: &'a [T],
+    min_len: usize,
+    key: impl Fn(&T) -> (Uuid, String),
+) -> Result<&'a T, ShortIdError> {
+    let input = input.trim();
+
+    if let Ok(uuid) = Uuid::parse_str(input) {
+        return candidates
+            .iter()
+            .find(|c| key(c).0 == uuid)
+            .ok_or_else(|| ShortIdError::NotFound(input.to_string()));
+    }
+
+    if input.chars().count() < min_len {
+        return Err(ShortIdError::TooShort {
+            prefix: input.to_string(),
+            min: min_len,
+        });
+    }
+
+    let prefix = input.to_lowercase();
+    let matches: Vec<&T> = candidates
+        .iter()
+        .filter(|c| key(c).0.to_string().starts_with(&prefix))
+        .collect();
+
+    match matches.as_slice() {
+        [] => Err(ShortIdError::NotFound(input.to_string())),
+        [only] => Ok(only),
+        _ => Err(ShortIdError::Ambiguous {
+            prefix: input.to_string(),
+            matches: matches.iter().map(|c| key(c)).collect(),
+        }),
+    }
+}
+
+/// The shortest prefix of `id` (at least `min_len` characters) that no other
+/// id in `among` shares, like git's abbreviated hashes.
+///
+/// `id` itself may appear in `among`; it is ignored when comparing.
+pub fn shortest_unique_prefix(id: Uuid, among: &[Uuid], min_len: usize) -> String {
+    let full = id.to_string();
+    let longest_shared = among
+        .iter()
+        .filter(|other| **other != id)
+        .map(|other| {
+            full.bytes()
+                .zip(other.to_string().bytes())
+                .take_while(|(a, b)| a == b)
+                .count()
+        })
+        .max()
+        .unwrap_or(0);
+
+    let len = (longest_shared + 1).max(min_len).min(full.len());
+    full[..len].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids() -> Vec<(Uuid, String)> {
+        vec![
+            (Uuid::parse_str("abcd1111-0000-0000-0000-000000000000").unwrap(), "First".into()),
+            (Uuid::parse_str("abcd2222-0000-0000-0000-000000000000").unwrap(), "Second".into()),
+            (Uuid::parse_str("ffff0000-0000-0000-0000-000000000000").unwrap(), "Third".into()),
+        ]
+    }
+
+    #[test]
+    fn test_resolve_unique_prefix() {
+        let items = ids();
+        let found = resolve_short_id("abcd1", &items, 4, |c| c.clone()).unwrap();
+        assert_eq!(found.1, "First");
+
+        let upper = resolve_short_id("FFFF", &items, 4, |c| c.clone()).unwrap();
+        assert_eq!(upper.1, "Third");
+    }
+
+    #[test]
+    fn test_resolve_full_uuid() {
+        let items = ids();
+        let full = items[1].0.to_string();
+        let found = resolve_short_id(&full, &items, 4, |c| c.clone()).unwrap();
+        assert_eq!(found.1, "Second");
+    }
+
+    #[test]
+    fn test_resolve_ambiguous() {
+        let items = ids();
+        let err = resolve_short_id("abcd", &items, 4, |c| c.clone()).unwrap_err();
+        match &err {
+            ShortIdError::Ambiguous { matches, .. } => assert_eq!(matches.len(), 2),
+            other => panic!("expected ambiguity, got {:?}", other),
+        }
+        let message = err.to_string();
+        assert!(message.starts_with("Ambiguous id 'abcd' matches: abcd1111"));
+        assert!(message.contains("First") && message.contains("Second"));
+    }
+
+    #[test]
+    fn test_resolve_too_short_and_missing() {
+        let items = ids();
+        assert_eq!(
+            resolve_short_id("ab", &items, 4, |c| c.clone()).unwrap_err(),
+            ShortIdError::TooShort { prefix: "ab".into(), min: 4 }
+        );
+        assert_eq!(
+            resolve_short_id("0000", &items, 4, |c| c.clone()).unwrap_err(),
+            ShortIdError::NotFound("0000".into())
+        );
+    }
+
+    #[test]
+    fn test_shortest_unique_prefix() {
+        let items: Vec<Uuid> = ids().into_iter().map(|(id, _)| id).collect();
+
+        // Shares "abcd" with its neighbour, so needs one more character
+        assert_eq!(shortest_unique_prefix(items[0], &items, 4), "abcd1");
+        // Nothing shared, so the minimum length applies
+        assert_eq!(shortest_unique_prefix(items[2], &items, 4), "ffff");
+        assert_eq!(shortest_unique_prefix(items[2], &items, 1), "f");
+        // A lone id only needs the minimum
+        assert_eq!(shortest_unique_prefix(items[0], &[], 6), "abcd11");
+    }
+
+    #[test]
+    fn test_unique_prefix_resolves_back() {
+        let items = ids();
+        let uuids: Vec<Uuid> = items.iter().map(|(id, _)| *id).collect();
+        for (id, label) in &items {
+            let prefix = shortest_unique_prefix(*id, &uuids, 4);
+            let found = resolve_short_id(&prefix, &items, 4, |c| c.clone()).unwrap();
+            assert_eq!(&found.1, label);
+        }
+    }
+}
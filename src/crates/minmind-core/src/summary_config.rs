@@ -4,6 +4,8 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::{new_id, CoreError, CoreResult};
+
 /// Configuration for how articles should be summarized.
 /// 
 /// SummaryConfig allows personalization at two levels:
@@ -15,18 +17,26 @@ pub struct SummaryConfig {
     pub name: String,
     pub system_prompt: String,
     pub room_id: Option<Uuid>,
+    /// The Genius to summarize with; `None` leaves the choice to the caller
+    #[serde(default)]
+    pub genius_id: Option<Uuid>,
     pub active: bool,
     pub created_at: DateTime<Utc>,
 }
 
 impl SummaryConfig {
     /// Create a new global SummaryConfig
+    ///
+    /// Trailing whitespace is trimmed from the prompt so it round-trips
+    /// through an editor unchanged; see [`SummaryConfig::try_new_global`] to
+    /// also reject a blank one.
     pub fn new_global(name: impl Into<String>, system_prompt: impl Into<String>) -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: new_id(),
             name: name.into(),
-            system_prompt: system_prompt.into(),
+            system_prompt: system_prompt.into().trim_end().to_string(),
             room_id: None,
+            genius_id: None,
             active: true,
             created_at: Utc::now(),
         }
@@ -39,15 +49,55 @@ impl SummaryConfig {
         room_id: Uuid,
     ) -> Self {
         Self {
-            id: Uuid::new_v4(),
+            id: new_id(),
             name: name.into(),
-            system_prompt: system_prompt.into(),
+            system_prompt: system_prompt.into().trim_end().to_string(),
             room_id: Some(room_id),
+            genius_id: None,
             active: true,
             created_at: Utc::now(),
         }
     }
 
+    /// Create a new global SummaryConfig, rejecting a blank prompt
+    pub fn try_new_global(name: impl Into<String>, system_prompt: &str) -> CoreResult<Self> {
+        Ok(Self::new_global(name, normalize_summary_prompt(system_prompt)?))
+    }
+
+    /// Create a new room-specific SummaryConfig, rejecting a blank prompt
+    pub fn try_new_for_room(
+        name: impl Into<String>,
+        system_prompt: &str,
+        room_id: Uuid,
+    ) -> CoreResult<Self> {
+        Ok(Self::new_for_room(name, normalize_summary_prompt(system_prompt)?, room_id))
+    }
+
+    /// Replace the prompt, rejecting a blank one
+    pub fn set_prompt(&mut self, system_prompt: &str) -> CoreResult<()> {
+        self.system_prompt = normalize_summary_prompt(system_prompt)?;
+        Ok(())
+    }
+
+    /// A warning if the prompt is longer than `max_len` characters, which
+    /// tends to crowd out the article or exceed the provider's limits
+    pub fn prompt_length_warning(&self, max_len: usize) -> Option<String> {
+        let len = self.system_prompt.chars().count();
+        (len > max_len).then(|| {
+            format!(
+                "The prompt is {} characters long (over {}); long prompts leave less room \
+                 for the article and may be rejected by the provider",
+                len, max_len
+            )
+        })
+    }
+
+    /// Summarize with a specific Genius
+    pub fn with_genius(mut self, genius_id: Uuid) -> Self {
+        self.genius_id = Some(genius_id);
+        self
+    }
+
     /// Deactivate this config
     pub fn deactivate(&mut self) {
         self.active = false;
@@ -64,6 +114,78 @@ impl SummaryConfig {
     }
 }
 
+/// Where the prompt for a summary came from, in order of precedence
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptSource {
+    /// Given for this one summary, overriding any config
+    Override,
+    /// The active config of the article's Room
+    RoomConfig { id: Uuid, name: String },
+    /// The active global config
+    GlobalConfig { id: Uuid, name: String },
+    /// [`DEFAULT_SUMMARY_PROMPT`], as no config is active
+    Default,
+}
+
+impl std::fmt::Display for PromptSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PromptSource::Override => write!(f, "the given prompt"),
+            PromptSource::RoomConfig { name, .. } => write!(f, "room config '{}'", name),
+            PromptSource::GlobalConfig { name, .. } => write!(f, "config '{}'", name),
+            PromptSource::Default => write!(f, "the default prompt"),
+        }
+    }
+}
+
+/// The prompt to summarize an article with, and where it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedPrompt {
+    pub prompt: String,
+    pub source: PromptSource,
+    /// The Genius bound to the active config, if any. An override replaces
+    /// only the prompt, so this is kept even then.
+    pub genius_id: Option<Uuid>,
+}
+
+impl ResolvedPrompt {
+    /// The prompt of an active config
+    pub fn from_config(config: SummaryConfig) -> Self {
+        let source = match config.room_id {
+            Some(_) => PromptSource::RoomConfig { id: config.id, name: config.name },
+            None => PromptSource::GlobalConfig { id: config.id, name: config.name },
+        };
+        Self {
+            prompt: config.system_prompt,
+            source,
+            genius_id: config.genius_id,
+        }
+    }
+
+    /// [`DEFAULT_SUMMARY_PROMPT`], for when no config is active
+    pub fn default_prompt() -> Self {
+        Self {
+            prompt: DEFAULT_SUMMARY_PROMPT.to_string(),
+            source: PromptSource::Default,
+            genius_id: None,
+        }
+    }
+}
+
+/// Prompt length above which [`SummaryConfig::prompt_length_warning`] warns
+/// by default, in characters
+pub const DEFAULT_MAX_PROMPT_LEN: usize = 8_000;
+
+/// A system prompt with its trailing whitespace trimmed, or an error if it
+/// has no text at all
+pub fn normalize_summary_prompt(prompt: &str) -> CoreResult<String> {
+    let prompt = prompt.trim_end();
+    if prompt.trim().is_empty() {
+        return Err(CoreError::InvalidInput("Summary prompt cannot be empty".to_string()));
+    }
+    Ok(prompt.to_string())
+}
+
 /// Default summary prompt that focuses on understanding "why" first
 pub const DEFAULT_SUMMARY_PROMPT: &str = r#"Summarize this article for someone who learns by understanding the "why" first, then concrete examples.
 
@@ -76,6 +198,39 @@ Structure your summary as:
 
 Keep the tone conversational but precise. Focus on signal over noise."#;
 
+/// The style of an article's primary summary, the one in `Article::summary`
+pub const DEFAULT_SUMMARY_STYLE: &str = "default";
+
+/// Summary styles besides the default, with the instruction added to the
+/// prompt to get each one
+pub const SUMMARY_STYLES: &[(&str, &str)] = &[
+    ("tldr", "Instead of that structure, reply with a TL;DR of one or two sentences."),
+    ("bullet", "Instead of that structure, reply with 3-7 short bullet points and nothing else."),
+    ("tweet", "Instead of that structure, reply with a single tweet of at most 280 characters."),
+];
+
+/// The instruction to append to the summary prompt for `style`, `None` for
+/// the default style
+pub fn summary_style_instruction(style: &str) -> CoreResult<Option<&'static str>> {
+    let style = style.trim().to_lowercase();
+    if style == DEFAULT_SUMMARY_STYLE {
+        return Ok(None);
+    }
+    SUMMARY_STYLES
+        .iter()
+        .find(|(name, _)| *name == style)
+        .map(|(_, instruction)| Some(*instruction))
+        .ok_or_else(|| {
+            let known: Vec<&str> = SUMMARY_STYLES.iter().map(|(name, _)| *name).collect();
+            CoreError::InvalidInput(format!(
+                "Unknown summary style '{}'; use {} or {}",
+                style,
+                DEFAULT_SUMMARY_STYLE,
+                known.join(", ")
+            ))
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -94,4 +249,26 @@ mod tests {
         assert!(!config.is_global());
         assert_eq!(config.room_id, Some(room_id));
     }
+
+    #[test]
+    fn test_prompt_validation() {
+        assert!(SummaryConfig::try_new_global("Blank", "  \n\t ").is_err());
+        let mut config = SummaryConfig::try_new_global("Tidy", "  Be brief.\n\n").unwrap();
+        assert_eq!(config.system_prompt, "  Be brief.");
+        assert!(config.set_prompt("").is_err());
+        assert_eq!(config.system_prompt, "  Be brief.");
+        assert!(config.prompt_length_warning(DEFAULT_MAX_PROMPT_LEN).is_none());
+
+        config.set_prompt(&"word ".repeat(2_000)).unwrap();
+        let warning = config.prompt_length_warning(DEFAULT_MAX_PROMPT_LEN).unwrap();
+        assert!(warning.contains("9999 characters"));
+        assert!(config.prompt_length_warning(10_000).is_none());
+    }
+
+    #[test]
+    fn test_summary_style_instruction() {
+        assert_eq!(summary_style_instruction("default").unwrap(), None);
+        assert!(summary_style_instruction(" TLDR ").unwrap().unwrap().contains("TL;DR"));
+        assert!(summary_style_instruction("haiku").is_err());
+    }
 }
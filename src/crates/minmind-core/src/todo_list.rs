@@ -0,0 +1,83 @@
+//! TodoList - Extract user actions from a plain todo list, one task per line
+//!
+//! Unlike plan files there are no `[USER]` markers: every non-empty line is a
+//! task. A leading `x ` or `[x]` marks it completed and `~` skipped; anything
+//! else is pending.
+
+use crate::{ActionStatus, EntitySource, UserAction};
+
+/// Parse one line of a todo list into its title and status, or `None` for a
+/// line with no task on it
+fn parse_todo_line(line: &str) -> Option<(&str, ActionStatus)> {
+    let line = line.trim();
+    let (title, status) = if let Some(rest) = line.strip_prefix("x ").or(line.strip_prefix("X ")) {
+        (rest, ActionStatus::Completed)
+    } else if let Some(rest) = line.strip_prefix("[x]").or(line.strip_prefix("[X]")) {
+        (rest, ActionStatus::Completed)
+    } else if let Some(rest) = line.strip_prefix('~') {
+        (rest, ActionStatus::Skipped)
+    } else {
+        (line, ActionStatus::Pending)
+    };
+
+    let title = title.trim();
+    (!title.is_empty()).then_some((title, status))
+}
+
+/// Turn each task in a todo list into a UserAction attributed to
+/// `source_file`, in the order they appear.
+///
+/// The actions carry no line number: the list isn't a plan file, so
+/// completing one of them later leaves the file untouched.
+pub fn parse_todo_list(content: &str, source_file: &str) -> Vec<UserAction> {
+    content
+        .lines()
+        .filter_map(parse_todo_line)
+        .map(|(title, status)| {
+            let mut action = UserAction::new(title).with_source(EntitySource::Import);
+            action.source_file = Some(source_file.to_string());
+            match status {
+                ActionStatus::Completed => action.complete(),
+                ActionStatus::Skipped => action.skip(),
+                _ => {}
+            }
+            action
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_todo_line_status_prefixes() {
+        assert_eq!(parse_todo_line("Buy milk"), Some(("Buy milk", ActionStatus::Pending)));
+        assert_eq!(parse_todo_line("x Call mom"), Some(("Call mom", ActionStatus::Completed)));
+        assert_eq!(parse_todo_line("[x] File taxes"), Some(("File taxes", ActionStatus::Completed)));
+        assert_eq!(parse_todo_line("  ~ Learn Go "), Some(("Learn Go", ActionStatus::Skipped)));
+        // Only a lone x followed by a space is a marker
+        assert_eq!(parse_todo_line("xray the box"), Some(("xray the box", ActionStatus::Pending)));
+        assert_eq!(parse_todo_line("   "), None);
+        assert_eq!(parse_todo_line("[x]"), None);
+    }
+
+    #[test]
+    fn test_parse_todo_list() {
+        let actions = parse_todo_list("Buy milk\n\nx Call mom\n~ Learn Go\n", "todos.txt");
+
+        let parsed: Vec<_> = actions.iter().map(|a| (a.title.as_str(), a.status)).collect();
+        assert_eq!(
+            parsed,
+            [
+                ("Buy milk", ActionStatus::Pending),
+                ("Call mom", ActionStatus::Completed),
+                ("Learn Go", ActionStatus::Skipped),
+            ]
+        );
+        assert!(actions[1].completed_at.is_some());
+        assert!(actions.iter().all(|a| a.source_file.as_deref() == Some("todos.txt")
+            && a.line_number.is_none()
+            && a.source == EntitySource::Import));
+    }
+}
@@ -4,17 +4,23 @@
 //! in the database for tracking. They sync bidirectionally between markdown
 //! files and SQLite.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::{new_id, EntitySource};
+
 /// The status of a user action
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// Serializes to the same names as `Display`, which is also how it's stored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ActionStatus {
     /// Not yet started
+    #[default]
     Pending,
     /// Currently being worked on
+    #[serde(rename = "in_progress", alias = "inprogress")]
     InProgress,
     /// Successfully completed
     Completed,
@@ -22,12 +28,6 @@ pub enum ActionStatus {
     Skipped,
 }
 
-impl Default for ActionStatus {
-    fn default() -> Self {
-        ActionStatus::Pending
-    }
-}
-
 impl std::fmt::Display for ActionStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -53,6 +53,38 @@ impl std::str::FromStr for ActionStatus {
     }
 }
 
+/// How urgent a user action is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+}
+
+impl std::fmt::Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Priority::Low => write!(f, "low"),
+            Priority::Medium => write!(f, "medium"),
+            Priority::High => write!(f, "high"),
+        }
+    }
+}
+
+impl std::str::FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Priority::Low),
+            "medium" | "med" => Ok(Priority::Medium),
+            "high" => Ok(Priority::High),
+            _ => Err(format!("Unknown priority: {}", s)),
+        }
+    }
+}
+
 /// A UserAction represents a task that requires human intervention.
 /// 
 /// These are typically extracted from plan files where they're marked with
@@ -74,8 +106,20 @@ pub struct UserAction {
     pub status: ActionStatus,
     /// When this action was created
     pub created_at: DateTime<Utc>,
+    /// When this action was first marked in progress (if ever)
+    #[serde(default)]
+    pub started_at: Option<DateTime<Utc>>,
     /// When this action was completed (if completed)
     pub completed_at: Option<DateTime<Utc>>,
+    /// How urgent this action is
+    #[serde(default)]
+    pub priority: Option<Priority>,
+    /// When this action should be done by
+    #[serde(default)]
+    pub due_date: Option<DateTime<Utc>>,
+    /// How this action was created
+    #[serde(default)]
+    pub source: EntitySource,
 }
 
 impl UserAction {
@@ -83,7 +127,7 @@ impl UserAction {
     pub fn new(title: impl Into<String>) -> Self {
         let now = Utc::now();
         Self {
-            id: Uuid::new_v4(),
+            id: new_id(),
             plan_id: None,
             source_file: None,
             line_number: None,
@@ -91,7 +135,11 @@ impl UserAction {
             description: None,
             status: ActionStatus::Pending,
             created_at: now,
+            started_at: None,
             completed_at: None,
+            priority: None,
+            due_date: None,
+            source: EntitySource::default(),
         }
     }
 
@@ -104,31 +152,73 @@ impl UserAction {
         Self {
             source_file: Some(source_file.into()),
             line_number: Some(line_number),
+            source: EntitySource::PlanSync,
             ..Self::new(title)
         }
     }
 
+    /// Track the action under `id` instead of a fresh one (see the `id` module)
+    pub fn with_id(mut self, id: Uuid) -> Self {
+        self.id = id;
+        self
+    }
+
     /// Set the plan ID
     pub fn with_plan_id(mut self, plan_id: Uuid) -> Self {
         self.plan_id = Some(plan_id);
         self
     }
 
+    /// Record how this action was created
+    pub fn with_source(mut self, source: EntitySource) -> Self {
+        self.source = source;
+        self
+    }
+
     /// Set the description
     pub fn with_description(mut self, description: impl Into<String>) -> Self {
         self.description = Some(description.into());
         self
     }
 
+    /// Set the priority
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Set the due date
+    pub fn with_due_date(mut self, due_date: DateTime<Utc>) -> Self {
+        self.due_date = Some(due_date);
+        self
+    }
+
     /// Mark this action as in progress
     pub fn start(&mut self) {
+        self.start_at(Utc::now());
+    }
+
+    /// Mark this action as in progress at `now`. Only the first start is
+    /// recorded, so pausing and resuming doesn't reset the cycle time.
+    pub fn start_at(&mut self, now: DateTime<Utc>) {
         self.status = ActionStatus::InProgress;
+        self.started_at.get_or_insert(now);
     }
 
     /// Mark this action as completed
     pub fn complete(&mut self) {
+        self.complete_at(Utc::now());
+    }
+
+    /// Mark this action as completed at `now`
+    pub fn complete_at(&mut self, now: DateTime<Utc>) {
         self.status = ActionStatus::Completed;
-        self.completed_at = Some(Utc::now());
+        self.completed_at = Some(now);
+    }
+
+    /// How long the action took from its first start to completion
+    pub fn cycle_time(&self) -> Option<Duration> {
+        Some(self.completed_at? - self.started_at?)
     }
 
     /// Mark this action as skipped
@@ -146,6 +236,17 @@ impl UserAction {
         matches!(self.status, ActionStatus::Pending)
     }
 
+    /// Whether the action is still open and its due date is before `now`.
+    /// Actions without a due date are never overdue.
+    pub fn is_overdue_at(&self, now: DateTime<Utc>) -> bool {
+        !self.is_done() && self.due_date.is_some_and(|due| due < now)
+    }
+
+    /// Whether the action is overdue right now
+    pub fn is_overdue(&self) -> bool {
+        self.is_overdue_at(Utc::now())
+    }
+
     /// Get the marker string for this action's status in markdown
     pub fn marker(&self) -> &'static str {
         match self.status {
@@ -157,10 +258,157 @@ impl UserAction {
     }
 }
 
+/// Criteria for querying user actions. Unset fields don't filter.
+#[derive(Debug, Clone, Default)]
+pub struct ActionFilter {
+    pub status: Option<ActionStatus>,
+    pub source_file: Option<String>,
+    pub priority: Option<Priority>,
+    /// Only actions due before this instant (actions without a due date are excluded)
+    pub due_before: Option<DateTime<Utc>>,
+    /// Only actions that are still open (pending or in progress)
+    pub open_only: bool,
+    /// Full-text query over title and description
+    pub search: Option<String>,
+}
+
+impl ActionFilter {
+    /// A filter matching every action
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only actions with this status
+    pub fn with_status(mut self, status: ActionStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Only actions from this source file
+    pub fn with_source_file(mut self, source_file: impl Into<String>) -> Self {
+        self.source_file = Some(source_file.into());
+        self
+    }
+
+    /// Only actions with this priority
+    pub fn with_priority(mut self, priority: Priority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Only actions due before this instant
+    pub fn with_due_before(mut self, due_before: DateTime<Utc>) -> Self {
+        self.due_before = Some(due_before);
+        self
+    }
+
+    /// Only actions that are still open (pending or in progress)
+    pub fn open_only(mut self) -> Self {
+        self.open_only = true;
+        self
+    }
+
+    /// Only actions matching this full-text query
+    pub fn with_search(mut self, query: impl Into<String>) -> Self {
+        self.search = Some(query.into());
+        self
+    }
+}
+
+/// Todos grouped for a standup-style report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoReport {
+    /// Completed actions are only those finished at or after this instant
+    pub since: DateTime<Utc>,
+    pub completed: Vec<UserAction>,
+    pub in_progress: Vec<UserAction>,
+    pub pending: Vec<UserAction>,
+}
+
+impl TodoReport {
+    /// Mean time from first start to completion across the completed actions
+    /// that were started, or `None` if none were
+    pub fn average_cycle_time(&self) -> Option<Duration> {
+        let times: Vec<Duration> =
+            self.completed.iter().filter_map(UserAction::cycle_time).collect();
+        let count = i32::try_from(times.len()).ok().filter(|&n| n > 0)?;
+        Some(times.into_iter().sum::<Duration>() / count)
+    }
+
+    /// Render the report as a markdown document
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# Todo report since {}\n", self.since.format("%Y-%m-%d %H:%M UTC"));
+        if let Some(average) = self.average_cycle_time() {
+            out.push_str(&format!("\nAverage completion time: {}\n", format_cycle_time(average)));
+        }
+        for (heading, actions, marker) in [
+            ("Completed", &self.completed, "[x]"),
+            ("In Progress", &self.in_progress, "[ ]"),
+            ("Pending", &self.pending, "[ ]"),
+        ] {
+            out.push_str(&format!("\n## {} ({})\n\n", heading, actions.len()));
+            if actions.is_empty() {
+                out.push_str("_None_\n");
+            }
+            for action in actions {
+                out.push_str(&format!("- {} {}", marker, action.title));
+                let mut details = Vec::new();
+                if let Some(priority) = action.priority {
+                    details.push(format!("{} priority", priority));
+                }
+                if let Some(due) = action.due_date {
+                    details.push(format!("due {}", due.format("%Y-%m-%d")));
+                }
+                if let Some(source) = &action.source_file {
+                    details.push(match action.line_number {
+                        Some(line) => format!("{}:{}", source, line),
+                        None => source.clone(),
+                    });
+                }
+                if !details.is_empty() {
+                    out.push_str(&format!(" ({})", details.join(", ")));
+                }
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+/// A cycle time at a glance: `2d 3h`, `3h 20m`, `45m`, or `<1m`
+fn format_cycle_time(duration: Duration) -> String {
+    let (days, hours, minutes) =
+        (duration.num_days(), duration.num_hours() % 24, duration.num_minutes() % 60);
+    match (days, hours, minutes) {
+        (0, 0, 0) => "<1m".to_string(),
+        (0, 0, m) => format!("{}m", m),
+        (0, h, m) => format!("{}h {}m", h, m),
+        (d, h, _) => format!("{}d {}h", d, h),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_todo_report_markdown() {
+        let mut done = UserAction::new("Ship release");
+        done.complete();
+        let wip = UserAction::new("Write notes").with_priority(Priority::High);
+        let report = TodoReport {
+            since: Utc::now(),
+            completed: vec![done],
+            in_progress: vec![wip],
+            pending: Vec::new(),
+        };
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("## Completed (1)\n\n- [x] Ship release\n"));
+        assert!(markdown.contains("- [ ] Write notes (high priority)\n"));
+        assert!(markdown.contains("## Pending (0)\n\n_None_\n"));
+    }
+
     #[test]
     fn test_new_user_action() {
         let action = UserAction::new("Configure API keys");
@@ -179,6 +427,7 @@ mod tests {
         );
         assert_eq!(action.source_file.as_deref(), Some("plans/001-foundation.md"));
         assert_eq!(action.line_number, Some(42));
+        assert_eq!(action.source, EntitySource::PlanSync);
     }
 
     #[test]
@@ -201,6 +450,14 @@ mod tests {
         assert_eq!(ActionStatus::Skipped.to_string(), "skipped");
     }
 
+    #[test]
+    fn test_action_status_serde_matches_display() {
+        assert_eq!(serde_json::to_string(&ActionStatus::InProgress).unwrap(), r#""in_progress""#);
+        // JSON written before the rename still reads
+        let legacy: ActionStatus = serde_json::from_str(r#""inprogress""#).unwrap();
+        assert_eq!(legacy, ActionStatus::InProgress);
+    }
+
     #[test]
     fn test_action_status_parse() {
         assert_eq!("pending".parse::<ActionStatus>().unwrap(), ActionStatus::Pending);
@@ -209,6 +466,24 @@ mod tests {
         assert_eq!("skip".parse::<ActionStatus>().unwrap(), ActionStatus::Skipped);
     }
 
+    #[test]
+    fn test_is_overdue_boundaries() {
+        let now = Utc::now();
+        let minute = chrono::Duration::minutes(1);
+
+        let just_missed = UserAction::new("Late").with_due_date(now - minute);
+        let almost_due = UserAction::new("Soon").with_due_date(now + minute);
+        let undated = UserAction::new("Someday");
+        let mut finished = UserAction::new("Done").with_due_date(now - minute);
+        finished.complete();
+
+        assert!(just_missed.is_overdue_at(now));
+        assert!(!almost_due.is_overdue_at(now));
+        assert!(almost_due.is_overdue_at(now + minute * 2));
+        assert!(!undated.is_overdue_at(now));
+        assert!(!finished.is_overdue_at(now));
+    }
+
     #[test]
     fn test_marker() {
         let mut action = UserAction::new("Test");
@@ -220,4 +495,36 @@ mod tests {
         action.complete();
         assert_eq!(action.marker(), "[USER:done]");
     }
+
+    #[test]
+    fn test_started_at_survives_restart() {
+        let started = Utc::now() - Duration::hours(30);
+        let mut action = UserAction::new("Test");
+        action.start_at(started);
+        action.status = ActionStatus::Pending;
+        action.start();
+        assert_eq!(action.started_at, Some(started));
+
+        action.complete_at(started + Duration::hours(27));
+        assert_eq!(action.completed_at, Some(started + Duration::hours(27)));
+        assert_eq!(action.cycle_time(), Some(Duration::hours(27)));
+
+        let report = TodoReport {
+            since: started,
+            completed: vec![action, UserAction::new("Never started")],
+            in_progress: Vec::new(),
+            pending: Vec::new(),
+        };
+        assert_eq!(report.average_cycle_time(), Some(Duration::hours(27)));
+        assert!(report.to_markdown().contains("Average completion time: 1d 3h\n"));
+    }
+
+    #[test]
+    fn test_complete_at() {
+        let done_at = Utc::now() - chrono::Duration::days(3);
+        let mut action = UserAction::new("Test");
+        action.complete_at(done_at);
+        assert_eq!(action.status, ActionStatus::Completed);
+        assert_eq!(action.completed_at, Some(done_at));
+    }
 }
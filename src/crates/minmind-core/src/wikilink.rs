@@ -0,0 +1,62 @@
+//! Wikilinks - `[[Title]]` references between notes in markdown content
+
+/// Point every `[[old]]` wikilink in `content` at `new` instead.
+///
+/// Targets match case-insensitively, and `[[old|label]]` and
+/// `[[old#heading]]` keep their label and heading. Returns the rewritten
+/// content and how many links changed.
+pub fn rewrite_wikilinks(content: &str, old: &str, new: &str) -> (String, usize) {
+    let old = old.trim().to_lowercase();
+    let mut out = String::with_capacity(content.len());
+    let mut rewritten = 0;
+    let mut rest = content;
+
+    while let Some(start) = rest.find("[[") {
+        let (before, link) = rest.split_at(start);
+        out.push_str(before);
+        let Some(end) = link[2..].find("]]").map(|end| end + 2) else {
+            rest = link;
+            break;
+        };
+        let inner = &link[2..end];
+        let target_len = inner.find(['|', '#']).unwrap_or(inner.len());
+        if inner[..target_len].trim().to_lowercase() == old {
+            out.push_str("[[");
+            out.push_str(new);
+            out.push_str(&inner[target_len..]);
+            out.push_str("]]");
+            rewritten += 1;
+        } else {
+            out.push_str(&link[..end + 2]);
+        }
+        rest = &link[end + 2..];
+    }
+    out.push_str(rest);
+
+    (out, rewritten)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_wikilinks() {
+        let content = "See [[Old Title]], [[old title|the plan]] and [[Old Title#Goals]].\n\
+                       Leave [[Other]] and [[Old Titles]] alone.";
+        let (rewritten, count) = rewrite_wikilinks(content, "Old Title", "New Title");
+        assert_eq!(count, 3);
+        assert_eq!(
+            rewritten,
+            "See [[New Title]], [[New Title|the plan]] and [[New Title#Goals]].\n\
+             Leave [[Other]] and [[Old Titles]] alone."
+        );
+    }
+
+    #[test]
+    fn test_rewrite_wikilinks_unterminated() {
+        let (rewritten, count) = rewrite_wikilinks("a [[Old", "Old", "New");
+        assert_eq!(count, 0);
+        assert_eq!(rewritten, "a [[Old");
+    }
+}
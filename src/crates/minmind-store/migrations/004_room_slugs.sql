@@ -0,0 +1,7 @@
+-- Room slugs
+-- Stable, URL-safe identifiers for rooms. Existing rows are backfilled
+-- by the migration runner since slug generation lives in Rust.
+
+ALTER TABLE rooms ADD COLUMN slug TEXT;
+
+CREATE UNIQUE INDEX idx_rooms_slug ON rooms(slug);
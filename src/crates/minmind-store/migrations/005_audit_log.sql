@@ -0,0 +1,15 @@
+-- Audit log
+-- Append-only record of article and user action status transitions.
+-- Rows are never updated or deleted, and outlive the entities they describe.
+
+CREATE TABLE audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    entity_type TEXT NOT NULL,
+    entity_id TEXT NOT NULL,
+    from_status TEXT,
+    to_status TEXT NOT NULL,
+    at TEXT NOT NULL,
+    source TEXT NOT NULL
+);
+
+CREATE INDEX idx_audit_log_entity ON audit_log(entity_id);
@@ -0,0 +1,4 @@
+-- Summary usage
+-- Token counts and estimated cost of the call that produced each summary
+
+ALTER TABLE articles ADD COLUMN summary_usage TEXT;  -- JSON, NULL if unknown
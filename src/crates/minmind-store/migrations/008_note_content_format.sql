@@ -0,0 +1,2 @@
+-- How note content is interpreted: 'markdown' (rendered) or 'plain' (verbatim)
+ALTER TABLE notes ADD COLUMN content_format TEXT NOT NULL DEFAULT 'markdown';
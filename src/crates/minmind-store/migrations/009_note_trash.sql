@@ -0,0 +1,4 @@
+-- Soft delete: trashed notes keep their row until purged
+ALTER TABLE notes ADD COLUMN deleted_at TEXT;
+
+CREATE INDEX idx_notes_deleted_at ON notes(deleted_at);
@@ -0,0 +1,2 @@
+-- Bind a summary config to the Genius that should run it
+ALTER TABLE summary_configs ADD COLUMN genius_id TEXT REFERENCES geniuses(id) ON DELETE SET NULL;
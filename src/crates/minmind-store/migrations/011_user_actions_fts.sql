@@ -0,0 +1,25 @@
+-- Full-text search for user actions
+CREATE VIRTUAL TABLE user_actions_fts USING fts5(
+    title, description, content='user_actions', content_rowid='rowid'
+);
+
+-- Index the actions that already exist
+INSERT INTO user_actions_fts(user_actions_fts) VALUES('rebuild');
+
+-- Triggers to keep FTS in sync
+CREATE TRIGGER user_actions_ai AFTER INSERT ON user_actions BEGIN
+    INSERT INTO user_actions_fts(rowid, title, description)
+    VALUES (new.rowid, new.title, new.description);
+END;
+
+CREATE TRIGGER user_actions_ad AFTER DELETE ON user_actions BEGIN
+    INSERT INTO user_actions_fts(user_actions_fts, rowid, title, description)
+    VALUES('delete', old.rowid, old.title, old.description);
+END;
+
+CREATE TRIGGER user_actions_au AFTER UPDATE ON user_actions BEGIN
+    INSERT INTO user_actions_fts(user_actions_fts, rowid, title, description)
+    VALUES('delete', old.rowid, old.title, old.description);
+    INSERT INTO user_actions_fts(rowid, title, description)
+    VALUES (new.rowid, new.title, new.description);
+END;
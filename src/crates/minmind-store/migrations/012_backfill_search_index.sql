@@ -0,0 +1,3 @@
+-- Index rows that predate the FTS triggers; they only cover later writes
+INSERT INTO notes_fts(notes_fts) VALUES('rebuild');
+INSERT INTO articles_fts(articles_fts) VALUES('rebuild');
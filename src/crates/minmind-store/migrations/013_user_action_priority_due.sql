@@ -0,0 +1,5 @@
+-- Priority and due dates for user actions
+ALTER TABLE user_actions ADD COLUMN priority TEXT;
+ALTER TABLE user_actions ADD COLUMN due_date TEXT;
+
+CREATE INDEX idx_user_actions_due ON user_actions(due_date);
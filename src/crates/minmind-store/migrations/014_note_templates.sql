@@ -0,0 +1,7 @@
+-- Note templates: default note bodies keyed by note type or a custom name
+
+CREATE TABLE note_templates (
+    name TEXT PRIMARY KEY,
+    content TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
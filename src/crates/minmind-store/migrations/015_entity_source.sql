@@ -0,0 +1,9 @@
+-- Provenance of notes and user actions
+ALTER TABLE notes ADD COLUMN source TEXT NOT NULL DEFAULT 'manual';
+ALTER TABLE user_actions ADD COLUMN source TEXT NOT NULL DEFAULT 'manual';
+
+-- Actions that point back into a plan file came from plan sync
+UPDATE user_actions SET source = 'plan_sync' WHERE source_file IS NOT NULL;
+
+CREATE INDEX idx_notes_entity_source ON notes(source);
+CREATE INDEX idx_user_actions_entity_source ON user_actions(source);
@@ -0,0 +1,13 @@
+-- Site and first author pulled out of the source_metadata JSON so articles
+-- from the same source can be found through an index
+ALTER TABLE articles ADD COLUMN site_name TEXT COLLATE NOCASE GENERATED ALWAYS AS (
+    CASE WHEN json_valid(source_metadata)
+         THEN NULLIF(TRIM(json_extract(source_metadata, '$.site_name')), '') END
+) VIRTUAL;
+ALTER TABLE articles ADD COLUMN author TEXT COLLATE NOCASE GENERATED ALWAYS AS (
+    CASE WHEN json_valid(source_metadata)
+         THEN NULLIF(TRIM(json_extract(source_metadata, '$.author')), '') END
+) VIRTUAL;
+
+CREATE INDEX idx_articles_site_name ON articles(site_name);
+CREATE INDEX idx_articles_author ON articles(author);
@@ -0,0 +1,2 @@
+-- When an article was actually read, separate from its review status
+ALTER TABLE articles ADD COLUMN read_at TEXT;
@@ -0,0 +1,15 @@
+-- Files attached to notes; the bytes live in a content-addressed blob store
+
+CREATE TABLE attachments (
+    id TEXT PRIMARY KEY,
+    note_id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    filename TEXT NOT NULL,
+    content_hash TEXT NOT NULL,
+    mime_type TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    stored_path TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX idx_attachments_note ON attachments(note_id);
+CREATE INDEX idx_attachments_hash ON attachments(content_hash);
@@ -0,0 +1,2 @@
+-- Archived articles can drop their raw content to save space
+ALTER TABLE articles ADD COLUMN content_purged INTEGER NOT NULL DEFAULT 0;
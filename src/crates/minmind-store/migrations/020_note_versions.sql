@@ -0,0 +1,26 @@
+-- Earlier versions of each note's title and content, numbered from 1.
+-- A trigger saves the old text whenever either changes, however the
+-- update was made.
+
+CREATE TABLE note_versions (
+    note_id TEXT NOT NULL REFERENCES notes(id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    -- When this version was written: the note's updated_at at the time
+    saved_at TEXT NOT NULL,
+    PRIMARY KEY (note_id, version)
+);
+
+CREATE TRIGGER notes_version AFTER UPDATE OF title, content ON notes
+WHEN old.title IS NOT new.title OR old.content IS NOT new.content
+BEGIN
+    INSERT INTO note_versions (note_id, version, title, content, saved_at)
+    VALUES (
+        old.id,
+        COALESCE((SELECT MAX(version) FROM note_versions WHERE note_id = old.id), 0) + 1,
+        old.title,
+        old.content,
+        old.updated_at
+    );
+END;
@@ -0,0 +1,10 @@
+-- When a user action was first marked in progress, for cycle times.
+-- Existing actions take it from the earliest such transition in the audit log.
+ALTER TABLE user_actions ADD COLUMN started_at TEXT;
+
+UPDATE user_actions SET started_at = (
+    SELECT MIN(at) FROM audit_log
+    WHERE entity_type = 'user_action'
+      AND entity_id = user_actions.id
+      AND to_status = 'in_progress'
+);
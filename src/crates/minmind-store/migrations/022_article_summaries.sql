@@ -0,0 +1,5 @@
+-- Articles can hold summaries in several styles, as a JSON object keyed by
+-- style name; the existing summary becomes the "default" style
+ALTER TABLE articles ADD COLUMN summaries TEXT NOT NULL DEFAULT '{}';
+
+UPDATE articles SET summaries = json_object('default', summary) WHERE summary IS NOT NULL;
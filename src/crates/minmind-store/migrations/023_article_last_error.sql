@@ -0,0 +1,3 @@
+-- Why the last attempt to fetch an article's content failed, so failed
+-- fetches can be kept as stubs and retried
+ALTER TABLE articles ADD COLUMN last_error TEXT;
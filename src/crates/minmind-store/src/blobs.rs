@@ -0,0 +1,83 @@
+//! Content-addressed file storage for note attachments
+//!
+//! Blobs are named by the SHA-256 of their contents and sharded by the first
+//! two hex digits: `attachments/ab/abcdef...`.
+
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+/// Name of the attachment directory, created next to the database file
+pub(crate) const ATTACHMENTS_DIR_NAME: &str = "attachments";
+
+/// Hex-encoded SHA-256 of `bytes`
+pub(crate) fn content_hash(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Path of the blob for `hash`, relative to the attachment directory
+fn blob_relative_path(hash: &str) -> String {
+    format!("{}/{}", &hash[..2], hash)
+}
+
+/// Store `bytes` under `root` unless a blob with this hash already exists.
+/// Returns the blob's path relative to `root`.
+pub(crate) fn write_blob(root: &Path, hash: &str, bytes: &[u8]) -> io::Result<String> {
+    let relative = blob_relative_path(hash);
+    let path = root.join(&relative);
+    if !path.exists() {
+        let dir = path.parent().expect("blob paths have a shard directory");
+        std::fs::create_dir_all(dir)?;
+        // Write under a temporary name first so a crash never leaves a
+        // truncated blob behind under its final name
+        let partial = dir.join(format!("{}.partial", hash));
+        std::fs::write(&partial, bytes)?;
+        std::fs::rename(&partial, &path)?;
+    }
+    Ok(relative)
+}
+
+/// Delete the blob for `hash` under `root`, along with its shard directory
+/// if that is left empty. Returns whether there was a blob to delete.
+pub(crate) fn remove_blob(root: &Path, hash: &str) -> io::Result<bool> {
+    let path = root.join(blob_relative_path(hash));
+    match std::fs::remove_file(&path) {
+        Ok(()) => {
+            if let Some(shard) = path.parent() {
+                let _ = std::fs::remove_dir(shard);
+            }
+            Ok(true)
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Delete blobs under `root` whose hash isn't in `referenced`, leaving
+/// `.partial` files from writes still in progress alone.
+/// Returns how many were removed.
+pub(crate) fn remove_unreferenced(root: &Path, referenced: &HashSet<String>) -> io::Result<usize> {
+    if !root.is_dir() {
+        return Ok(0);
+    }
+    let mut removed = 0;
+    for shard in std::fs::read_dir(root)? {
+        let shard = shard?.path();
+        if !shard.is_dir() {
+            continue;
+        }
+        for blob in std::fs::read_dir(&shard)? {
+            let blob: PathBuf = blob?.path();
+            let name = blob.file_name().map(|n| n.to_string_lossy().into_owned());
+            if name.is_some_and(|name| !name.ends_with(".partial") && !referenced.contains(&name)) {
+                std::fs::remove_file(&blob)?;
+                removed += 1;
+            }
+        }
+        // Drop the shard directory once it's empty
+        let _ = std::fs::remove_dir(&shard);
+    }
+    Ok(removed)
+}
@@ -1,12 +1,20 @@
 //! Error types for MinMind Store
 
+use std::path::PathBuf;
+
 use thiserror::Error;
 
 /// Store error type for MinMind persistence operations
 #[derive(Error, Debug)]
 pub enum StoreError {
     #[error("Database error: {0}")]
-    Database(#[from] rusqlite::Error),
+    Database(rusqlite::Error),
+
+    #[error("Database is open read-only")]
+    ReadOnly,
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
 
     #[error("Entity not found: {0}")]
     NotFound(String),
@@ -17,8 +25,30 @@ pub enum StoreError {
     #[error("Migration error: {0}")]
     Migration(String),
 
+    #[error("Database file is corrupt or not a MinMind database: {}", path.display())]
+    CorruptDatabase { path: PathBuf },
+
+    #[error("Invalid search query: {0}")]
+    InvalidQuery(String),
+
+    #[error("A room named '{0}' already exists there")]
+    DuplicateName(String),
+
+    #[error("Room {0} would become its own ancestor")]
+    RoomCycle(uuid::Uuid),
+
     #[error("Core error: {0}")]
     Core(#[from] minmind_core::CoreError),
 }
 
+impl From<rusqlite::Error> for StoreError {
+    /// Writes rejected by a read-only connection surface as [`StoreError::ReadOnly`]
+    fn from(err: rusqlite::Error) -> Self {
+        match err.sqlite_error_code() {
+            Some(rusqlite::ErrorCode::ReadOnly) => StoreError::ReadOnly,
+            _ => StoreError::Database(err),
+        }
+    }
+}
+
 pub type StoreResult<T> = Result<T, StoreError>;
@@ -2,9 +2,16 @@
 //!
 //! This crate provides local-first persistence for MinMind using SQLite.
 
+mod blobs;
 mod error;
 mod migrations;
+mod paths;
+mod progress;
 mod sqlite;
+mod transaction;
 
 pub use error::*;
+pub use paths::*;
+pub use progress::Progress;
 pub use sqlite::*;
+pub use transaction::ExclusiveTransaction;
@@ -1,9 +1,58 @@
 //! Database migrations for MinMind
 
-use rusqlite::Connection;
+use minmind_core::slugify;
+use rusqlite::{Connection, OptionalExtension};
 
 use crate::StoreResult;
 
+/// Every migration, in the order it is applied
+const MIGRATIONS: &[(&str, &str)] = &[
+    ("001_initial_schema", include_str!("../migrations/001_initial_schema.sql")),
+    ("002_articles", include_str!("../migrations/002_articles.sql")),
+    ("003_user_actions", include_str!("../migrations/003_user_actions.sql")),
+    ("004_room_slugs", include_str!("../migrations/004_room_slugs.sql")),
+    ("005_audit_log", include_str!("../migrations/005_audit_log.sql")),
+    ("006_summary_usage", include_str!("../migrations/006_summary_usage.sql")),
+    ("007_tags", include_str!("../migrations/007_tags.sql")),
+    ("008_note_content_format", include_str!("../migrations/008_note_content_format.sql")),
+    ("009_note_trash", include_str!("../migrations/009_note_trash.sql")),
+    ("010_summary_config_genius", include_str!("../migrations/010_summary_config_genius.sql")),
+    ("011_user_actions_fts", include_str!("../migrations/011_user_actions_fts.sql")),
+    ("012_backfill_search_index", include_str!("../migrations/012_backfill_search_index.sql")),
+    ("013_user_action_priority_due", include_str!("../migrations/013_user_action_priority_due.sql")),
+    ("014_note_templates", include_str!("../migrations/014_note_templates.sql")),
+    ("015_entity_source", include_str!("../migrations/015_entity_source.sql")),
+    ("016_article_source_columns", include_str!("../migrations/016_article_source_columns.sql")),
+    ("017_article_read_at", include_str!("../migrations/017_article_read_at.sql")),
+    ("018_attachments", include_str!("../migrations/018_attachments.sql")),
+    ("019_article_content_purged", include_str!("../migrations/019_article_content_purged.sql")),
+    ("020_note_versions", include_str!("../migrations/020_note_versions.sql")),
+    ("021_user_action_started_at", include_str!("../migrations/021_user_action_started_at.sql")),
+    ("022_article_summaries", include_str!("../migrations/022_article_summaries.sql")),
+    ("023_article_last_error", include_str!("../migrations/023_article_last_error.sql")),
+];
+
+/// Names of migrations not yet applied to this database, without changing it
+pub fn pending_migrations(conn: &Connection) -> StoreResult<Vec<&'static str>> {
+    let has_table: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_migrations')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !has_table {
+        return Ok(MIGRATIONS.iter().map(|(name, _)| *name).collect());
+    }
+
+    let mut stmt = conn.prepare("SELECT EXISTS(SELECT 1 FROM _migrations WHERE name = ?)")?;
+    let mut pending = Vec::new();
+    for (name, _) in MIGRATIONS {
+        if !stmt.query_row([name], |row| row.get::<_, bool>(0))? {
+            pending.push(*name);
+        }
+    }
+    Ok(pending)
+}
+
 /// Run all database migrations
 pub fn run_migrations(conn: &Connection) -> StoreResult<()> {
     // Create migrations table if it doesn't exist
@@ -17,13 +66,7 @@ pub fn run_migrations(conn: &Connection) -> StoreResult<()> {
     )?;
 
     // Run each migration in order
-    let migrations: &[(&str, &str)] = &[
-        ("001_initial_schema", include_str!("../migrations/001_initial_schema.sql")),
-        ("002_articles", include_str!("../migrations/002_articles.sql")),
-        ("003_user_actions", include_str!("../migrations/003_user_actions.sql")),
-    ];
-
-    for (name, sql) in migrations {
+    for (name, sql) in MIGRATIONS {
         let already_applied: bool = conn.query_row(
             "SELECT EXISTS(SELECT 1 FROM _migrations WHERE name = ?)",
             [name],
@@ -39,5 +82,39 @@ pub fn run_migrations(conn: &Connection) -> StoreResult<()> {
         }
     }
 
+    backfill_room_slugs(conn)?;
+
     Ok(())
 }
+
+/// Assign slugs to rooms created before slugs existed
+fn backfill_room_slugs(conn: &Connection) -> StoreResult<()> {
+    let mut stmt = conn.prepare("SELECT id, name FROM rooms WHERE slug IS NULL ORDER BY created_at")?;
+    let rooms = stmt
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (id, name) in rooms {
+        let slug = unique_room_slug(conn, &slugify(&name))?;
+        conn.execute("UPDATE rooms SET slug = ?1 WHERE id = ?2", [slug, id])?;
+    }
+
+    Ok(())
+}
+
+/// Find a free slug, appending `-2`, `-3`, ... to `base` on collision
+pub(crate) fn unique_room_slug(conn: &Connection, base: &str) -> StoreResult<String> {
+    let mut candidate = base.to_string();
+    let mut suffix = 2;
+    loop {
+        let taken = conn
+            .query_row("SELECT 1 FROM rooms WHERE slug = ?", [&candidate], |_| Ok(()))
+            .optional()?
+            .is_some();
+        if !taken {
+            return Ok(candidate);
+        }
+        candidate = format!("{}-{}", base, suffix);
+        suffix += 1;
+    }
+}
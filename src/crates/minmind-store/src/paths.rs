@@ -0,0 +1,68 @@
+//! Default locations for MinMind data on disk
+
+use std::path::{Path, PathBuf};
+
+use directories::{BaseDirs, ProjectDirs};
+
+/// File name of the MinMind database inside its data directory
+pub const DATABASE_FILE_NAME: &str = "minmind.db";
+
+/// Resolve the default database path
+///
+/// An existing `~/.minmind/minmind.db` keeps being used so older installs are
+/// not stranded. Otherwise the platform data directory is used
+/// (`$XDG_DATA_HOME/minmind` on Linux, `%APPDATA%\minmind\data` on Windows,
+/// `~/Library/Application Support/minmind` on macOS), falling back to
+/// `~/.minmind` when no data directory can be determined.
+///
+/// `MINMIND_DB` overrides all of this; it is handled by the caller.
+pub fn default_database_path() -> PathBuf {
+    database_path_in(
+        legacy_database_path(),
+        ProjectDirs::from("", "", "minmind").map(|dirs| dirs.data_dir().to_path_buf()),
+    )
+}
+
+/// [`default_database_path`] with the legacy path and the platform data
+/// directory passed in
+fn database_path_in(legacy: Option<PathBuf>, data_dir: Option<PathBuf>) -> PathBuf {
+    if let Some(path) = legacy.as_deref().filter(|p| p.exists()) {
+        return path.to_path_buf();
+    }
+
+    data_dir
+        .map(|dir| dir.join(DATABASE_FILE_NAME))
+        .or(legacy)
+        .unwrap_or_else(|| Path::new(".minmind").join(DATABASE_FILE_NAME))
+}
+
+/// The pre-XDG location, `~/.minmind/minmind.db`
+fn legacy_database_path() -> Option<PathBuf> {
+    BaseDirs::new().map(|dirs| dirs.home_dir().join(".minmind").join(DATABASE_FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_database_path_resolution() {
+        let home = tempfile::tempdir().unwrap();
+        let data = home.path().join(".local/share/minmind");
+        let legacy = home.path().join(".minmind").join(DATABASE_FILE_NAME);
+
+        // No legacy database yet: the data directory
+        assert_eq!(
+            database_path_in(Some(legacy.clone()), Some(data.clone())),
+            data.join(DATABASE_FILE_NAME)
+        );
+        // No data directory: the legacy location, or the working directory
+        assert_eq!(database_path_in(Some(legacy.clone()), None), legacy);
+        assert_eq!(database_path_in(None, None), Path::new(".minmind/minmind.db"));
+
+        // An existing legacy database wins over the data directory
+        std::fs::create_dir_all(legacy.parent().unwrap()).unwrap();
+        std::fs::write(&legacy, b"").unwrap();
+        assert_eq!(database_path_in(Some(legacy.clone()), Some(data)), legacy);
+    }
+}
@@ -0,0 +1,30 @@
+//! Progress reports from long-running Store operations
+//!
+//! Bulk operations like [`Store::import_json_with_progress`] call back with a
+//! [`Progress`] as they go, so a caller can show that a big palace is being
+//! worked through rather than hung. Each has a plain variant without the
+//! callback for callers that don't care.
+//!
+//! [`Store::import_json_with_progress`]: crate::Store::import_json_with_progress
+
+/// How far an operation has got through one of its phases
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// What is being worked through, e.g. "notes"
+    pub phase: &'static str,
+    /// Items of this phase handled so far
+    pub done: usize,
+    /// Items in this phase
+    pub total: usize,
+}
+
+impl Progress {
+    pub fn new(phase: &'static str, done: usize, total: usize) -> Self {
+        Self { phase, done, total }
+    }
+
+    /// Whether this is the last report of its phase
+    pub fn is_phase_done(&self) -> bool {
+        self.done >= self.total
+    }
+}
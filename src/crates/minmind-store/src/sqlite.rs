@@ -1,29 +1,120 @@
 //! SQLite implementation of MinMind storage
 
-use std::path::Path;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration as StdDuration;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use minmind_core::{
-    ActionStatus, Article, ArticleStatus, Genius, Link, Note, NoteType, Provider, Room,
-    SourceMetadata, Status, SummaryConfig, UserAction,
+    audit_sources, fuzzy_title_distance, new_id, normalize_summary_prompt, normalize_tag,
+    rewrite_wikilinks, shortest_unique_prefix, ActionFilter, ActionStatus, Article, ArticleStatus,
+    Attachment, AuditEntity, AuditEntry, Clock, ContentFormat, CoreError, EntitySource, Genius,
+    GraphStats, ImportReport, Link, Note, NoteDeletionReport, NoteType, NoteWithLinks, PalaceExport,
+    Priority, PromptSource, Provider, ResolvedPrompt, Room, RoomCounts, SourceMetadata, Status,
+    SummaryConfig, SummaryUsage, SystemClock, TodoReport, UserAction, DEFAULT_SUMMARY_STYLE,
+    MIN_SHORT_ID_LEN,
 };
-use rusqlite::{params, Connection, OptionalExtension};
+use rusqlite::{params, params_from_iter, Connection, OpenFlags, OptionalExtension, Row};
 use uuid::Uuid;
 
-use crate::{migrations, StoreError, StoreResult};
+use crate::blobs::{self, ATTACHMENTS_DIR_NAME};
+use crate::transaction::{ExclusiveTransaction, WriteTransaction};
+use crate::{migrations, Progress, StoreError, StoreResult};
+
+/// The full-text index tables and the base tables they index, kept in sync
+/// by triggers
+const SEARCH_INDEXES: [(&str, &str); 3] = [
+    ("notes_fts", "notes"),
+    ("articles_fts", "articles"),
+    ("user_actions_fts", "user_actions"),
+];
+
+/// How long a handle waits for another connection's write lock by default
+pub const DEFAULT_BUSY_TIMEOUT: StdDuration = StdDuration::from_secs(5);
 
 /// SQLite-backed store for MinMind
 pub struct Store {
     conn: Connection,
+    min_id_prefix: usize,
+    read_only: bool,
+    /// Where attachment blobs are kept; `None` for in-memory stores
+    attachments_dir: Option<PathBuf>,
+    /// Hashes of blobs whose attachment rows were deleted, to remove once
+    /// the deletion has committed
+    orphaned_blobs: RefCell<Vec<String>>,
+    /// Source of the timestamps the store writes itself (trash, moves, archiving)
+    clock: Box<dyn Clock + Send>,
 }
 
 impl Store {
     /// Open or create a MinMind database at the given path
+    ///
+    /// Returns [`StoreError::CorruptDatabase`] if the file exists but is not
+    /// a readable SQLite database.
     pub fn open(path: impl AsRef<Path>) -> StoreResult<Self> {
-        let conn = Connection::open(path)?;
-        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
-        migrations::run_migrations(&conn)?;
-        Ok(Self { conn })
+        let path = path.as_ref();
+        let corrupt = |e: StoreError| match e {
+            StoreError::Database(ref err) if is_corruption(err) => StoreError::CorruptDatabase {
+                path: path.to_path_buf(),
+            },
+            other => other,
+        };
+
+        let conn = Connection::open(path).map_err(|e| corrupt(e.into()))?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")
+            .map_err(|e| corrupt(e.into()))?;
+        conn.busy_timeout(DEFAULT_BUSY_TIMEOUT)?;
+        migrations::run_migrations(&conn).map_err(corrupt)?;
+        Ok(Self {
+            conn,
+            min_id_prefix: MIN_SHORT_ID_LEN,
+            read_only: false,
+            attachments_dir: attachments_dir_for(path),
+            orphaned_blobs: RefCell::new(Vec::new()),
+            clock: Box::new(SystemClock),
+        })
+    }
+
+    /// Open an existing MinMind database without the ability to change it
+    ///
+    /// Every write fails with [`StoreError::ReadOnly`], so reporting tools can
+    /// share a database safely. Migrations are never run: if the schema is
+    /// behind this version, opening fails with [`StoreError::Migration`] and
+    /// the database must be opened read-write once to upgrade it. A database
+    /// in WAL mode also needs its `-shm` file to exist or its directory to
+    /// be writable, since SQLite can't create the shared-memory index otherwise.
+    pub fn open_readonly(path: impl AsRef<Path>) -> StoreResult<Self> {
+        let path = path.as_ref();
+        let flags = OpenFlags::SQLITE_OPEN_READ_ONLY
+            | OpenFlags::SQLITE_OPEN_URI
+            | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+        let conn = Connection::open_with_flags(path, flags)?;
+        conn.busy_timeout(DEFAULT_BUSY_TIMEOUT)?;
+
+        let pending = migrations::pending_migrations(&conn).map_err(|e| match e {
+            StoreError::Database(ref err) if is_corruption(err) => StoreError::CorruptDatabase {
+                path: path.to_path_buf(),
+            },
+            other => other,
+        })?;
+        if !pending.is_empty() {
+            return Err(StoreError::Migration(format!(
+                "{} is missing {} migration(s) (first: {}); open it read-write to upgrade",
+                path.display(),
+                pending.len(),
+                pending[0]
+            )));
+        }
+
+        Ok(Self {
+            conn,
+            min_id_prefix: MIN_SHORT_ID_LEN,
+            read_only: true,
+            attachments_dir: attachments_dir_for(path),
+            orphaned_blobs: RefCell::new(Vec::new()),
+            clock: Box::new(SystemClock),
+        })
     }
 
     /// Create an in-memory database (useful for testing)
@@ -31,19 +122,138 @@ impl Store {
         let conn = Connection::open_in_memory()?;
         conn.execute_batch("PRAGMA foreign_keys = ON;")?;
         migrations::run_migrations(&conn)?;
-        Ok(Self { conn })
+        Ok(Self {
+            conn,
+            min_id_prefix: MIN_SHORT_ID_LEN,
+            read_only: false,
+            attachments_dir: None,
+            orphaned_blobs: RefCell::new(Vec::new()),
+            clock: Box::new(SystemClock),
+        })
+    }
+
+    /// Whether this store was opened with [`Store::open_readonly`]
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Replace the clock used for timestamps the store stamps itself (e.g. a
+    /// [`minmind_core::FixedClock`] in tests)
+    pub fn set_clock(&mut self, clock: impl Clock + Send + 'static) {
+        self.clock = Box::new(clock);
+    }
+
+    /// How long a call waits for another connection's write lock before
+    /// failing with a "database is locked" error (defaults to
+    /// [`DEFAULT_BUSY_TIMEOUT`])
+    pub fn set_busy_timeout(&self, timeout: StdDuration) -> StoreResult<()> {
+        self.conn.busy_timeout(timeout)?;
+        Ok(())
+    }
+
+    // ==================== Transactions ====================
+
+    /// Start a transaction that takes the write lock immediately.
+    ///
+    /// Store methods use deferred transactions, which only lock the database
+    /// at their first write and release it when they return. Bulk jobs such
+    /// as an import or a backup should run inside this instead, so no other
+    /// handle can write between their steps: every Store call made before
+    /// [`ExclusiveTransaction::commit`] joins it, and dropping it rolls them
+    /// all back. Other handles wait up to their busy timeout for the lock.
+    pub fn begin_exclusive(&self) -> StoreResult<ExclusiveTransaction<'_>> {
+        if self.read_only {
+            return Err(StoreError::ReadOnly);
+        }
+        ExclusiveTransaction::begin(self, &self.conn)
+    }
+
+    /// The transaction a multi-statement write runs in: a fresh deferred
+    /// one, or the caller's [`ExclusiveTransaction`] if one is open
+    fn write_transaction(&self) -> StoreResult<WriteTransaction<'_>> {
+        WriteTransaction::begin(&self.conn)
+    }
+
+    // ==================== Search Index ====================
+
+    /// Rebuild every full-text index from its base table, repairing an index
+    /// that has drifted out of sync (e.g. rows written without triggers)
+    pub fn rebuild_search_index(&self) -> StoreResult<()> {
+        self.rebuild_search_index_with_progress(&mut |_| {})
+    }
+
+    /// [`Store::rebuild_search_index`], reporting after each index is rebuilt
+    pub fn rebuild_search_index_with_progress(
+        &self,
+        progress: &mut dyn FnMut(Progress),
+    ) -> StoreResult<()> {
+        for (i, (index, _)) in SEARCH_INDEXES.iter().enumerate() {
+            self.conn.execute(&format!("INSERT INTO {0}({0}) VALUES('rebuild')", index), [])?;
+            progress(Progress::new("search indexes", i + 1, SEARCH_INDEXES.len()));
+        }
+        Ok(())
+    }
+
+    /// Compare how many rows each full-text index holds with its base table
+    ///
+    /// The indexes are external-content FTS5 tables, so the count of indexed
+    /// rows comes from the `_docsize` shadow table; counting the index itself
+    /// would just count the base table. A mismatch means searches miss (or
+    /// return stale) rows until [`Store::rebuild_search_index`] is run.
+    pub fn search_index_health(&self) -> StoreResult<IndexHealth> {
+        let mut indexes = Vec::with_capacity(SEARCH_INDEXES.len());
+        for (index, table) in SEARCH_INDEXES {
+            let count = |table: &str| -> StoreResult<u64> {
+                let sql = format!("SELECT COUNT(*) FROM {}", table);
+                Ok(self.conn.query_row(&sql, [], |row| row.get(0))?)
+            };
+            indexes.push(IndexCount {
+                index,
+                table,
+                rows: count(table)?,
+                indexed: count(&format!("{}_docsize", index))?,
+            });
+        }
+        Ok(IndexHealth { indexes })
+    }
+
+    // ==================== Short IDs ====================
+
+    /// Minimum prefix length used for short ids (defaults to [`MIN_SHORT_ID_LEN`])
+    pub fn min_id_prefix(&self) -> usize {
+        self.min_id_prefix
+    }
+
+    /// Set the minimum prefix length used for short ids
+    pub fn set_min_id_prefix(&mut self, len: usize) {
+        self.min_id_prefix = len.max(1);
+    }
+
+    /// The shortest prefix that uniquely identifies `id` within `among`,
+    /// never shorter than [`Store::min_id_prefix`]
+    pub fn shortest_unique_prefix(&self, id: Uuid, among: &[Uuid]) -> String {
+        shortest_unique_prefix(id, among, self.min_id_prefix)
     }
 
     // ==================== Room Operations ====================
 
     /// Create a new Room
-    pub fn create_room(&self, room: &Room) -> StoreResult<()> {
+    ///
+    /// The room's slug is made unique before insertion by appending `-2`, `-3`,
+    /// ... on collision, and `room.slug` is updated to the stored value.
+    ///
+    /// Fails with [`StoreError::DuplicateName`] if a sibling already has the
+    /// same name ignoring case; the name is stored with its casing as given.
+    pub fn create_room(&self, room: &mut Room) -> StoreResult<()> {
+        self.check_room_name_free(&room.name, room.parent_id, room.id)?;
+        room.slug = migrations::unique_room_slug(&self.conn, &room.slug)?;
         self.conn.execute(
-            "INSERT INTO rooms (id, name, description, parent_id, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "INSERT INTO rooms (id, name, slug, description, parent_id, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             params![
                 room.id.to_string(),
                 room.name,
+                room.slug,
                 room.description,
                 room.parent_id.map(|id| id.to_string()),
                 room.created_at.to_rfc3339(),
@@ -53,53 +263,146 @@ impl Store {
         Ok(())
     }
 
+    /// Whether a Room with this ID exists, without loading it
+    pub fn room_exists(&self, id: Uuid) -> StoreResult<bool> {
+        self.row_exists("rooms", id)
+    }
+
     /// Get a Room by ID
     pub fn get_room(&self, id: Uuid) -> StoreResult<Option<Room>> {
         self.conn
             .query_row(
-                "SELECT id, name, description, parent_id, created_at, updated_at
-                 FROM rooms WHERE id = ?",
+                &format!("SELECT {} FROM rooms WHERE id = ?", ROOM_COLUMNS),
                 [id.to_string()],
-                |row| {
-                    Ok(Room {
-                        id: parse_uuid(row.get::<_, String>(0)?),
-                        name: row.get(1)?,
-                        description: row.get(2)?,
-                        parent_id: row.get::<_, Option<String>>(3)?.map(parse_uuid),
-                        created_at: parse_datetime(row.get::<_, String>(4)?),
-                        updated_at: parse_datetime(row.get::<_, String>(5)?),
-                    })
-                },
+                room_from_row,
+            )
+            .optional()
+            .map_err(StoreError::from)
+    }
+
+    /// Get a Room by its slug
+    pub fn get_room_by_slug(&self, slug: &str) -> StoreResult<Option<Room>> {
+        self.conn
+            .query_row(
+                &format!("SELECT {} FROM rooms WHERE slug = ?", ROOM_COLUMNS),
+                [slug],
+                room_from_row,
+            )
+            .optional()
+            .map_err(StoreError::from)
+    }
+
+    /// Get the Room named `name` (case-insensitively) under `parent`, creating
+    /// it if there is none
+    ///
+    /// Names are only matched among siblings, so `Work/Ideas` and
+    /// `Personal/Ideas` are different rooms; `parent: None` looks at the
+    /// top-level rooms only.
+    pub fn get_or_create_room(&self, name: &str, parent: Option<Uuid>) -> StoreResult<Room> {
+        let tx = self.write_transaction()?;
+        let room = match self.sibling_room_named(name, parent, None)? {
+            Some(room) => room,
+            None => {
+                let mut room = Room::new(name);
+                if let Some(parent) = parent {
+                    room = room.with_parent(parent);
+                }
+                self.create_room(&mut room)?;
+                room
+            }
+        };
+        tx.commit()?;
+        Ok(room)
+    }
+
+    /// The Room directly under `parent` named `name` (ignoring case), other
+    /// than `excluding`
+    fn sibling_room_named(
+        &self,
+        name: &str,
+        parent: Option<Uuid>,
+        excluding: Option<Uuid>,
+    ) -> StoreResult<Option<Room>> {
+        self.conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM rooms
+                     WHERE name = ?1 COLLATE NOCASE AND parent_id IS ?2 AND id IS NOT ?3
+                     ORDER BY created_at LIMIT 1",
+                    ROOM_COLUMNS
+                ),
+                params![
+                    name,
+                    parent.map(|id| id.to_string()),
+                    excluding.map(|id| id.to_string())
+                ],
+                room_from_row,
             )
             .optional()
             .map_err(StoreError::from)
     }
 
+    /// Fail with [`StoreError::DuplicateName`] if a sibling of Room `id`
+    /// already uses `name`
+    fn check_room_name_free(&self, name: &str, parent: Option<Uuid>, id: Uuid) -> StoreResult<()> {
+        match self.sibling_room_named(name, parent, Some(id))? {
+            Some(existing) => Err(StoreError::DuplicateName(existing.name)),
+            None => Ok(()),
+        }
+    }
+
     /// List all Rooms
     pub fn list_rooms(&self) -> StoreResult<Vec<Room>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, name, description, parent_id, created_at, updated_at
+        let mut stmt = self
+            .conn
+            .prepare(&format!("SELECT {} FROM rooms ORDER BY name", ROOM_COLUMNS))?;
+
+        let rooms = stmt
+            .query_map([], room_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(rooms)
+    }
+
+    /// List all Rooms together with their note, article and todo counts
+    ///
+    /// Runs as a single query, so rooms without any content still appear
+    /// with zero counts.
+    pub fn list_rooms_with_counts(&self) -> StoreResult<Vec<(Room, RoomCounts)>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {},
+                (SELECT COUNT(*) FROM notes n
+                 WHERE n.room_id = rooms.id AND n.deleted_at IS NULL),
+                (SELECT COUNT(*) FROM articles a WHERE a.room_id = rooms.id),
+                (SELECT COUNT(*) FROM notes n
+                 WHERE n.room_id = rooms.id AND n.deleted_at IS NULL
+                   AND n.note_type = ?1 AND n.status = ?2)
              FROM rooms ORDER BY name",
-        )?;
+            ROOM_COLUMNS
+        ))?;
 
         let rooms = stmt
-            .query_map([], |row| {
-                Ok(Room {
-                    id: parse_uuid(row.get::<_, String>(0)?),
-                    name: row.get(1)?,
-                    description: row.get(2)?,
-                    parent_id: row.get::<_, Option<String>>(3)?.map(parse_uuid),
-                    created_at: parse_datetime(row.get::<_, String>(4)?),
-                    updated_at: parse_datetime(row.get::<_, String>(5)?),
-                })
-            })?
+            .query_map(
+                params![NoteType::Task.to_string(), Status::Active.to_string()],
+                |row| {
+                    let counts = RoomCounts {
+                        notes: row.get(7)?,
+                        articles: row.get(8)?,
+                        todos: row.get(9)?,
+                    };
+                    Ok((room_from_row(row)?, counts))
+                },
+            )?
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(rooms)
     }
 
     /// Update a Room
+    ///
+    /// Like [`Store::create_room`], refuses a name a sibling already has.
     pub fn update_room(&self, room: &Room) -> StoreResult<()> {
+        self.check_room_name_free(&room.name, room.parent_id, room.id)?;
         let rows = self.conn.execute(
             "UPDATE rooms SET name = ?2, description = ?3, parent_id = ?4, updated_at = ?5
              WHERE id = ?1",
@@ -118,6 +421,73 @@ impl Store {
         Ok(())
     }
 
+    /// Re-parent several Rooms at once
+    ///
+    /// Each move is `(room, new_parent)`, with `None` making the room
+    /// top-level. The resulting hierarchy is checked for cycles and for
+    /// same-named siblings as a whole before anything is written, so a set
+    /// of moves that is only valid together succeeds, and an invalid set
+    /// changes nothing.
+    pub fn set_room_parent_batch(&self, moves: &[(Uuid, Option<Uuid>)]) -> StoreResult<()> {
+        let tx = self.write_transaction()?;
+        let rooms = self.list_rooms()?;
+        let mut parents: HashMap<Uuid, Option<Uuid>> =
+            rooms.iter().map(|room| (room.id, room.parent_id)).collect();
+
+        for &(room_id, parent_id) in moves {
+            if !parents.contains_key(&room_id) {
+                return Err(StoreError::NotFound(format!("Room {}", room_id)));
+            }
+            if let Some(parent_id) = parent_id {
+                if !parents.contains_key(&parent_id) {
+                    return Err(StoreError::NotFound(format!("Room {}", parent_id)));
+                }
+            }
+        }
+        for &(room_id, parent_id) in moves {
+            parents.insert(room_id, parent_id);
+        }
+
+        // Walking up from a moved room must reach the top within as many
+        // steps as there are rooms; otherwise the walk is going round a cycle
+        for &(room_id, _) in moves {
+            let mut current = parents[&room_id];
+            for _ in 0..parents.len() {
+                match current {
+                    Some(id) if id == room_id => return Err(StoreError::RoomCycle(room_id)),
+                    Some(id) => current = parents[&id],
+                    None => break,
+                }
+            }
+            if current.is_some() {
+                return Err(StoreError::RoomCycle(room_id));
+            }
+        }
+
+        // Names compare like `COLLATE NOCASE` in `sibling_room_named`
+        for &(room_id, _) in moves {
+            let room = rooms.iter().find(|room| room.id == room_id).expect("checked above");
+            let clash = rooms.iter().find(|other| {
+                other.id != room_id
+                    && parents[&other.id] == parents[&room_id]
+                    && other.name.eq_ignore_ascii_case(&room.name)
+            });
+            if let Some(existing) = clash {
+                return Err(StoreError::DuplicateName(existing.name.clone()));
+            }
+        }
+
+        let now = self.clock.now().to_rfc3339();
+        for &(room_id, parent_id) in moves {
+            tx.execute(
+                "UPDATE rooms SET parent_id = ?2, updated_at = ?3 WHERE id = ?1",
+                params![room_id.to_string(), parent_id.map(|id| id.to_string()), now],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
     /// Delete a Room
     pub fn delete_room(&self, id: Uuid) -> StoreResult<()> {
         let rows = self
@@ -130,128 +500,382 @@ impl Store {
         Ok(())
     }
 
+    /// Merge Room `absorb` into Room `keep`, in one transaction: its notes,
+    /// articles, summary configs and child Rooms move to `keep`, then the
+    /// emptied Room is deleted. Returns how many notes moved.
+    ///
+    /// A child named like one of `keep`'s children is merged into that one
+    /// in turn, so sibling names stay unique. Moved summary configs are
+    /// deactivated if `keep` already has an active one. Merging a Room into
+    /// itself or one of its descendants is refused.
+    pub fn merge_rooms(&self, keep: Uuid, absorb: Uuid) -> StoreResult<usize> {
+        if keep == absorb {
+            let message = "Cannot merge a room into itself".to_string();
+            return Err(CoreError::InvalidInput(message).into());
+        }
+        if !self.room_exists(keep)? {
+            return Err(StoreError::NotFound(format!("Room {}", keep)));
+        }
+        if self.room_subtree_ids(absorb)?.contains(&keep) {
+            return Err(StoreError::RoomCycle(keep));
+        }
+
+        let tx = self.write_transaction()?;
+        let moved = self.merge_room_into(&tx, keep, absorb)?;
+        tx.commit()?;
+        Ok(moved)
+    }
+
+    /// The body of [`Store::merge_rooms`], run inside its transaction
+    fn merge_room_into(&self, conn: &Connection, keep: Uuid, absorb: Uuid) -> StoreResult<usize> {
+        let (keep_id, absorb_id) = (keep.to_string(), absorb.to_string());
+        let now = self.clock.now().to_rfc3339();
+
+        let mut moved = conn.execute(
+            "UPDATE notes SET room_id = ?1, updated_at = ?3 WHERE room_id = ?2",
+            params![keep_id, absorb_id, now],
+        )?;
+        conn.execute(
+            "UPDATE articles SET room_id = ?1 WHERE room_id = ?2",
+            params![keep_id, absorb_id],
+        )?;
+        let keep_has_config: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM summary_configs WHERE room_id = ?1 AND active = 1)",
+            [&keep_id],
+            |row| row.get(0),
+        )?;
+        conn.execute(
+            "UPDATE summary_configs SET room_id = ?1, active = active AND NOT ?3
+             WHERE room_id = ?2",
+            params![keep_id, absorb_id, keep_has_config],
+        )?;
+
+        let children = {
+            let mut stmt = conn.prepare("SELECT id, name FROM rooms WHERE parent_id = ?")?;
+            let rows = stmt.query_map([&absorb_id], |row| Ok((uuid_at(row, 0)?, row.get(1)?)))?;
+            rows.collect::<Result<Vec<(Uuid, String)>, _>>()?
+        };
+        for (child, name) in children {
+            match self.sibling_room_named(&name, Some(keep), None)? {
+                Some(existing) => moved += self.merge_room_into(conn, existing.id, child)?,
+                None => {
+                    conn.execute(
+                        "UPDATE rooms SET parent_id = ?2, updated_at = ?3 WHERE id = ?1",
+                        params![child.to_string(), keep_id, now],
+                    )?;
+                }
+            }
+        }
+
+        conn.execute("DELETE FROM rooms WHERE id = ?", [&absorb_id])?;
+        Ok(moved)
+    }
+
     // ==================== Note Operations ====================
 
     /// Create a new Note
     pub fn create_note(&self, note: &Note) -> StoreResult<()> {
+        if !self.room_exists(note.room_id)? {
+            return Err(StoreError::NotFound(format!("Room {}", note.room_id)));
+        }
         self.conn.execute(
-            "INSERT INTO notes (id, room_id, title, content, note_type, status, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT INTO notes (id, room_id, title, content, content_format, note_type, status,
+                                created_at, updated_at, source)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
             params![
                 note.id.to_string(),
                 note.room_id.to_string(),
                 note.title,
                 note.content,
+                note.content_format.to_string(),
                 note.note_type.to_string(),
                 note.status.map(|s| s.to_string()),
                 note.created_at.to_rfc3339(),
                 note.updated_at.to_rfc3339(),
+                note.source.to_string(),
             ],
         )?;
         Ok(())
     }
 
+    /// Whether a Note with this ID exists (trashed or not), without loading it
+    pub fn note_exists(&self, id: Uuid) -> StoreResult<bool> {
+        self.row_exists("notes", id)
+    }
+
     /// Get a Note by ID
     pub fn get_note(&self, id: Uuid) -> StoreResult<Option<Note>> {
         self.conn
             .query_row(
-                "SELECT id, room_id, title, content, note_type, status, created_at, updated_at
-                 FROM notes WHERE id = ?",
+                &format!("SELECT {} FROM notes n WHERE n.id = ?", NOTE_COLUMNS),
                 [id.to_string()],
-                |row| {
-                    Ok(Note {
-                        id: parse_uuid(row.get::<_, String>(0)?),
-                        room_id: parse_uuid(row.get::<_, String>(1)?),
-                        title: row.get(2)?,
-                        content: row.get(3)?,
-                        note_type: row
-                            .get::<_, String>(4)?
-                            .parse::<NoteType>()
-                            .unwrap_or(NoteType::Idea),
-                        status: row
-                            .get::<_, Option<String>>(5)?
-                            .and_then(|s| s.parse::<Status>().ok()),
-                        created_at: parse_datetime(row.get::<_, String>(6)?),
-                        updated_at: parse_datetime(row.get::<_, String>(7)?),
-                    })
-                },
+                note_from_row,
             )
             .optional()
             .map_err(StoreError::from)
     }
 
+    /// List every Note outside the trash, across all Rooms
+    pub fn list_notes(&self) -> StoreResult<Vec<Note>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM notes n
+             WHERE n.deleted_at IS NULL
+             ORDER BY n.updated_at DESC",
+            NOTE_COLUMNS
+        ))?;
+
+        let notes = stmt
+            .query_map([], note_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(notes)
+    }
+
     /// List Notes in a Room
     pub fn list_notes_in_room(&self, room_id: Uuid) -> StoreResult<Vec<Note>> {
+        self.list_notes_in_room_filtered(room_id, None, None)
+    }
+
+    /// List Notes in a Room, optionally only those of one type and/or status
+    pub fn list_notes_in_room_filtered(
+        &self,
+        room_id: Uuid,
+        note_type: Option<NoteType>,
+        status: Option<Status>,
+    ) -> StoreResult<Vec<Note>> {
+        let mut conditions = vec!["n.room_id = ?", "n.deleted_at IS NULL"];
+        let mut values = vec![room_id.to_string()];
+
+        if let Some(note_type) = note_type {
+            conditions.push("n.note_type = ?");
+            values.push(note_type.to_string());
+        }
+        if let Some(status) = status {
+            conditions.push("n.status = ?");
+            values.push(status.to_string());
+        }
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM notes n WHERE {} ORDER BY n.updated_at DESC",
+            NOTE_COLUMNS,
+            conditions.join(" AND ")
+        ))?;
+
+        let notes = stmt
+            .query_map(params_from_iter(values), note_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(notes)
+    }
+
+    /// List Notes in a Room and every Room nested below it, most recently
+    /// updated first
+    ///
+    /// The walk down the hierarchy visits each Room once, so a parent cycle
+    /// can't make it loop.
+    pub fn notes_in_room_recursive(&self, root: Uuid) -> StoreResult<Vec<Note>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "WITH RECURSIVE subtree(id) AS (
+                 SELECT id FROM rooms WHERE id = ?1
+                 UNION
+                 SELECT r.id FROM rooms r JOIN subtree s ON r.parent_id = s.id
+             )
+             SELECT {} FROM notes n
+             WHERE n.room_id IN (SELECT id FROM subtree) AND n.deleted_at IS NULL
+             ORDER BY n.updated_at DESC",
+            NOTE_COLUMNS
+        ))?;
+
+        let notes = stmt
+            .query_map([root.to_string()], note_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(notes)
+    }
+
+    /// The most recently edited Note in a Room, if it has any
+    pub fn latest_note_in_room(&self, room_id: Uuid) -> StoreResult<Option<Note>> {
+        self.note_in_room_ordered_by(room_id, "n.updated_at DESC")
+    }
+
+    /// The Note created first in a Room, if it has any
+    pub fn first_note_in_room(&self, room_id: Uuid) -> StoreResult<Option<Note>> {
+        self.note_in_room_ordered_by(room_id, "n.created_at ASC")
+    }
+
+    /// The first Note outside the trash in a Room under `order_by`
+    fn note_in_room_ordered_by(&self, room_id: Uuid, order_by: &str) -> StoreResult<Option<Note>> {
+        self.conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM notes n
+                     WHERE n.room_id = ? AND n.deleted_at IS NULL
+                     ORDER BY {}, n.id
+                     LIMIT 1",
+                    NOTE_COLUMNS, order_by
+                ),
+                [room_id.to_string()],
+                note_from_row,
+            )
+            .optional()
+            .map_err(StoreError::from)
+    }
+
+    /// Count the Notes in a Room, grouped by type
+    pub fn note_count_by_type(&self, room_id: Uuid) -> StoreResult<HashMap<NoteType, u64>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, room_id, title, content, note_type, status, created_at, updated_at
-             FROM notes WHERE room_id = ? ORDER BY updated_at DESC",
+            "SELECT note_type, COUNT(*) FROM notes
+             WHERE room_id = ? AND deleted_at IS NULL
+             GROUP BY note_type",
         )?;
 
-        let notes = stmt
+        let rows = stmt
             .query_map([room_id.to_string()], |row| {
-                Ok(Note {
-                    id: parse_uuid(row.get::<_, String>(0)?),
-                    room_id: parse_uuid(row.get::<_, String>(1)?),
-                    title: row.get(2)?,
-                    content: row.get(3)?,
-                    note_type: row
-                        .get::<_, String>(4)?
-                        .parse::<NoteType>()
-                        .unwrap_or(NoteType::Idea),
-                    status: row
-                        .get::<_, Option<String>>(5)?
-                        .and_then(|s| s.parse::<Status>().ok()),
-                    created_at: parse_datetime(row.get::<_, String>(6)?),
-                    updated_at: parse_datetime(row.get::<_, String>(7)?),
-                })
+                Ok((parsed_at::<NoteType>(row, 0)?, row.get::<_, u64>(1)?))
             })?
             .collect::<Result<Vec<_>, _>>()?;
 
-        Ok(notes)
+        let mut counts = HashMap::new();
+        for (note_type, count) in rows {
+            *counts.entry(note_type).or_insert(0) += count;
+        }
+
+        Ok(counts)
     }
 
     /// Search Notes by content
     pub fn search_notes(&self, query: &str) -> StoreResult<Vec<Note>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT n.id, n.room_id, n.title, n.content, n.note_type, n.status, n.created_at, n.updated_at
+        self.search_notes_filtered(query, None, None, usize::MAX)
+    }
+
+    /// Search Notes by content within a single Room
+    pub fn search_notes_in_room(&self, room_id: Uuid, query: &str) -> StoreResult<Vec<Note>> {
+        self.search_notes_filtered(query, Some(room_id), None, usize::MAX)
+    }
+
+    /// The `limit` best matches for `query`, optionally within one Room
+    ///
+    /// The cap is applied in SQL, so a common word doesn't load every
+    /// matching note; [`Store::count_search_matches`] gives the full count.
+    pub fn search_notes_limited(
+        &self,
+        query: &str,
+        room_id: Option<Uuid>,
+        limit: usize,
+    ) -> StoreResult<Vec<Note>> {
+        self.search_notes_filtered(query, room_id, None, limit)
+    }
+
+    /// The `limit` best matches for `query`, optionally only within one Room
+    /// and/or of one type
+    ///
+    /// The filters are applied in the same query as the full-text match, so
+    /// the cap counts only notes that pass them.
+    pub fn search_notes_filtered(
+        &self,
+        query: &str,
+        room_id: Option<Uuid>,
+        note_type: Option<NoteType>,
+        limit: usize,
+    ) -> StoreResult<Vec<Note>> {
+        check_search_query(query)?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}
              FROM notes n
              JOIN notes_fts fts ON n.rowid = fts.rowid
-             WHERE notes_fts MATCH ?
-             ORDER BY rank",
-        )?;
+             WHERE notes_fts MATCH ?1 AND (?2 IS NULL OR n.room_id = ?2)
+               AND (?3 IS NULL OR n.note_type = ?3) AND n.deleted_at IS NULL
+             ORDER BY rank
+             LIMIT ?4",
+            NOTE_COLUMNS
+        ))?;
 
         let notes = stmt
-            .query_map([query], |row| {
-                Ok(Note {
-                    id: parse_uuid(row.get::<_, String>(0)?),
-                    room_id: parse_uuid(row.get::<_, String>(1)?),
-                    title: row.get(2)?,
-                    content: row.get(3)?,
-                    note_type: row
-                        .get::<_, String>(4)?
-                        .parse::<NoteType>()
-                        .unwrap_or(NoteType::Idea),
-                    status: row
-                        .get::<_, Option<String>>(5)?
-                        .and_then(|s| s.parse::<Status>().ok()),
-                    created_at: parse_datetime(row.get::<_, String>(6)?),
-                    updated_at: parse_datetime(row.get::<_, String>(7)?),
-                })
-            })?
+            .query_map(
+                params![
+                    query,
+                    room_id.map(|id| id.to_string()),
+                    note_type.map(|t| t.to_string()),
+                    i64::try_from(limit).unwrap_or(i64::MAX)
+                ],
+                note_from_row,
+            )?
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(notes)
     }
 
+    /// How many live Notes match `query`, optionally within one Room
+    pub fn count_search_matches(&self, query: &str, room_id: Option<Uuid>) -> StoreResult<u64> {
+        self.count_search_matches_filtered(query, room_id, None)
+    }
+
+    /// How many live Notes match `query`, with the same filters as
+    /// [`Store::search_notes_filtered`]
+    pub fn count_search_matches_filtered(
+        &self,
+        query: &str,
+        room_id: Option<Uuid>,
+        note_type: Option<NoteType>,
+    ) -> StoreResult<u64> {
+        check_search_query(query)?;
+        let count = self.conn.query_row(
+            "SELECT COUNT(*)
+             FROM notes n
+             JOIN notes_fts fts ON n.rowid = fts.rowid
+             WHERE notes_fts MATCH ?1 AND (?2 IS NULL OR n.room_id = ?2)
+               AND (?3 IS NULL OR n.note_type = ?3) AND n.deleted_at IS NULL",
+            params![query, room_id.map(|id| id.to_string()), note_type.map(|t| t.to_string())],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// Suggest Notes whose titles nearly match `query`, closest first
+    ///
+    /// This scans every title, so it is meant as a fallback for when
+    /// [`Store::search_notes`] finds nothing, not as a replacement.
+    pub fn search_notes_fuzzy(
+        &self,
+        query: &str,
+        room_id: Option<Uuid>,
+        limit: usize,
+    ) -> StoreResult<Vec<Note>> {
+        check_search_query(query)?;
+        let notes = match room_id {
+            Some(room_id) => self.list_notes_in_room(room_id)?,
+            None => {
+                let mut stmt = self.conn.prepare(&format!(
+                    "SELECT {} FROM notes n WHERE n.deleted_at IS NULL",
+                    NOTE_COLUMNS
+                ))?;
+                let notes = stmt
+                    .query_map([], note_from_row)?
+                    .collect::<Result<Vec<_>, _>>()?;
+                notes
+            }
+        };
+
+        let mut matches: Vec<(usize, Note)> = notes
+            .into_iter()
+            .filter_map(|note| fuzzy_title_distance(query, &note.title).map(|d| (d, note)))
+            .collect();
+        matches.sort_by(|(a, x), (b, y)| a.cmp(b).then_with(|| x.title.cmp(&y.title)));
+
+        Ok(matches.into_iter().take(limit).map(|(_, note)| note).collect())
+    }
+
     /// Update a Note
     pub fn update_note(&self, note: &Note) -> StoreResult<()> {
         let rows = self.conn.execute(
-            "UPDATE notes SET title = ?2, content = ?3, note_type = ?4, status = ?5, updated_at = ?6
+            "UPDATE notes SET title = ?2, content = ?3, content_format = ?4, note_type = ?5,
+                              status = ?6, updated_at = ?7
              WHERE id = ?1",
             params![
                 note.id.to_string(),
                 note.title,
                 note.content,
+                note.content_format.to_string(),
                 note.note_type.to_string(),
                 note.status.map(|s| s.to_string()),
                 note.updated_at.to_rfc3339(),
@@ -264,17 +888,55 @@ impl Store {
         Ok(())
     }
 
-    /// Delete a Note
-    pub fn delete_note(&self, id: Uuid) -> StoreResult<()> {
-        // First delete any links involving this note
-        self.conn.execute(
-            "DELETE FROM links WHERE source_id = ? OR target_id = ?",
-            [id.to_string(), id.to_string()],
+    /// A Note as it was at an earlier `version`: its title and content then,
+    /// with `updated_at` set to when that version was written.
+    ///
+    /// A version is saved each time a Note's title or content changes,
+    /// numbered from 1 (the oldest).
+    pub fn get_note_version(&self, id: Uuid, version: u32) -> StoreResult<Option<Note>> {
+        let Some(mut note) = self.get_note(id)? else {
+            return Ok(None);
+        };
+        let saved = self
+            .conn
+            .query_row(
+                "SELECT title, content, saved_at FROM note_versions
+                 WHERE note_id = ? AND version = ?",
+                params![id.to_string(), version],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        datetime_at(row, 2)?,
+                    ))
+                },
+            )
+            .optional()?;
+
+        Ok(saved.map(|(title, content, saved_at)| {
+            note.title = title;
+            note.content = content;
+            note.updated_at = saved_at;
+            note
+        }))
+    }
+
+    /// How many earlier versions of a Note are saved
+    pub fn note_version_count(&self, id: Uuid) -> StoreResult<u32> {
+        let count = self.conn.query_row(
+            "SELECT COUNT(*) FROM note_versions WHERE note_id = ?",
+            [id.to_string()],
+            |row| row.get(0),
         )?;
+        Ok(count)
+    }
 
-        let rows = self
-            .conn
-            .execute("DELETE FROM notes WHERE id = ?", [id.to_string()])?;
+    /// Move a Note into another Room
+    pub fn move_note(&self, id: Uuid, room_id: Uuid) -> StoreResult<()> {
+        let rows = self.conn.execute(
+            "UPDATE notes SET room_id = ?2, updated_at = ?3 WHERE id = ?1",
+            params![id.to_string(), room_id.to_string(), self.clock.now().to_rfc3339()],
+        )?;
 
         if rows == 0 {
             return Err(StoreError::NotFound(format!("Note {}", id)));
@@ -282,68 +944,754 @@ impl Store {
         Ok(())
     }
 
-    // ==================== Link Operations ====================
-
-    /// Create a new Link
-    pub fn create_link(&self, link: &Link) -> StoreResult<()> {
-        self.conn.execute(
-            "INSERT INTO links (id, source_id, target_id, link_type, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
-            params![
-                link.id.to_string(),
-                link.source_id.to_string(),
-                link.target_id.to_string(),
-                link.link_type,
-                link.created_at.to_rfc3339(),
-            ],
+    /// Rename a Note. With `update_backlinks`, `[[Old Title]]` wikilinks in
+    /// other notes are rewritten to the new title in the same transaction.
+    ///
+    /// Returns how many other notes were rewritten.
+    pub fn rename_note(&self, id: Uuid, title: &str, update_backlinks: bool) -> StoreResult<usize> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(CoreError::InvalidInput("Note title cannot be empty".to_string()).into());
+        }
+        let old_title = self
+            .get_note(id)?
+            .ok_or_else(|| StoreError::NotFound(format!("Note {}", id)))?
+            .title;
+        let now = self.clock.now().to_rfc3339();
+
+        let tx = self.write_transaction()?;
+        tx.execute(
+            "UPDATE notes SET title = ?2, updated_at = ?3 WHERE id = ?1",
+            params![id.to_string(), title, now],
         )?;
-        Ok(())
+
+        let mut updated = 0;
+        if update_backlinks {
+            let candidates = tx
+                .prepare(
+                    "SELECT id, content FROM notes WHERE id != ?1 AND instr(content, '[[') > 0",
+                )?
+                .query_map([id.to_string()], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            for (other_id, content) in candidates {
+                let (content, count) = rewrite_wikilinks(&content, &old_title, title);
+                if count > 0 {
+                    tx.execute(
+                        "UPDATE notes SET content = ?2, updated_at = ?3 WHERE id = ?1",
+                        params![other_id, content, now],
+                    )?;
+                    updated += 1;
+                }
+            }
+        }
+
+        tx.commit()?;
+        Ok(updated)
     }
 
-    /// Get Links for a Note (both directions)
-    pub fn get_links_for_note(&self, note_id: Uuid) -> StoreResult<Vec<Link>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, source_id, target_id, link_type, created_at
-             FROM links WHERE source_id = ? OR target_id = ?",
+    /// Duplicate a Note with a fresh id, "(copy)" appended to the title and
+    /// new timestamps, optionally into a different Room.
+    ///
+    /// Tags are copied; links are not, so the copy starts unconnected.
+    pub fn duplicate_note(&self, id: Uuid, target_room: Option<Uuid>) -> StoreResult<Note> {
+        let original = self
+            .get_note(id)?
+            .ok_or_else(|| StoreError::NotFound(format!("Note {}", id)))?;
+
+        let room_id = target_room.unwrap_or(original.room_id);
+        if target_room.is_some() && self.get_room(room_id)?.is_none() {
+            return Err(StoreError::NotFound(format!("Room {}", room_id)));
+        }
+
+        let now = self.clock.now();
+        let copy = Note {
+            id: new_id(),
+            room_id,
+            title: format!("{} (copy)", original.title),
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            source: EntitySource::Manual,
+            ..original
+        };
+
+        let tx = self.write_transaction()?;
+        self.create_note(&copy)?;
+        tx.execute(
+            "INSERT INTO note_tags (note_id, tag_id)
+             SELECT ?1, tag_id FROM note_tags WHERE note_id = ?2",
+            params![copy.id.to_string(), id.to_string()],
         )?;
+        tx.commit()?;
 
-        let links = stmt
-            .query_map([note_id.to_string(), note_id.to_string()], |row| {
-                Ok(Link {
-                    id: parse_uuid(row.get::<_, String>(0)?),
-                    source_id: parse_uuid(row.get::<_, String>(1)?),
-                    target_id: parse_uuid(row.get::<_, String>(2)?),
-                    link_type: row.get(3)?,
-                    created_at: parse_datetime(row.get::<_, String>(4)?),
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
+        Ok(copy)
+    }
 
-        Ok(links)
+    /// Move a Note to the trash. Trashed notes are hidden from listings and
+    /// search until restored or purged.
+    pub fn trash_note(&self, id: Uuid) -> StoreResult<()> {
+        let rows = self.conn.execute(
+            "UPDATE notes SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL",
+            params![id.to_string(), self.clock.now().to_rfc3339()],
+        )?;
+
+        if rows == 0 {
+            return Err(StoreError::NotFound(format!("Note {}", id)));
+        }
+        Ok(())
     }
 
-    /// Delete a Link
-    pub fn delete_link(&self, id: Uuid) -> StoreResult<()> {
-        let rows = self
-            .conn
-            .execute("DELETE FROM links WHERE id = ?", [id.to_string()])?;
+    /// Take a Note back out of the trash
+    pub fn restore_note(&self, id: Uuid) -> StoreResult<()> {
+        let rows = self.conn.execute(
+            "UPDATE notes SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+            [id.to_string()],
+        )?;
 
         if rows == 0 {
-            return Err(StoreError::NotFound(format!("Link {}", id)));
+            return Err(StoreError::NotFound(format!("Trashed note {}", id)));
         }
         Ok(())
     }
 
-    // ==================== Genius Operations ====================
+    /// List trashed Notes, most recently trashed first
+    pub fn list_trashed_notes(&self) -> StoreResult<Vec<Note>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM notes n
+             WHERE n.deleted_at IS NOT NULL
+             ORDER BY n.deleted_at DESC",
+            NOTE_COLUMNS
+        ))?;
 
-    /// Create a new Genius
-    pub fn create_genius(&self, genius: &Genius) -> StoreResult<()> {
-        self.conn.execute(
-            "INSERT INTO geniuses (id, name, provider, model, system_prompt, config)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-            params![
-                genius.id.to_string(),
-                genius.name,
+        let notes = stmt
+            .query_map([], note_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(notes)
+    }
+
+    /// Permanently delete Notes that have been in the trash for longer than
+    /// `older_than`, along with their links. Returns how many were purged.
+    pub fn purge_trashed(&self, older_than: Duration) -> StoreResult<usize> {
+        let cutoff = (self.clock.now() - older_than).to_rfc3339();
+        let tx = self.write_transaction()?;
+
+        let blobs = attachment_hashes(
+            &tx,
+            "SELECT DISTINCT a.content_hash FROM attachments a
+             JOIN notes n ON n.id = a.note_id WHERE n.deleted_at < ?",
+            &cutoff,
+        )?;
+        tx.execute(
+            "DELETE FROM links WHERE source_id IN (SELECT id FROM notes WHERE deleted_at < ?1)
+                OR target_id IN (SELECT id FROM notes WHERE deleted_at < ?1)",
+            [&cutoff],
+        )?;
+        let purged = tx.execute("DELETE FROM notes WHERE deleted_at < ?", [&cutoff])?;
+        delete_unused_tags(&tx)?;
+
+        self.orphaned_blobs.borrow_mut().extend(blobs);
+        tx.commit()?;
+        self.remove_orphaned_blobs();
+        Ok(purged)
+    }
+
+    /// Delete a Note
+    pub fn delete_note(&self, id: Uuid) -> StoreResult<()> {
+        self.delete_note_report(id).map(|_| ())
+    }
+
+    /// Delete a Note, reporting the links that were removed with it
+    ///
+    /// The links are counted and deleted in the same transaction as the note.
+    pub fn delete_note_report(&self, id: Uuid) -> StoreResult<NoteDeletionReport> {
+        let tx = self.write_transaction()?;
+
+        let linked_titles = {
+            let mut stmt = tx.prepare(
+                "SELECT n.title FROM links l
+                 JOIN notes n ON n.id = CASE WHEN l.source_id = ?1 THEN l.target_id
+                                             ELSE l.source_id END
+                 WHERE l.source_id = ?1 OR l.target_id = ?1
+                 ORDER BY n.title",
+            )?;
+            let titles = stmt
+                .query_map([id.to_string()], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()?;
+            titles
+        };
+
+        let blobs = attachment_hashes(
+            &tx,
+            "SELECT DISTINCT content_hash FROM attachments WHERE note_id = ?",
+            &id.to_string(),
+        )?;
+
+        // Links go first so none are left pointing at the deleted note
+        let removed_links = tx.execute(
+            "DELETE FROM links WHERE source_id = ?1 OR target_id = ?1",
+            [id.to_string()],
+        )?;
+
+        let rows = tx.execute("DELETE FROM notes WHERE id = ?", [id.to_string()])?;
+        if rows == 0 {
+            return Err(StoreError::NotFound(format!("Note {}", id)));
+        }
+        delete_unused_tags(&tx)?;
+        self.orphaned_blobs.borrow_mut().extend(blobs);
+        tx.commit()?;
+        self.remove_orphaned_blobs();
+
+        Ok(NoteDeletionReport {
+            removed_links,
+            linked_titles,
+        })
+    }
+
+    // ==================== Tag Operations ====================
+
+    /// Attach a tag to a Note, creating the tag if it doesn't exist yet
+    pub fn add_note_tag(&self, note_id: Uuid, tag: &str) -> StoreResult<()> {
+        let tag = normalize_tag(tag)?;
+        if !self.note_exists(note_id)? {
+            return Err(StoreError::NotFound(format!("Note {}", note_id)));
+        }
+        let tx = self.write_transaction()?;
+        tx.execute("INSERT OR IGNORE INTO tags (name) VALUES (?)", [&tag])?;
+        tx.execute(
+            "INSERT OR IGNORE INTO note_tags (note_id, tag_id)
+             SELECT ?1, id FROM tags WHERE name = ?2",
+            params![note_id.to_string(), tag],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Detach a tag from a Note, dropping the tag once nothing uses it.
+    /// Returns whether the Note had the tag.
+    pub fn remove_note_tag(&self, note_id: Uuid, tag: &str) -> StoreResult<bool> {
+        let tag = normalize_tag(tag)?;
+        let tx = self.write_transaction()?;
+        let rows = tx.execute(
+            "DELETE FROM note_tags
+             WHERE note_id = ?1 AND tag_id = (SELECT id FROM tags WHERE name = ?2)",
+            params![note_id.to_string(), tag],
+        )?;
+        delete_unused_tags(&tx)?;
+        tx.commit()?;
+        Ok(rows > 0)
+    }
+
+    /// Tags attached to a Note, alphabetically
+    pub fn tags_for_note(&self, note_id: Uuid) -> StoreResult<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT t.name FROM tags t
+             JOIN note_tags nt ON nt.tag_id = t.id
+             WHERE nt.note_id = ?
+             ORDER BY t.name",
+        )?;
+
+        let tags = stmt
+            .query_map([note_id.to_string()], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(tags)
+    }
+
+    /// List Notes carrying a tag
+    pub fn list_notes_with_tag(&self, tag: &str) -> StoreResult<Vec<Note>> {
+        let tag = normalize_tag(tag)?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}
+             FROM notes n
+             JOIN note_tags nt ON nt.note_id = n.id
+             JOIN tags t ON t.id = nt.tag_id
+             WHERE t.name = ? AND n.deleted_at IS NULL
+             ORDER BY n.updated_at DESC",
+            NOTE_COLUMNS
+        ))?;
+
+        let notes = stmt
+            .query_map([tag], note_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(notes)
+    }
+
+    /// List all tags with the number of Notes using each, most used first
+    pub fn list_tags(&self) -> StoreResult<Vec<(String, u64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT t.name, COUNT(nt.note_id) AS uses
+             FROM tags t
+             LEFT JOIN note_tags nt ON nt.tag_id = t.id
+             GROUP BY t.id
+             ORDER BY uses DESC, t.name ASC",
+        )?;
+
+        let tags = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(tags)
+    }
+
+    /// Rename a tag everywhere it is used. If `new` already exists the two
+    /// tags are merged.
+    pub fn rename_tag(&self, old: &str, new: &str) -> StoreResult<()> {
+        let old = normalize_tag(old)?;
+        let new = normalize_tag(new)?;
+        if old == new {
+            return Ok(());
+        }
+
+        let tx = self.write_transaction()?;
+        let old_id: i64 = tx
+            .query_row("SELECT id FROM tags WHERE name = ?", [&old], |row| row.get(0))
+            .optional()?
+            .ok_or_else(|| StoreError::NotFound(format!("Tag {}", old)))?;
+        let new_id: Option<i64> = tx
+            .query_row("SELECT id FROM tags WHERE name = ?", [&new], |row| row.get(0))
+            .optional()?;
+
+        match new_id {
+            Some(new_id) => {
+                tx.execute(
+                    "INSERT OR IGNORE INTO note_tags (note_id, tag_id)
+                     SELECT note_id, ?2 FROM note_tags WHERE tag_id = ?1",
+                    params![old_id, new_id],
+                )?;
+                tx.execute("DELETE FROM tags WHERE id = ?", [old_id])?;
+            }
+            None => {
+                tx.execute("UPDATE tags SET name = ?2 WHERE id = ?1", params![old_id, new])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    // ==================== Template Operations ====================
+
+    /// Get the template body for a note type or template name
+    pub fn get_template(&self, name: &str) -> StoreResult<Option<String>> {
+        self.conn
+            .query_row(
+                "SELECT content FROM note_templates WHERE name = ?",
+                [normalize_template_name(name)],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(StoreError::from)
+    }
+
+    /// Create or replace a template
+    pub fn set_template(&self, name: &str, content: &str) -> StoreResult<()> {
+        let name = normalize_template_name(name);
+        if name.is_empty() {
+            return Err(CoreError::InvalidInput("template name cannot be empty".into()).into());
+        }
+        self.conn.execute(
+            "INSERT INTO note_templates (name, content, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(name) DO UPDATE SET content = ?2, updated_at = ?3",
+            params![name, content, self.clock.now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// List template names in alphabetical order
+    pub fn list_templates(&self) -> StoreResult<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT name FROM note_templates ORDER BY name")?;
+
+        let names = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(names)
+    }
+
+    /// Delete a template, returning whether it existed
+    pub fn delete_template(&self, name: &str) -> StoreResult<bool> {
+        let rows = self.conn.execute(
+            "DELETE FROM note_templates WHERE name = ?",
+            [normalize_template_name(name)],
+        )?;
+        Ok(rows > 0)
+    }
+
+    // ==================== Attachment Operations ====================
+
+    /// Attach the file at `path` to a Note, copying it into the
+    /// content-addressed store next to the database. Identical files are
+    /// stored once, however many notes they are attached to.
+    pub fn add_attachment(&self, note_id: Uuid, path: impl AsRef<Path>) -> StoreResult<Attachment> {
+        let path = path.as_ref();
+        if self.read_only {
+            return Err(StoreError::ReadOnly);
+        }
+        let root = self.attachments_root()?;
+        if !self.note_exists(note_id)? {
+            return Err(StoreError::NotFound(format!("Note {}", note_id)));
+        }
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .ok_or_else(|| CoreError::InvalidInput(format!("Not a file: {}", path.display())))?;
+
+        let bytes = std::fs::read(path)?;
+        let content_hash = blobs::content_hash(&bytes);
+        let stored_path = blobs::write_blob(root, &content_hash, &bytes)?;
+        let attachment = Attachment {
+            id: new_id(),
+            note_id,
+            filename,
+            content_hash,
+            mime_type: mime_guess::from_path(path).first_or_octet_stream().to_string(),
+            size: bytes.len() as u64,
+            stored_path,
+            created_at: self.clock.now(),
+        };
+
+        self.conn.execute(
+            "INSERT INTO attachments (id, note_id, filename, content_hash, mime_type, size,
+                                      stored_path, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                attachment.id.to_string(),
+                attachment.note_id.to_string(),
+                attachment.filename,
+                attachment.content_hash,
+                attachment.mime_type,
+                attachment.size as i64,
+                attachment.stored_path,
+                attachment.created_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(attachment)
+    }
+
+    /// Attachments of a Note, oldest first
+    pub fn list_attachments(&self, note_id: Uuid) -> StoreResult<Vec<Attachment>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM attachments WHERE note_id = ? ORDER BY created_at, filename",
+            ATTACHMENT_COLUMNS
+        ))?;
+
+        let attachments = stmt
+            .query_map([note_id.to_string()], attachment_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(attachments)
+    }
+
+    /// Where an attachment's bytes are stored on disk
+    pub fn attachment_path(&self, attachment: &Attachment) -> StoreResult<PathBuf> {
+        Ok(self.attachments_root()?.join(&attachment.stored_path))
+    }
+
+    /// Delete every stored blob that no attachment refers to any more, and
+    /// return how many were removed
+    ///
+    /// Deleting notes already removes the blobs only they used; this sweep
+    /// is for blobs left behind otherwise, e.g. by a crash. Don't run it while
+    /// another process may be adding attachments: a blob it has written but
+    /// not yet recorded looks unreferenced.
+    pub fn collect_attachment_garbage(&self) -> StoreResult<usize> {
+        let Some(root) = &self.attachments_dir else {
+            return Ok(0);
+        };
+        let referenced = self
+            .conn
+            .prepare("SELECT DISTINCT content_hash FROM attachments")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<HashSet<_>, _>>()?;
+        Ok(blobs::remove_unreferenced(root, &referenced)?)
+    }
+
+    /// Remove the blobs queued in `orphaned_blobs` that no attachment refers
+    /// to, once no transaction is open any more. The deletions that queued
+    /// them have committed by then, so a failure here only warns.
+    pub(crate) fn remove_orphaned_blobs(&self) {
+        if !self.conn.is_autocommit() {
+            return;
+        }
+        let hashes = std::mem::take(&mut *self.orphaned_blobs.borrow_mut());
+        let Some(root) = &self.attachments_dir else {
+            return;
+        };
+        for hash in hashes {
+            if let Err(err) = self.remove_blob_if_unreferenced(root, &hash) {
+                eprintln!("Warning: couldn't remove attachment blob {}: {}", hash, err);
+            }
+        }
+    }
+
+    fn remove_blob_if_unreferenced(&self, root: &Path, hash: &str) -> StoreResult<()> {
+        let referenced: bool = self.conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM attachments WHERE content_hash = ?)",
+            [hash],
+            |row| row.get(0),
+        )?;
+        if !referenced {
+            blobs::remove_blob(root, hash)?;
+        }
+        Ok(())
+    }
+
+    fn attachments_root(&self) -> StoreResult<&Path> {
+        self.attachments_dir.as_deref().ok_or_else(|| {
+            CoreError::InvalidInput("In-memory stores can't hold attachments".to_string()).into()
+        })
+    }
+
+    // ==================== Link Operations ====================
+
+    /// Create a new Link
+    pub fn create_link(&self, link: &Link) -> StoreResult<()> {
+        link.validate()?;
+        for id in [link.source_id, link.target_id] {
+            if !self.note_exists(id)? {
+                return Err(StoreError::NotFound(format!("Note {}", id)));
+            }
+        }
+        self.conn.execute(
+            "INSERT INTO links (id, source_id, target_id, link_type, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                link.id.to_string(),
+                link.source_id.to_string(),
+                link.target_id.to_string(),
+                link.link_type,
+                link.created_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Create a Link unless an identical one (same source, target and type)
+    /// already exists. Returns whether a link was created, so repeated
+    /// imports don't pile up duplicate edges.
+    pub fn create_link_if_absent(&self, link: &Link) -> StoreResult<bool> {
+        if self.link_exists(link.source_id, link.target_id, link.link_type.as_deref())? {
+            return Ok(false);
+        }
+        self.create_link(link)?;
+        Ok(true)
+    }
+
+    /// Whether a Link from `source` to `target` with exactly this type exists
+    ///
+    /// `None` only matches untyped links.
+    pub fn link_exists(
+        &self,
+        source: Uuid,
+        target: Uuid,
+        link_type: Option<&str>,
+    ) -> StoreResult<bool> {
+        let exists = self.conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM links
+                           WHERE source_id = ?1 AND target_id = ?2 AND link_type IS ?3)",
+            params![source.to_string(), target.to_string(), link_type],
+            |row| row.get(0),
+        )?;
+        Ok(exists)
+    }
+
+    /// The distinct link types in use, most used first (ties by name)
+    ///
+    /// Untyped links are left out. Meant for suggesting types alongside the
+    /// known `link_types` constants, so custom types get reused rather than
+    /// retyped slightly differently.
+    pub fn link_types_in_use(&self) -> StoreResult<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT link_type FROM links
+             WHERE link_type IS NOT NULL
+             GROUP BY link_type
+             ORDER BY COUNT(*) DESC, link_type",
+        )?;
+        let types = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(types)
+    }
+
+    /// Get Links for a Note (both directions)
+    ///
+    /// Each link is returned once; `create_link` refuses self-links, so no
+    /// link has this note on both ends.
+    pub fn get_links_for_note(&self, note_id: Uuid) -> StoreResult<Vec<Link>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM links WHERE source_id = ? OR target_id = ?",
+            LINK_COLUMNS
+        ))?;
+
+        let links = stmt
+            .query_map([note_id.to_string(), note_id.to_string()], link_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(links)
+    }
+
+    /// Get a Note with its linked neighbors
+    ///
+    /// Uses three queries regardless of how many links the note has. Links to
+    /// trashed notes are left out.
+    pub fn get_note_with_links(&self, id: Uuid) -> StoreResult<Option<NoteWithLinks>> {
+        let Some(note) = self.get_note(id)? else {
+            return Ok(None);
+        };
+        let links = self.get_links_for_note(id)?;
+
+        let neighbor_ids: Vec<String> = links
+            .iter()
+            .map(|l| if l.source_id == id { l.target_id } else { l.source_id })
+            .map(|n| n.to_string())
+            .collect();
+        let mut neighbors = HashMap::new();
+        if !neighbor_ids.is_empty() {
+            let placeholders = vec!["?"; neighbor_ids.len()].join(", ");
+            let mut stmt = self.conn.prepare(&format!(
+                "SELECT {} FROM notes n WHERE n.id IN ({}) AND n.deleted_at IS NULL",
+                NOTE_COLUMNS, placeholders
+            ))?;
+            for neighbor in stmt.query_map(params_from_iter(&neighbor_ids), note_from_row)? {
+                let neighbor = neighbor?;
+                neighbors.insert(neighbor.id, neighbor);
+            }
+        }
+
+        let mut outgoing = Vec::new();
+        let mut incoming = Vec::new();
+        for link in links {
+            if link.source_id == id {
+                if let Some(target) = neighbors.get(&link.target_id) {
+                    outgoing.push((link.clone(), target.clone()));
+                }
+            }
+            if link.target_id == id {
+                if let Some(source) = neighbors.get(&link.source_id) {
+                    incoming.push((link, source.clone()));
+                }
+            }
+        }
+
+        Ok(Some(NoteWithLinks {
+            note,
+            outgoing,
+            incoming,
+        }))
+    }
+
+    /// Delete a Link
+    pub fn delete_link(&self, id: Uuid) -> StoreResult<()> {
+        let rows = self
+            .conn
+            .execute("DELETE FROM links WHERE id = ?", [id.to_string()])?;
+
+        if rows == 0 {
+            return Err(StoreError::NotFound(format!("Link {}", id)));
+        }
+        Ok(())
+    }
+
+    /// Count the Links between notes that aren't in the trash
+    pub fn count_links(&self) -> StoreResult<u64> {
+        let count = self.conn.query_row(
+            "SELECT COUNT(*) FROM links l
+             JOIN notes s ON s.id = l.source_id
+             JOIN notes t ON t.id = l.target_id
+             WHERE s.deleted_at IS NULL AND t.deleted_at IS NULL",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// Count Notes with no links to other live notes, optionally only in one Room
+    pub fn count_isolated_notes(&self, room_id: Option<Uuid>) -> StoreResult<u64> {
+        let count = self.conn.query_row(
+            &format!(
+                "{} SELECT COUNT(*) FROM degrees WHERE degree = 0",
+                NOTE_DEGREES_CTE
+            ),
+            [room_id.map(|id| id.to_string())],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// Link density of the palace, or of a single Room
+    ///
+    /// In a room, links to notes elsewhere count towards the room's notes.
+    pub fn graph_stats(&self, room_id: Option<Uuid>) -> StoreResult<GraphStats> {
+        let room = room_id.map(|id| id.to_string());
+        let (notes, isolated_notes, total_degree): (u64, u64, u64) = self.conn.query_row(
+            &format!(
+                "{} SELECT COUNT(*), COALESCE(SUM(degree = 0), 0), COALESCE(SUM(degree), 0)
+                 FROM degrees",
+                NOTE_DEGREES_CTE
+            ),
+            [&room],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        let most_connected = self
+            .conn
+            .query_row(
+                &format!(
+                    "{} SELECT id, title, degree FROM degrees WHERE degree > 0
+                     ORDER BY degree DESC, title LIMIT 1",
+                    NOTE_DEGREES_CTE
+                ),
+                [&room],
+                |row| {
+                    Ok((
+                        uuid_at(row, 0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, u64>(2)?,
+                    ))
+                },
+            )
+            .optional()?;
+        let links = match &room {
+            None => self.count_links()?,
+            Some(room) => self.conn.query_row(
+                "SELECT COUNT(*) FROM links l
+                 JOIN notes s ON s.id = l.source_id
+                 JOIN notes t ON t.id = l.target_id
+                 WHERE s.deleted_at IS NULL AND t.deleted_at IS NULL
+                   AND (s.room_id = ?1 OR t.room_id = ?1)",
+                [room],
+                |row| row.get(0),
+            )?,
+        };
+
+        Ok(GraphStats {
+            notes,
+            links,
+            isolated_notes,
+            average_links_per_note: if notes == 0 {
+                0.0
+            } else {
+                total_degree as f64 / notes as f64
+            },
+            most_connected,
+        })
+    }
+
+    // ==================== Genius Operations ====================
+
+    /// Create a new Genius
+    pub fn create_genius(&self, genius: &Genius) -> StoreResult<()> {
+        self.conn.execute(
+            "INSERT INTO geniuses (id, name, provider, model, system_prompt, config)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                genius.id.to_string(),
+                genius.name,
                 genius.provider.to_string(),
                 genius.model,
                 genius.system_prompt,
@@ -362,7 +1710,7 @@ impl Store {
                 [id.to_string()],
                 |row| {
                     Ok(Genius {
-                        id: parse_uuid(row.get::<_, String>(0)?),
+                        id: uuid_at(row, 0)?,
                         name: row.get(1)?,
                         provider: row
                             .get::<_, String>(2)?
@@ -389,7 +1737,7 @@ impl Store {
         let geniuses = stmt
             .query_map([], |row| {
                 Ok(Genius {
-                    id: parse_uuid(row.get::<_, String>(0)?),
+                    id: uuid_at(row, 0)?,
                     name: row.get(1)?,
                     provider: row
                         .get::<_, String>(2)?
@@ -421,11 +1769,26 @@ impl Store {
     // ==================== Article Operations ====================
 
     /// Create a new Article
+    ///
+    /// Records the initial status in the audit log.
     pub fn create_article(&self, article: &Article) -> StoreResult<()> {
         let metadata_json = serde_json::to_string(&article.source_metadata)?;
-        self.conn.execute(
-            "INSERT INTO articles (id, url, title, raw_content, summary, room_id, status, source_metadata, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        let usage_json = article
+            .summary_usage
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        let summaries_json = summaries_json(article)?;
+        if let Some(room_id) = article.room_id {
+            if !self.room_exists(room_id)? {
+                return Err(StoreError::NotFound(format!("Room {}", room_id)));
+            }
+        }
+        let tx = self.write_transaction()?;
+        tx.execute(
+            "INSERT INTO articles (id, url, title, raw_content, summary, room_id, status, source_metadata, summary_usage, created_at, updated_at,
+                                   read_at, content_purged, summaries, last_error)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
             params![
                 article.id.to_string(),
                 article.url,
@@ -435,41 +1798,40 @@ impl Store {
                 article.room_id.map(|id| id.to_string()),
                 article.status.to_string(),
                 metadata_json,
+                usage_json,
                 article.created_at.to_rfc3339(),
                 article.updated_at.to_rfc3339(),
+                article.read_at.map(|dt| dt.to_rfc3339()),
+                article.content_purged,
+                summaries_json,
+                article.last_error,
             ],
         )?;
+        record_transition(
+            &tx,
+            AuditEntity::Article,
+            article.id,
+            None,
+            &article.status.to_string(),
+            article.created_at,
+            audit_sources::MANUAL,
+        )?;
+        tx.commit()?;
         Ok(())
     }
 
+    /// Whether an Article with this ID exists, without loading its content
+    pub fn article_exists(&self, id: Uuid) -> StoreResult<bool> {
+        self.row_exists("articles", id)
+    }
+
     /// Get an Article by ID
     pub fn get_article(&self, id: Uuid) -> StoreResult<Option<Article>> {
         self.conn
             .query_row(
-                "SELECT id, url, title, raw_content, summary, room_id, status, source_metadata, created_at, updated_at
-                 FROM articles WHERE id = ?",
+                &format!("SELECT {} FROM articles a WHERE a.id = ?", ARTICLE_COLUMNS),
                 [id.to_string()],
-                |row| {
-                    let metadata: SourceMetadata = row
-                        .get::<_, Option<String>>(7)?
-                        .and_then(|s| serde_json::from_str(&s).ok())
-                        .unwrap_or_default();
-                    Ok(Article {
-                        id: parse_uuid(row.get::<_, String>(0)?),
-                        url: row.get(1)?,
-                        title: row.get(2)?,
-                        raw_content: row.get(3)?,
-                        summary: row.get(4)?,
-                        room_id: row.get::<_, Option<String>>(5)?.map(parse_uuid),
-                        status: row
-                            .get::<_, String>(6)?
-                            .parse::<ArticleStatus>()
-                            .unwrap_or(ArticleStatus::Pending),
-                        source_metadata: metadata,
-                        created_at: parse_datetime(row.get::<_, String>(8)?),
-                        updated_at: parse_datetime(row.get::<_, String>(9)?),
-                    })
-                },
+                article_from_row,
             )
             .optional()
             .map_err(StoreError::from)
@@ -479,30 +1841,9 @@ impl Store {
     pub fn get_article_by_url(&self, url: &str) -> StoreResult<Option<Article>> {
         self.conn
             .query_row(
-                "SELECT id, url, title, raw_content, summary, room_id, status, source_metadata, created_at, updated_at
-                 FROM articles WHERE url = ?",
+                &format!("SELECT {} FROM articles a WHERE a.url = ?", ARTICLE_COLUMNS),
                 [url],
-                |row| {
-                    let metadata: SourceMetadata = row
-                        .get::<_, Option<String>>(7)?
-                        .and_then(|s| serde_json::from_str(&s).ok())
-                        .unwrap_or_default();
-                    Ok(Article {
-                        id: parse_uuid(row.get::<_, String>(0)?),
-                        url: row.get(1)?,
-                        title: row.get(2)?,
-                        raw_content: row.get(3)?,
-                        summary: row.get(4)?,
-                        room_id: row.get::<_, Option<String>>(5)?.map(parse_uuid),
-                        status: row
-                            .get::<_, String>(6)?
-                            .parse::<ArticleStatus>()
-                            .unwrap_or(ArticleStatus::Pending),
-                        source_metadata: metadata,
-                        created_at: parse_datetime(row.get::<_, String>(8)?),
-                        updated_at: parse_datetime(row.get::<_, String>(9)?),
-                    })
-                },
+                article_from_row,
             )
             .optional()
             .map_err(StoreError::from)
@@ -510,133 +1851,306 @@ impl Store {
 
     /// List Articles by status
     pub fn list_articles_by_status(&self, status: ArticleStatus) -> StoreResult<Vec<Article>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, url, title, raw_content, summary, room_id, status, source_metadata, created_at, updated_at
-             FROM articles WHERE status = ? ORDER BY updated_at DESC",
-        )?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM articles a WHERE a.status = ? ORDER BY a.updated_at DESC",
+            ARTICLE_COLUMNS
+        ))?;
 
         let articles = stmt
-            .query_map([status.to_string()], |row| {
-                let metadata: SourceMetadata = row
-                    .get::<_, Option<String>>(7)?
-                    .and_then(|s| serde_json::from_str(&s).ok())
-                    .unwrap_or_default();
-                Ok(Article {
-                    id: parse_uuid(row.get::<_, String>(0)?),
-                    url: row.get(1)?,
-                    title: row.get(2)?,
-                    raw_content: row.get(3)?,
-                    summary: row.get(4)?,
-                    room_id: row.get::<_, Option<String>>(5)?.map(parse_uuid),
-                    status: row
-                        .get::<_, String>(6)?
-                        .parse::<ArticleStatus>()
-                        .unwrap_or(ArticleStatus::Pending),
-                    source_metadata: metadata,
-                    created_at: parse_datetime(row.get::<_, String>(8)?),
-                    updated_at: parse_datetime(row.get::<_, String>(9)?),
-                })
-            })?
+            .query_map([status.to_string()], article_from_row)?
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(articles)
     }
 
-    /// List all Articles
-    pub fn list_articles(&self) -> StoreResult<Vec<Article>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, url, title, raw_content, summary, room_id, status, source_metadata, created_at, updated_at
-             FROM articles ORDER BY updated_at DESC",
-        )?;
+    /// List Articles with any of `statuses`, grouped by status in the order
+    /// given, most recently updated first within each
+    pub fn list_articles_by_statuses(
+        &self,
+        statuses: &[ArticleStatus],
+    ) -> StoreResult<Vec<Article>> {
+        if statuses.is_empty() {
+            return Ok(Vec::new());
+        }
+        let numbered: Vec<String> = (1..=statuses.len()).map(|i| format!("?{}", i)).collect();
+        let rank: String = numbered
+            .iter()
+            .enumerate()
+            .map(|(i, param)| format!(" WHEN {} THEN {}", param, i))
+            .collect();
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM articles a WHERE a.status IN ({})
+             ORDER BY CASE a.status{} END, a.updated_at DESC",
+            ARTICLE_COLUMNS,
+            numbered.join(", "),
+            rank
+        ))?;
 
         let articles = stmt
-            .query_map([], |row| {
-                let metadata: SourceMetadata = row
-                    .get::<_, Option<String>>(7)?
-                    .and_then(|s| serde_json::from_str(&s).ok())
-                    .unwrap_or_default();
-                Ok(Article {
-                    id: parse_uuid(row.get::<_, String>(0)?),
-                    url: row.get(1)?,
-                    title: row.get(2)?,
-                    raw_content: row.get(3)?,
-                    summary: row.get(4)?,
-                    room_id: row.get::<_, Option<String>>(5)?.map(parse_uuid),
-                    status: row
-                        .get::<_, String>(6)?
-                        .parse::<ArticleStatus>()
-                        .unwrap_or(ArticleStatus::Pending),
-                    source_metadata: metadata,
-                    created_at: parse_datetime(row.get::<_, String>(8)?),
-                    updated_at: parse_datetime(row.get::<_, String>(9)?),
-                })
-            })?
+            .query_map(params_from_iter(statuses.iter().map(|s| s.to_string())), article_from_row)?
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(articles)
     }
 
-    /// Search Articles
-    pub fn search_articles(&self, query: &str) -> StoreResult<Vec<Article>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT a.id, a.url, a.title, a.raw_content, a.summary, a.room_id, a.status, a.source_metadata, a.created_at, a.updated_at
-             FROM articles a
-             JOIN articles_fts fts ON a.rowid = fts.rowid
-             WHERE articles_fts MATCH ?
-             ORDER BY rank",
-        )?;
+    /// List the Articles filed in a Room, optionally only those with `status`,
+    /// most recently updated first
+    pub fn list_articles_in_room(
+        &self,
+        room_id: Uuid,
+        status: Option<ArticleStatus>,
+    ) -> StoreResult<Vec<Article>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM articles a
+             WHERE a.room_id = ?1 AND (?2 IS NULL OR a.status = ?2)
+             ORDER BY a.updated_at DESC",
+            ARTICLE_COLUMNS
+        ))?;
 
         let articles = stmt
-            .query_map([query], |row| {
-                let metadata: SourceMetadata = row
-                    .get::<_, Option<String>>(7)?
-                    .and_then(|s| serde_json::from_str(&s).ok())
-                    .unwrap_or_default();
-                Ok(Article {
-                    id: parse_uuid(row.get::<_, String>(0)?),
-                    url: row.get(1)?,
-                    title: row.get(2)?,
-                    raw_content: row.get(3)?,
-                    summary: row.get(4)?,
-                    room_id: row.get::<_, Option<String>>(5)?.map(parse_uuid),
-                    status: row
-                        .get::<_, String>(6)?
-                        .parse::<ArticleStatus>()
-                        .unwrap_or(ArticleStatus::Pending),
-                    source_metadata: metadata,
-                    created_at: parse_datetime(row.get::<_, String>(8)?),
-                    updated_at: parse_datetime(row.get::<_, String>(9)?),
-                })
-            })?
+            .query_map(
+                params![room_id.to_string(), status.map(|s| s.to_string())],
+                article_from_row,
+            )?
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(articles)
     }
 
-    /// Update an Article
-    pub fn update_article(&self, article: &Article) -> StoreResult<()> {
-        let metadata_json = serde_json::to_string(&article.source_metadata)?;
-        let rows = self.conn.execute(
-            "UPDATE articles SET title = ?2, raw_content = ?3, summary = ?4, room_id = ?5, status = ?6, source_metadata = ?7, updated_at = ?8
-             WHERE id = ?1",
-            params![
-                article.id.to_string(),
-                article.title,
-                article.raw_content,
-                article.summary,
-                article.room_id.map(|id| id.to_string()),
-                article.status.to_string(),
-                metadata_json,
-                article.updated_at.to_rfc3339(),
-            ],
+    /// Other Articles from the same site or by the same (first) author as
+    /// `article_id`, most recently updated first. Matching ignores case.
+    pub fn find_similar_articles(&self, article_id: Uuid) -> StoreResult<Vec<Article>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM articles a JOIN articles o ON o.id = ?1
+             WHERE a.id != o.id AND (a.site_name = o.site_name OR a.author = o.author)
+             ORDER BY a.updated_at DESC",
+            ARTICLE_COLUMNS
+        ))?;
+
+        let articles = stmt
+            .query_map([article_id.to_string()], article_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(articles)
+    }
+
+    /// Count Articles per status in a single query
+    ///
+    /// Statuses with no articles are absent from the map.
+    pub fn article_status_counts(&self) -> StoreResult<HashMap<ArticleStatus, u64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT status, COUNT(*) FROM articles GROUP BY status")?;
+
+        let rows = stmt
+            .query_map([], |row| Ok((parsed_at::<ArticleStatus>(row, 0)?, row.get::<_, u64>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut counts = HashMap::new();
+        for (status, count) in rows {
+            *counts.entry(status).or_insert(0) += count;
+        }
+
+        Ok(counts)
+    }
+
+    /// List all Articles
+    pub fn list_articles(&self) -> StoreResult<Vec<Article>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM articles a ORDER BY a.updated_at DESC",
+            ARTICLE_COLUMNS
+        ))?;
+
+        let articles = stmt
+            .query_map([], article_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(articles)
+    }
+
+    /// Search Articles
+    pub fn search_articles(&self, query: &str) -> StoreResult<Vec<Article>> {
+        check_search_query(query)?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {}
+             FROM articles a
+             JOIN articles_fts fts ON a.rowid = fts.rowid
+             WHERE articles_fts MATCH ?
+             ORDER BY rank",
+            ARTICLE_COLUMNS
+        ))?;
+
+        let articles = stmt
+            .query_map([query], article_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(articles)
+    }
+
+    /// IDs of every Article
+    pub fn article_ids(&self) -> StoreResult<Vec<Uuid>> {
+        self.ids_in("articles")
+    }
+
+    /// Total token usage and estimated cost across all stored summaries
+    pub fn total_summary_usage(&self) -> StoreResult<SummaryUsage> {
+        self.conn
+            .query_row(
+                "SELECT COALESCE(SUM(json_extract(summary_usage, '$.input_tokens')), 0),
+                        COALESCE(SUM(json_extract(summary_usage, '$.output_tokens')), 0),
+                        SUM(json_extract(summary_usage, '$.estimated_cost_usd'))
+                 FROM articles WHERE summary_usage IS NOT NULL",
+                [],
+                |row| {
+                    Ok(SummaryUsage {
+                        model: None,
+                        input_tokens: row.get(0)?,
+                        output_tokens: row.get(1)?,
+                        estimated_cost_usd: row.get(2)?,
+                    })
+                },
+            )
+            .map_err(StoreError::from)
+    }
+
+    /// Update an Article
+    pub fn update_article(&self, article: &Article) -> StoreResult<()> {
+        self.update_article_with_source(article, audit_sources::MANUAL)
+    }
+
+    /// Update an Article, attributing any status change to `source`
+    ///
+    /// A status change is written to the audit log in the same transaction as
+    /// the update, so the log never disagrees with the stored status.
+    pub fn update_article_with_source(&self, article: &Article, source: &str) -> StoreResult<()> {
+        let metadata_json = serde_json::to_string(&article.source_metadata)?;
+        let usage_json = article
+            .summary_usage
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+        let summaries_json = summaries_json(article)?;
+        let tx = self.write_transaction()?;
+        let previous: Option<String> = tx
+            .query_row(
+                "SELECT status FROM articles WHERE id = ?",
+                [article.id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let rows = tx.execute(
+            "UPDATE articles SET title = ?2, raw_content = ?3, summary = ?4, room_id = ?5, status = ?6, source_metadata = ?7, summary_usage = ?8, updated_at = ?9,
+                                 read_at = ?10, content_purged = ?11, summaries = ?12,
+                                 last_error = ?13
+             WHERE id = ?1",
+            params![
+                article.id.to_string(),
+                article.title,
+                article.raw_content,
+                article.summary,
+                article.room_id.map(|id| id.to_string()),
+                article.status.to_string(),
+                metadata_json,
+                usage_json,
+                article.updated_at.to_rfc3339(),
+                article.read_at.map(|dt| dt.to_rfc3339()),
+                article.content_purged,
+                summaries_json,
+                article.last_error,
+            ],
         )?;
 
         if rows == 0 {
             return Err(StoreError::NotFound(format!("Article {}", article.id)));
         }
+
+        let status = article.status.to_string();
+        if previous.as_deref() != Some(status.as_str()) {
+            record_transition(
+                &tx,
+                AuditEntity::Article,
+                article.id,
+                previous.as_deref(),
+                &status,
+                article.updated_at,
+                source,
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Archive every Reviewed Article last updated before `cutoff`
+    ///
+    /// Returns how many articles were archived. Each one gets an audit entry.
+    pub fn archive_reviewed_before(&self, cutoff: DateTime<Utc>) -> StoreResult<usize> {
+        let now = self.clock.now().to_rfc3339();
+        let reviewed = ArticleStatus::Reviewed.to_string();
+        let archived = ArticleStatus::Archived.to_string();
+        let tx = self.write_transaction()?;
+
+        tx.execute(
+            "INSERT INTO audit_log (entity_type, entity_id, from_status, to_status, at, source)
+             SELECT ?1, id, status, ?2, ?3, ?4 FROM articles
+             WHERE status = ?5 AND updated_at < ?6",
+            params![
+                AuditEntity::Article.to_string(),
+                archived,
+                now,
+                audit_sources::CLEANUP,
+                reviewed,
+                cutoff.to_rfc3339(),
+            ],
+        )?;
+        let count = tx.execute(
+            "UPDATE articles SET status = ?1, updated_at = ?2
+             WHERE status = ?3 AND updated_at < ?4",
+            params![archived, now, reviewed, cutoff.to_rfc3339()],
+        )?;
+
+        tx.commit()?;
+        Ok(count)
+    }
+
+    /// Drop an Article's raw content, keeping its summary, URL, and metadata
+    ///
+    /// Only Reviewed or Archived articles can be purged, since anything still
+    /// in review needs its content. `updated_at` is left alone so purging
+    /// doesn't restart retention windows.
+    pub fn purge_article_content(&self, id: Uuid) -> StoreResult<()> {
+        let article = self
+            .get_article(id)?
+            .ok_or_else(|| StoreError::NotFound(format!("Article {}", id)))?;
+        if !matches!(article.status, ArticleStatus::Reviewed | ArticleStatus::Archived) {
+            return Err(CoreError::InvalidInput(format!(
+                "Article {} is {}; only reviewed or archived articles can be purged",
+                id, article.status
+            ))
+            .into());
+        }
+
+        self.conn.execute(
+            "UPDATE articles SET raw_content = '', content_purged = 1 WHERE id = ?",
+            [id.to_string()],
+        )?;
         Ok(())
     }
 
+    /// Drop the raw content of every Reviewed or Archived Article last
+    /// updated before `cutoff`. Returns how many articles were purged.
+    pub fn purge_article_content_before(&self, cutoff: DateTime<Utc>) -> StoreResult<usize> {
+        let purged = self.conn.execute(
+            "UPDATE articles SET raw_content = '', content_purged = 1
+             WHERE status IN (?1, ?2) AND content_purged = 0 AND updated_at < ?3",
+            params![
+                ArticleStatus::Reviewed.to_string(),
+                ArticleStatus::Archived.to_string(),
+                cutoff.to_rfc3339(),
+            ],
+        )?;
+        Ok(purged)
+    }
+
     /// Delete an Article
     pub fn delete_article(&self, id: Uuid) -> StoreResult<()> {
         let rows = self
@@ -654,8 +2168,9 @@ impl Store {
     /// Create a new SummaryConfig
     pub fn create_summary_config(&self, config: &SummaryConfig) -> StoreResult<()> {
         self.conn.execute(
-            "INSERT INTO summary_configs (id, name, system_prompt, room_id, active, created_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            "INSERT INTO summary_configs (id, name, system_prompt, room_id, active, created_at,
+                                          genius_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
             params![
                 config.id.to_string(),
                 config.name,
@@ -663,6 +2178,7 @@ impl Store {
                 config.room_id.map(|id| id.to_string()),
                 config.active as i32,
                 config.created_at.to_rfc3339(),
+                config.genius_id.map(|id| id.to_string()),
             ],
         )?;
         Ok(())
@@ -672,19 +2188,9 @@ impl Store {
     pub fn get_summary_config(&self, id: Uuid) -> StoreResult<Option<SummaryConfig>> {
         self.conn
             .query_row(
-                "SELECT id, name, system_prompt, room_id, active, created_at
-                 FROM summary_configs WHERE id = ?",
+                &format!("SELECT {} FROM summary_configs WHERE id = ?", SUMMARY_CONFIG_COLUMNS),
                 [id.to_string()],
-                |row| {
-                    Ok(SummaryConfig {
-                        id: parse_uuid(row.get::<_, String>(0)?),
-                        name: row.get(1)?,
-                        system_prompt: row.get(2)?,
-                        room_id: row.get::<_, Option<String>>(3)?.map(parse_uuid),
-                        active: row.get::<_, i32>(4)? != 0,
-                        created_at: parse_datetime(row.get::<_, String>(5)?),
-                    })
-                },
+                summary_config_from_row,
             )
             .optional()
             .map_err(StoreError::from)
@@ -696,19 +2202,12 @@ impl Store {
         if let Some(rid) = room_id {
             let room_config = self.conn
                 .query_row(
-                    "SELECT id, name, system_prompt, room_id, active, created_at
-                     FROM summary_configs WHERE room_id = ? AND active = 1 LIMIT 1",
+                    &format!(
+                        "SELECT {} FROM summary_configs WHERE room_id = ? AND active = 1 LIMIT 1",
+                        SUMMARY_CONFIG_COLUMNS
+                    ),
                     [rid.to_string()],
-                    |row| {
-                        Ok(SummaryConfig {
-                            id: parse_uuid(row.get::<_, String>(0)?),
-                            name: row.get(1)?,
-                            system_prompt: row.get(2)?,
-                            room_id: row.get::<_, Option<String>>(3)?.map(parse_uuid),
-                            active: row.get::<_, i32>(4)? != 0,
-                            created_at: parse_datetime(row.get::<_, String>(5)?),
-                        })
-                    },
+                    summary_config_from_row,
                 )
                 .optional()?;
 
@@ -720,51 +2219,62 @@ impl Store {
         // Fall back to global config
         self.conn
             .query_row(
-                "SELECT id, name, system_prompt, room_id, active, created_at
-                 FROM summary_configs WHERE room_id IS NULL AND active = 1 LIMIT 1",
+                &format!(
+                    "SELECT {} FROM summary_configs WHERE room_id IS NULL AND active = 1 LIMIT 1",
+                    SUMMARY_CONFIG_COLUMNS
+                ),
                 [],
-                |row| {
-                    Ok(SummaryConfig {
-                        id: parse_uuid(row.get::<_, String>(0)?),
-                        name: row.get(1)?,
-                        system_prompt: row.get(2)?,
-                        room_id: row.get::<_, Option<String>>(3)?.map(parse_uuid),
-                        active: row.get::<_, i32>(4)? != 0,
-                        created_at: parse_datetime(row.get::<_, String>(5)?),
-                    })
-                },
+                summary_config_from_row,
             )
             .optional()
             .map_err(StoreError::from)
     }
 
+    /// The prompt to summarize an article in `room_id` with
+    ///
+    /// In order of precedence: `override_prompt`, the Room's active config,
+    /// the active global config, then [`DEFAULT_SUMMARY_PROMPT`]. A blank
+    /// override is rejected rather than skipped.
+    pub fn resolve_summary_prompt(
+        &self,
+        room_id: Option<Uuid>,
+        override_prompt: Option<&str>,
+    ) -> StoreResult<ResolvedPrompt> {
+        let config = self.get_active_summary_config(room_id)?;
+        if let Some(prompt) = override_prompt {
+            return Ok(ResolvedPrompt {
+                prompt: normalize_summary_prompt(prompt)?,
+                source: PromptSource::Override,
+                genius_id: config.and_then(|c| c.genius_id),
+            });
+        }
+        Ok(config.map_or_else(ResolvedPrompt::default_prompt, ResolvedPrompt::from_config))
+    }
+
     /// List all SummaryConfigs
     pub fn list_summary_configs(&self) -> StoreResult<Vec<SummaryConfig>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, name, system_prompt, room_id, active, created_at
-             FROM summary_configs ORDER BY room_id IS NULL DESC, name",
-        )?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM summary_configs ORDER BY room_id IS NULL DESC, name",
+            SUMMARY_CONFIG_COLUMNS
+        ))?;
 
         let configs = stmt
-            .query_map([], |row| {
-                Ok(SummaryConfig {
-                    id: parse_uuid(row.get::<_, String>(0)?),
-                    name: row.get(1)?,
-                    system_prompt: row.get(2)?,
-                    room_id: row.get::<_, Option<String>>(3)?.map(parse_uuid),
-                    active: row.get::<_, i32>(4)? != 0,
-                    created_at: parse_datetime(row.get::<_, String>(5)?),
-                })
-            })?
+            .query_map([], summary_config_from_row)?
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(configs)
     }
 
+    /// IDs of every SummaryConfig
+    pub fn summary_config_ids(&self) -> StoreResult<Vec<Uuid>> {
+        self.ids_in("summary_configs")
+    }
+
     /// Update a SummaryConfig
     pub fn update_summary_config(&self, config: &SummaryConfig) -> StoreResult<()> {
         let rows = self.conn.execute(
-            "UPDATE summary_configs SET name = ?2, system_prompt = ?3, room_id = ?4, active = ?5
+            "UPDATE summary_configs SET name = ?2, system_prompt = ?3, room_id = ?4, active = ?5,
+                                        genius_id = ?6
              WHERE id = ?1",
             params![
                 config.id.to_string(),
@@ -772,6 +2282,7 @@ impl Store {
                 config.system_prompt,
                 config.room_id.map(|id| id.to_string()),
                 config.active as i32,
+                config.genius_id.map(|id| id.to_string()),
             ],
         )?;
 
@@ -796,10 +2307,19 @@ impl Store {
     // ==================== UserAction Operations ====================
 
     /// Create a new UserAction
+    ///
+    /// Records the initial status in the audit log.
     pub fn create_user_action(&self, action: &UserAction) -> StoreResult<()> {
-        self.conn.execute(
-            "INSERT INTO user_actions (id, plan_id, source_file, line_number, title, description, status, created_at, completed_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        self.create_user_action_with_source(action, audit_sources::MANUAL)
+    }
+
+    /// Create a new UserAction, attributing its initial status to `source`
+    pub fn create_user_action_with_source(&self, action: &UserAction, source: &str) -> StoreResult<()> {
+        let tx = self.write_transaction()?;
+        tx.execute(
+            "INSERT INTO user_actions (id, plan_id, source_file, line_number, title, description, status, created_at, completed_at,
+                                       priority, due_date, source, started_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
             params![
                 action.id.to_string(),
                 action.plan_id.map(|id| id.to_string()),
@@ -810,8 +2330,22 @@ impl Store {
                 action.status.to_string(),
                 action.created_at.to_rfc3339(),
                 action.completed_at.map(|dt| dt.to_rfc3339()),
+                action.priority.map(|p| p.to_string()),
+                action.due_date.map(|dt| dt.to_rfc3339()),
+                action.source.to_string(),
+                action.started_at.map(|dt| dt.to_rfc3339()),
             ],
         )?;
+        record_transition(
+            &tx,
+            AuditEntity::UserAction,
+            action.id,
+            None,
+            &action.status.to_string(),
+            action.created_at,
+            source,
+        )?;
+        tx.commit()?;
         Ok(())
     }
 
@@ -819,25 +2353,9 @@ impl Store {
     pub fn get_user_action(&self, id: Uuid) -> StoreResult<Option<UserAction>> {
         self.conn
             .query_row(
-                "SELECT id, plan_id, source_file, line_number, title, description, status, created_at, completed_at
-                 FROM user_actions WHERE id = ?",
+                &format!("SELECT {} FROM user_actions u WHERE u.id = ?", USER_ACTION_COLUMNS),
                 [id.to_string()],
-                |row| {
-                    Ok(UserAction {
-                        id: parse_uuid(row.get::<_, String>(0)?),
-                        plan_id: row.get::<_, Option<String>>(1)?.map(parse_uuid),
-                        source_file: row.get(2)?,
-                        line_number: row.get(3)?,
-                        title: row.get(4)?,
-                        description: row.get(5)?,
-                        status: row
-                            .get::<_, String>(6)?
-                            .parse::<ActionStatus>()
-                            .unwrap_or(ActionStatus::Pending),
-                        created_at: parse_datetime(row.get::<_, String>(7)?),
-                        completed_at: row.get::<_, Option<String>>(8)?.map(parse_datetime),
-                    })
-                },
+                user_action_from_row,
             )
             .optional()
             .map_err(StoreError::from)
@@ -845,95 +2363,122 @@ impl Store {
 
     /// List all UserActions
     pub fn list_user_actions(&self) -> StoreResult<Vec<UserAction>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, plan_id, source_file, line_number, title, description, status, created_at, completed_at
-             FROM user_actions ORDER BY created_at DESC",
-        )?;
-
-        let actions = stmt
-            .query_map([], |row| {
-                Ok(UserAction {
-                    id: parse_uuid(row.get::<_, String>(0)?),
-                    plan_id: row.get::<_, Option<String>>(1)?.map(parse_uuid),
-                    source_file: row.get(2)?,
-                    line_number: row.get(3)?,
-                    title: row.get(4)?,
-                    description: row.get(5)?,
-                    status: row
-                        .get::<_, String>(6)?
-                        .parse::<ActionStatus>()
-                        .unwrap_or(ActionStatus::Pending),
-                    created_at: parse_datetime(row.get::<_, String>(7)?),
-                    completed_at: row.get::<_, Option<String>>(8)?.map(parse_datetime),
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
-
-        Ok(actions)
+        self.query_user_actions(&ActionFilter::new())
     }
 
     /// List UserActions by status
     pub fn list_user_actions_by_status(&self, status: ActionStatus) -> StoreResult<Vec<UserAction>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, plan_id, source_file, line_number, title, description, status, created_at, completed_at
-             FROM user_actions WHERE status = ? ORDER BY created_at DESC",
-        )?;
-
-        let actions = stmt
-            .query_map([status.to_string()], |row| {
-                Ok(UserAction {
-                    id: parse_uuid(row.get::<_, String>(0)?),
-                    plan_id: row.get::<_, Option<String>>(1)?.map(parse_uuid),
-                    source_file: row.get(2)?,
-                    line_number: row.get(3)?,
-                    title: row.get(4)?,
-                    description: row.get(5)?,
-                    status: row
-                        .get::<_, String>(6)?
-                        .parse::<ActionStatus>()
-                        .unwrap_or(ActionStatus::Pending),
-                    created_at: parse_datetime(row.get::<_, String>(7)?),
-                    completed_at: row.get::<_, Option<String>>(8)?.map(parse_datetime),
-                })
-            })?
-            .collect::<Result<Vec<_>, _>>()?;
-
-        Ok(actions)
+        self.query_user_actions(&ActionFilter::new().with_status(status))
     }
 
     /// List UserActions by source file
     pub fn list_user_actions_by_source(&self, source_file: &str) -> StoreResult<Vec<UserAction>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, plan_id, source_file, line_number, title, description, status, created_at, completed_at
-             FROM user_actions WHERE source_file = ? ORDER BY line_number ASC",
-        )?;
+        self.query_user_actions(&ActionFilter::new().with_source_file(source_file))
+    }
+
+    /// Group todos for a report: completed since `since`, in progress, and pending
+    pub fn generate_todo_report(&self, since: DateTime<Utc>) -> StoreResult<TodoReport> {
+        let mut completed =
+            self.query_user_actions(&ActionFilter::new().with_status(ActionStatus::Completed))?;
+        completed.retain(|a| a.completed_at.is_some_and(|at| at >= since));
+        completed.sort_by_key(|a| a.completed_at);
+
+        Ok(TodoReport {
+            since,
+            completed,
+            in_progress: self
+                .query_user_actions(&ActionFilter::new().with_status(ActionStatus::InProgress))?,
+            pending: self
+                .query_user_actions(&ActionFilter::new().with_status(ActionStatus::Pending))?,
+        })
+    }
+
+    /// Search UserActions by title and description
+    pub fn search_user_actions(&self, query: &str) -> StoreResult<Vec<UserAction>> {
+        self.query_user_actions(&ActionFilter::new().with_search(query))
+    }
+
+    /// Query UserActions matching every criterion set on `filter`.
+    ///
+    /// Results are ranked when searching, in file order when filtering by
+    /// source file, and newest first otherwise.
+    pub fn query_user_actions(&self, filter: &ActionFilter) -> StoreResult<Vec<UserAction>> {
+        let mut sql = format!("SELECT {} FROM user_actions u", USER_ACTION_COLUMNS);
+        let mut conditions = Vec::new();
+        let mut values: Vec<String> = Vec::new();
+
+        if let Some(query) = &filter.search {
+            check_search_query(query)?;
+            sql.push_str(" JOIN user_actions_fts fts ON u.rowid = fts.rowid");
+            conditions.push("user_actions_fts MATCH ?");
+            values.push(query.clone());
+        }
+        if let Some(status) = filter.status {
+            conditions.push("u.status = ?");
+            values.push(status.to_string());
+        }
+        if let Some(source_file) = &filter.source_file {
+            conditions.push("u.source_file = ?");
+            values.push(source_file.clone());
+        }
+        if let Some(priority) = filter.priority {
+            conditions.push("u.priority = ?");
+            values.push(priority.to_string());
+        }
+        if let Some(due_before) = filter.due_before {
+            conditions.push("u.due_date < ?");
+            values.push(due_before.to_rfc3339());
+        }
+        if filter.open_only {
+            conditions.push("u.status IN ('pending', 'in_progress')");
+        }
 
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(if filter.search.is_some() {
+            " ORDER BY rank"
+        } else if filter.source_file.is_some() {
+            " ORDER BY u.line_number ASC"
+        } else {
+            " ORDER BY u.created_at DESC"
+        });
+
+        let mut stmt = self.conn.prepare(&sql)?;
         let actions = stmt
-            .query_map([source_file], |row| {
-                Ok(UserAction {
-                    id: parse_uuid(row.get::<_, String>(0)?),
-                    plan_id: row.get::<_, Option<String>>(1)?.map(parse_uuid),
-                    source_file: row.get(2)?,
-                    line_number: row.get(3)?,
-                    title: row.get(4)?,
-                    description: row.get(5)?,
-                    status: row
-                        .get::<_, String>(6)?
-                        .parse::<ActionStatus>()
-                        .unwrap_or(ActionStatus::Pending),
-                    created_at: parse_datetime(row.get::<_, String>(7)?),
-                    completed_at: row.get::<_, Option<String>>(8)?.map(parse_datetime),
-                })
-            })?
+            .query_map(params_from_iter(values), user_action_from_row)?
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(actions)
     }
 
+    /// IDs of every UserAction
+    pub fn user_action_ids(&self) -> StoreResult<Vec<Uuid>> {
+        self.ids_in("user_actions")
+    }
+
     /// Update a UserAction
     pub fn update_user_action(&self, action: &UserAction) -> StoreResult<()> {
-        let rows = self.conn.execute(
-            "UPDATE user_actions SET plan_id = ?2, source_file = ?3, line_number = ?4, title = ?5, description = ?6, status = ?7, completed_at = ?8
+        self.update_user_action_with_source(action, audit_sources::MANUAL)
+    }
+
+    /// Update a UserAction, attributing any status change to `source`
+    ///
+    /// A status change is written to the audit log in the same transaction as
+    /// the update, so the log never disagrees with the stored status.
+    pub fn update_user_action_with_source(&self, action: &UserAction, source: &str) -> StoreResult<()> {
+        let tx = self.write_transaction()?;
+        let previous: Option<String> = tx
+            .query_row(
+                "SELECT status FROM user_actions WHERE id = ?",
+                [action.id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let rows = tx.execute(
+            "UPDATE user_actions SET plan_id = ?2, source_file = ?3, line_number = ?4, title = ?5, description = ?6, status = ?7, completed_at = ?8,
+                                     priority = ?9, due_date = ?10, started_at = ?11
              WHERE id = ?1",
             params![
                 action.id.to_string(),
@@ -944,15 +2489,55 @@ impl Store {
                 action.description,
                 action.status.to_string(),
                 action.completed_at.map(|dt| dt.to_rfc3339()),
+                action.priority.map(|p| p.to_string()),
+                action.due_date.map(|dt| dt.to_rfc3339()),
+                action.started_at.map(|dt| dt.to_rfc3339()),
             ],
         )?;
 
         if rows == 0 {
             return Err(StoreError::NotFound(format!("UserAction {}", action.id)));
         }
+
+        let status = action.status.to_string();
+        if previous.as_deref() != Some(status.as_str()) {
+            record_transition(
+                &tx,
+                AuditEntity::UserAction,
+                action.id,
+                previous.as_deref(),
+                &status,
+                self.clock.now(),
+                source,
+            )?;
+        }
+        tx.commit()?;
         Ok(())
     }
 
+    /// Count the UserActions synced from `source_file`, grouped by status
+    pub fn user_action_counts_by_source(
+        &self,
+        source_file: &str,
+    ) -> StoreResult<HashMap<ActionStatus, u64>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT status, COUNT(*) FROM user_actions WHERE source_file = ? GROUP BY status",
+        )?;
+
+        let rows = stmt
+            .query_map([source_file], |row| {
+                Ok((parsed_at::<ActionStatus>(row, 0)?, row.get::<_, u64>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut counts = HashMap::new();
+        for (status, count) in rows {
+            *counts.entry(status).or_insert(0) += count;
+        }
+
+        Ok(counts)
+    }
+
     /// Delete a UserAction
     pub fn delete_user_action(&self, id: Uuid) -> StoreResult<()> {
         let rows = self
@@ -972,102 +2557,1987 @@ impl Store {
             .execute("DELETE FROM user_actions WHERE source_file = ?", [source_file])?;
         Ok(rows)
     }
-}
 
-// Helper functions for parsing stored values
-fn parse_uuid(s: String) -> Uuid {
-    Uuid::parse_str(&s).unwrap_or_else(|_| Uuid::nil())
-}
+    /// Point every UserAction from the plan file `old` at `new` instead, e.g.
+    /// after the file was renamed, keeping their status and history.
+    /// Returns how many actions were moved.
+    pub fn rename_source_file(&self, old: &str, new: &str) -> StoreResult<usize> {
+        let rows = self.conn.execute(
+            "UPDATE user_actions SET source_file = ?2 WHERE source_file = ?1",
+            [old, new],
+        )?;
+        Ok(rows)
+    }
 
-fn parse_datetime(s: String) -> DateTime<Utc> {
-    DateTime::parse_from_rfc3339(&s)
-        .map(|dt| dt.with_timezone(&Utc))
-        .unwrap_or_else(|_| Utc::now())
-}
+    /// Whether `table` (a fixed internal table name) has a row with this id
+    fn row_exists(&self, table: &str, id: Uuid) -> StoreResult<bool> {
+        let exists = self.conn.query_row(
+            &format!("SELECT EXISTS(SELECT 1 FROM {} WHERE id = ?)", table),
+            [id.to_string()],
+            |row| row.get(0),
+        )?;
+        Ok(exists)
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// IDs of every row in `table` (a fixed internal table name)
+    fn ids_in(&self, table: &str) -> StoreResult<Vec<Uuid>> {
+        let mut stmt = self.conn.prepare(&format!("SELECT id FROM {}", table))?;
+        let ids = stmt
+            .query_map([], |row| uuid_at(row, 0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ids)
+    }
 
-    #[test]
-    fn test_room_crud() -> StoreResult<()> {
-        let store = Store::in_memory()?;
+    // ==================== Export ====================
 
-        // Create
-        let room = Room::new("Test Room").with_description("A test room");
-        store.create_room(&room)?;
+    /// Ids of a Room and every Room nested below it
+    pub fn room_subtree_ids(&self, room_id: Uuid) -> StoreResult<Vec<Uuid>> {
+        let mut stmt = self.conn.prepare(
+            "WITH RECURSIVE subtree(id) AS (
+                 SELECT id FROM rooms WHERE id = ?1
+                 UNION
+                 SELECT r.id FROM rooms r JOIN subtree s ON r.parent_id = s.id
+             )
+             SELECT id FROM subtree",
+        )?;
 
-        // Read
-        let fetched = store.get_room(room.id)?.expect("Room should exist");
-        assert_eq!(fetched.name, "Test Room");
+        let ids = stmt
+            .query_map([room_id.to_string()], |row| uuid_at(row, 0))?
+            .collect::<Result<Vec<_>, _>>()?;
 
-        // List
-        let rooms = store.list_rooms()?;
-        assert_eq!(rooms.len(), 1);
+        if ids.is_empty() {
+            return Err(StoreError::NotFound(format!("Room {}", room_id)));
+        }
+        Ok(ids)
+    }
 
-        // Delete
-        store.delete_room(room.id)?;
-        assert!(store.get_room(room.id)?.is_none());
+    /// Export the whole palace as pretty-printed JSON
+    pub fn export_json(&self) -> StoreResult<String> {
+        let room_ids: Vec<Uuid> = self.list_rooms()?.iter().map(|r| r.id).collect();
+        let mut export = self.export_rooms(&room_ids, false)?;
+        // Articles not filed under any room belong to a full export too
+        export.articles = self.list_articles()?;
+        Ok(serde_json::to_string_pretty(&export)?)
+    }
 
-        Ok(())
+    /// Export the given Rooms as pretty-printed JSON
+    ///
+    /// Only links between exported notes are kept, unless
+    /// `include_external_links` is set, in which case links crossing the
+    /// export boundary are listed separately under `external_links`.
+    pub fn export_room_json(
+        &self,
+        room_ids: &[Uuid],
+        include_external_links: bool,
+    ) -> StoreResult<String> {
+        let export = self.export_rooms(room_ids, include_external_links)?;
+        Ok(serde_json::to_string_pretty(&export)?)
     }
 
-    #[test]
-    fn test_note_crud() -> StoreResult<()> {
-        let store = Store::in_memory()?;
+    /// Import JSON written by [`Store::export_json`] or
+    /// [`Store::export_room_json`], from this or an older version.
+    ///
+    /// Entities are matched by id (articles also by URL) and only missing ones
+    /// are added, so importing the same file twice is harmless. A room with
+    /// the same name as an existing sibling is merged into it. Rooms whose
+    /// parent is in neither the export nor the store become top-level, and
+    /// links to notes that don't exist are skipped. Nothing is imported if
+    /// any part fails.
+    pub fn import_json(&self, json: &str) -> StoreResult<ImportReport> {
+        self.import_json_with_progress(json, &mut |_| {})
+    }
 
-        // Create a room first
-        let room = Room::new("Notes Room");
-        store.create_room(&room)?;
+    /// [`Store::import_json`], reporting after each room, note, link and
+    /// article is handled
+    pub fn import_json_with_progress(
+        &self,
+        json: &str,
+        progress: &mut dyn FnMut(Progress),
+    ) -> StoreResult<ImportReport> {
+        let export = PalaceExport::from_json(json)?;
+        if self.read_only {
+            return Err(StoreError::ReadOnly);
+        }
+        let mut report = ImportReport::default();
+        let tx = self.write_transaction()?;
+
+        // Parents before children, whatever order the file lists them in
+        let parents: HashMap<Uuid, Option<Uuid>> =
+            export.rooms.iter().map(|r| (r.id, r.parent_id)).collect();
+        let depth = |mut id: Uuid| {
+            let mut depth = 0;
+            while let Some(Some(parent)) = parents.get(&id) {
+                id = *parent;
+                depth += 1;
+                if depth > parents.len() {
+                    break;
+                }
+            }
+            depth
+        };
+        let mut rooms = export.rooms.clone();
+        rooms.sort_by_key(|r| depth(r.id));
+        // Imported rooms merged into an existing sibling of the same name
+        let mut merged: HashMap<Uuid, Uuid> = HashMap::new();
+        let total = rooms.len();
+        for (i, mut room) in rooms.into_iter().enumerate() {
+            progress(Progress::new("rooms", i + 1, total));
+            if self.get_room(room.id)?.is_some() {
+                continue;
+            }
+            if let Some(parent) = room.parent_id.map(|id| local_room_id(&merged, id)) {
+                room.parent_id = self.get_room(parent)?.map(|parent| parent.id);
+            }
+            if let Some(existing) = self.sibling_room_named(&room.name, room.parent_id, None)? {
+                merged.insert(room.id, existing.id);
+                continue;
+            }
+            self.create_room(&mut room)?;
+            report.rooms += 1;
+        }
 
-        // Create a note
-        let note =
-            Note::new(room.id, "Test Note", minmind_core::NoteType::Idea).with_content("Some content");
-        store.create_note(&note)?;
+        for (i, note) in export.notes.iter().enumerate() {
+            progress(Progress::new("notes", i + 1, export.notes.len()));
+            if !self.note_exists(note.id)? {
+                let mut note = note.clone();
+                note.room_id = local_room_id(&merged, note.room_id);
+                self.create_note(&note)?;
+                report.notes += 1;
+            }
+        }
 
-        // Read
-        let fetched = store.get_note(note.id)?.expect("Note should exist");
-        assert_eq!(fetched.title, "Test Note");
-        assert_eq!(fetched.content, "Some content");
+        let total = export.links.len() + export.external_links.len();
+        for (i, link) in export.links.iter().chain(&export.external_links).enumerate() {
+            progress(Progress::new("links", i + 1, total));
+            if self.note_exists(link.source_id)?
+                && self.note_exists(link.target_id)?
+                && self.create_link_if_absent(link)?
+            {
+                report.links += 1;
+            }
+        }
 
-        // Search
-        let results = store.search_notes("content")?;
-        assert_eq!(results.len(), 1);
+        for (i, article) in export.articles.iter().enumerate() {
+            progress(Progress::new("articles", i + 1, export.articles.len()));
+            if self.article_exists(article.id)?
+                || self.get_article_by_url(&article.url)?.is_some()
+            {
+                continue;
+            }
+            let mut article = article.clone();
+            if let Some(room_id) = article.room_id.map(|id| local_room_id(&merged, id)) {
+                article.room_id = Some(room_id);
+                if self.get_room(room_id)?.is_none() {
+                    article.room_id = None;
+                }
+            }
+            self.create_article(&article)?;
+            report.articles += 1;
+        }
 
-        Ok(())
+        tx.commit()?;
+        Ok(report)
     }
 
-    #[test]
-    fn test_article_crud() -> StoreResult<()> {
-        let store = Store::in_memory()?;
-
-        // Create
-        let article = Article::new(
-            "https://example.com/article",
-            "Test Article",
-            "Article content here",
-        );
-        store.create_article(&article)?;
+    fn export_rooms(
+        &self,
+        room_ids: &[Uuid],
+        include_external_links: bool,
+    ) -> StoreResult<PalaceExport> {
+        let mut export = PalaceExport::new();
+        let mut room_keys = Vec::new();
+        for &id in room_ids {
+            if export.rooms.iter().any(|r| r.id == id) {
+                continue;
+            }
+            let room = self
+                .get_room(id)?
+                .ok_or_else(|| StoreError::NotFound(format!("Room {}", id)))?;
+            room_keys.push(id.to_string());
+            export.rooms.push(room);
+        }
+        if room_keys.is_empty() {
+            return Ok(export);
+        }
+        // The rooms go in as one JSON array rather than a placeholder each,
+        // and notes are matched by subquery, so no export can run into
+        // SQLite's limit on bound variables
+        let rooms = serde_json::to_string(&room_keys)?;
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM notes n
+             WHERE n.room_id IN (SELECT value FROM json_each(?1)) AND n.deleted_at IS NULL
+             ORDER BY n.created_at",
+            NOTE_COLUMNS
+        ))?;
+        export.notes = stmt
+            .query_map([&rooms], note_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
 
-        // Read
-        let fetched = store.get_article(article.id)?.expect("Article should exist");
-        assert_eq!(fetched.title, "Test Article");
-        assert_eq!(fetched.status, ArticleStatus::Pending);
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM articles a
+             WHERE a.room_id IN (SELECT value FROM json_each(?1))
+             ORDER BY a.created_at",
+            ARTICLE_COLUMNS
+        ))?;
+        export.articles = stmt
+            .query_map([&rooms], article_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if export.notes.is_empty() {
+            return Ok(export);
+        }
+        let note_ids: HashSet<Uuid> = export.notes.iter().map(|n| n.id).collect();
+        let mut stmt = self.conn.prepare(&format!(
+            "WITH exported(id) AS (
+                 SELECT id FROM notes
+                 WHERE room_id IN (SELECT value FROM json_each(?1)) AND deleted_at IS NULL
+             )
+             SELECT {} FROM links
+             WHERE source_id IN (SELECT id FROM exported) OR target_id IN (SELECT id FROM exported)
+             ORDER BY created_at",
+            LINK_COLUMNS
+        ))?;
+        let links = stmt
+            .query_map([&rooms], link_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        for link in links {
+            if note_ids.contains(&link.source_id) && note_ids.contains(&link.target_id) {
+                export.links.push(link);
+            } else if include_external_links {
+                export.external_links.push(link);
+            }
+        }
+
+        Ok(export)
+    }
+
+    // ==================== Audit Operations ====================
+
+    /// Get the recorded status transitions for an entity, oldest first
+    pub fn audit_trail(&self, entity_id: Uuid) -> StoreResult<Vec<AuditEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, entity_type, entity_id, from_status, to_status, at, source
+             FROM audit_log WHERE entity_id = ? ORDER BY id ASC",
+        )?;
+
+        let entries = stmt
+            .query_map([entity_id.to_string()], |row| {
+                Ok(AuditEntry {
+                    id: row.get(0)?,
+                    entity_type: parsed_at(row, 1)?,
+                    entity_id: uuid_at(row, 2)?,
+                    from_status: row.get(3)?,
+                    to_status: row.get(4)?,
+                    at: datetime_at(row, 5)?,
+                    source: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(entries)
+    }
+
+    // ==================== Database Maintenance ====================
+
+    /// Row count and approximate size of every table, largest first
+    ///
+    /// Sizes come from the `dbstat` virtual table and include the table's
+    /// indexes. SQLite builds without `dbstat` fall back to adding up the
+    /// bytes stored in each row, which leaves out page overhead and indexes;
+    /// [`TableStat::size_estimated`] says which was used.
+    pub fn table_stats(&self) -> StoreResult<Vec<TableStat>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+        )?;
+        let tables = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        let page_sizes = self.dbstat_table_sizes()?;
+
+        let mut stats = Vec::with_capacity(tables.len());
+        for name in tables {
+            let quoted = format!("\"{}\"", name.replace('"', "\"\""));
+            let count_sql = format!("SELECT COUNT(*) FROM {}", quoted);
+            let rows: u64 = self.conn.query_row(&count_sql, [], |row| row.get(0))?;
+            let (bytes, size_estimated) = match &page_sizes {
+                Some(sizes) => (sizes.get(&name).copied().unwrap_or(0), false),
+                None => (self.table_payload_bytes(&name, &quoted)?, true),
+            };
+            stats.push(TableStat {
+                name,
+                rows,
+                bytes,
+                size_estimated,
+            });
+        }
+
+        stats.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+        Ok(stats)
+    }
+
+    /// Bytes of pages used by each table and its indexes, or `None` when this
+    /// SQLite build has no `dbstat` virtual table
+    fn dbstat_table_sizes(&self) -> StoreResult<Option<HashMap<String, u64>>> {
+        let mut stmt = match self.conn.prepare(
+            "SELECT m.tbl_name, SUM(d.pgsize) FROM dbstat d
+             JOIN sqlite_master m ON m.name = d.name
+             GROUP BY m.tbl_name",
+        ) {
+            Ok(stmt) => stmt,
+            Err(err) if err.to_string().contains("no such table: dbstat") => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let sizes = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64)))?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        Ok(Some(sizes))
+    }
+
+    /// Bytes stored in the columns of every row of `name`
+    fn table_payload_bytes(&self, name: &str, quoted: &str) -> StoreResult<u64> {
+        let mut stmt = self.conn.prepare("SELECT name FROM pragma_table_info(?)")?;
+        let columns = stmt
+            .query_map([name], |row| row.get::<_, String>(0))?
+            .map(|column| {
+                let column = column?.replace('"', "\"\"");
+                Ok(format!("COALESCE(LENGTH(CAST(\"{}\" AS BLOB)), 0)", column))
+            })
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        if columns.is_empty() {
+            return Ok(0);
+        }
+        let bytes: i64 = self.conn.query_row(
+            &format!("SELECT COALESCE(SUM({}), 0) FROM {}", columns.join(" + "), quoted),
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(bytes as u64)
+    }
+}
+
+/// Row counts of the full-text indexes against their base tables, from
+/// [`Store::search_index_health`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexHealth {
+    pub indexes: Vec<IndexCount>,
+}
+
+impl IndexHealth {
+    /// Whether every index holds exactly the rows of its base table
+    pub fn is_healthy(&self) -> bool {
+        self.indexes.iter().all(IndexCount::in_sync)
+    }
+
+    /// The indexes that have drifted from their base tables
+    pub fn mismatches(&self) -> impl Iterator<Item = &IndexCount> {
+        self.indexes.iter().filter(|count| !count.in_sync())
+    }
+}
+
+/// One full-text index's row count next to its base table's
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexCount {
+    pub index: &'static str,
+    pub table: &'static str,
+    /// Rows in the base table
+    pub rows: u64,
+    /// Rows the index holds
+    pub indexed: u64,
+}
+
+impl IndexCount {
+    pub fn in_sync(&self) -> bool {
+        self.rows == self.indexed
+    }
+}
+
+/// How many rows a table holds and roughly how much space it takes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableStat {
+    pub name: String,
+    pub rows: u64,
+    /// Approximate size in bytes
+    pub bytes: u64,
+    /// Whether `bytes` was estimated from row contents because `dbstat` is
+    /// unavailable, rather than measured from the pages on disk
+    pub size_estimated: bool,
+}
+
+/// Whether a full-text query has nothing to search for: it is empty, or made
+/// up only of whitespace and punctuation like `*`
+pub fn is_blank_search_query(query: &str) -> bool {
+    !query.chars().any(char::is_alphanumeric)
+}
+
+/// The first bare FTS operator (`AND`, `OR`, `NOT` or `NEAR`) in a query whose
+/// words are all operators, leaving nothing to match. A quoted `"AND"` is a
+/// term, not an operator.
+fn operator_only_query(query: &str) -> Option<&str> {
+    let mut words = query
+        .split_whitespace()
+        .map(|token| token.trim_matches(['(', ')']))
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .peekable();
+    let first = *words.peek()?;
+    words.all(|word| matches!(word, "AND" | "OR" | "NOT" | "NEAR")).then_some(first)
+}
+
+fn check_search_query(query: &str) -> StoreResult<()> {
+    if is_blank_search_query(query) {
+        return Err(StoreError::InvalidQuery("empty search query".to_string()));
+    }
+    if let Some(operator) = operator_only_query(query) {
+        return Err(StoreError::InvalidQuery(format!(
+            "{} is a search operator; to search for the word, quote it: '\"{}\"'",
+            operator, operator
+        )));
+    }
+    Ok(())
+}
+
+/// Template names are matched case-insensitively, like note types
+fn normalize_template_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// The id an imported room has here: the existing room it was merged into,
+/// or its own
+fn local_room_id(merged: &HashMap<Uuid, Uuid>, id: Uuid) -> Uuid {
+    merged.get(&id).copied().unwrap_or(id)
+}
+
+/// Drop tags no Note refers to any more
+fn delete_unused_tags(conn: &Connection) -> StoreResult<()> {
+    conn.execute(
+        "DELETE FROM tags WHERE id NOT IN (SELECT DISTINCT tag_id FROM note_tags)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// The blob hashes selected by `sql`, a query taking one parameter
+fn attachment_hashes(conn: &Connection, sql: &str, param: &str) -> StoreResult<Vec<String>> {
+    let hashes = conn
+        .prepare(sql)?
+        .query_map([param], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(hashes)
+}
+
+/// The attachment directory for a database file: next to it
+fn attachments_dir_for(db_path: &Path) -> Option<PathBuf> {
+    db_path.parent().map(|dir| dir.join(ATTACHMENTS_DIR_NAME))
+}
+
+/// Whether a SQLite error means the file itself is damaged or not a database
+fn is_corruption(err: &rusqlite::Error) -> bool {
+    matches!(
+        err.sqlite_error_code(),
+        Some(rusqlite::ErrorCode::NotADatabase | rusqlite::ErrorCode::DatabaseCorrupt)
+    )
+}
+
+/// Append a status transition to the audit log
+fn record_transition(
+    conn: &Connection,
+    entity_type: AuditEntity,
+    entity_id: Uuid,
+    from_status: Option<&str>,
+    to_status: &str,
+    at: DateTime<Utc>,
+    source: &str,
+) -> StoreResult<()> {
+    conn.execute(
+        "INSERT INTO audit_log (entity_type, entity_id, from_status, to_status, at, source)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            entity_type.to_string(),
+            entity_id.to_string(),
+            from_status,
+            to_status,
+            at.to_rfc3339(),
+            source,
+        ],
+    )?;
+    Ok(())
+}
+
+// Row mapping helpers shared by queries that select the standard column lists
+
+const ROOM_COLUMNS: &str = "id, name, slug, description, parent_id, created_at, updated_at";
+
+fn room_from_row(row: &Row) -> rusqlite::Result<Room> {
+    Ok(Room {
+        id: uuid_at(row, 0)?,
+        name: row.get(1)?,
+        slug: row.get(2)?,
+        description: row.get(3)?,
+        parent_id: optional_uuid_at(row, 4)?,
+        created_at: datetime_at(row, 5)?,
+        updated_at: datetime_at(row, 6)?,
+    })
+}
+
+const LINK_COLUMNS: &str = "id, source_id, target_id, link_type, created_at";
+
+fn link_from_row(row: &Row) -> rusqlite::Result<Link> {
+    Ok(Link {
+        id: uuid_at(row, 0)?,
+        source_id: uuid_at(row, 1)?,
+        target_id: uuid_at(row, 2)?,
+        link_type: row.get(3)?,
+        created_at: datetime_at(row, 4)?,
+    })
+}
+
+const NOTE_COLUMNS: &str = "n.id, n.room_id, n.title, n.content, n.note_type, n.status, \
+     n.created_at, n.updated_at, n.content_format, n.deleted_at, n.source";
+
+fn note_from_row(row: &Row) -> rusqlite::Result<Note> {
+    Ok(Note {
+        id: uuid_at(row, 0)?,
+        room_id: uuid_at(row, 1)?,
+        title: row.get(2)?,
+        content: row.get(3)?,
+        note_type: row
+            .get::<_, String>(4)?
+            .parse::<NoteType>()
+            .unwrap_or(NoteType::Idea),
+        status: row
+            .get::<_, Option<String>>(5)?
+            .and_then(|s| s.parse::<Status>().ok()),
+        created_at: datetime_at(row, 6)?,
+        updated_at: datetime_at(row, 7)?,
+        content_format: row
+            .get::<_, String>(8)?
+            .parse::<ContentFormat>()
+            .unwrap_or_default(),
+        deleted_at: optional_datetime_at(row, 9)?,
+        source: row
+            .get::<_, String>(10)?
+            .parse::<EntitySource>()
+            .unwrap_or_default(),
+    })
+}
+
+const ATTACHMENT_COLUMNS: &str =
+    "id, note_id, filename, content_hash, mime_type, size, stored_path, created_at";
+
+fn attachment_from_row(row: &Row) -> rusqlite::Result<Attachment> {
+    Ok(Attachment {
+        id: uuid_at(row, 0)?,
+        note_id: uuid_at(row, 1)?,
+        filename: row.get(2)?,
+        content_hash: row.get(3)?,
+        mime_type: row.get(4)?,
+        size: row.get::<_, i64>(5)? as u64,
+        stored_path: row.get(6)?,
+        created_at: datetime_at(row, 7)?,
+    })
+}
+
+const SUMMARY_CONFIG_COLUMNS: &str =
+    "id, name, system_prompt, room_id, active, created_at, genius_id";
+
+fn summary_config_from_row(row: &Row) -> rusqlite::Result<SummaryConfig> {
+    Ok(SummaryConfig {
+        id: uuid_at(row, 0)?,
+        name: row.get(1)?,
+        system_prompt: row.get(2)?,
+        room_id: optional_uuid_at(row, 3)?,
+        genius_id: optional_uuid_at(row, 6)?,
+        active: row.get::<_, i32>(4)? != 0,
+        created_at: datetime_at(row, 5)?,
+    })
+}
+
+const USER_ACTION_COLUMNS: &str = "u.id, u.plan_id, u.source_file, u.line_number, u.title, \
+     u.description, u.status, u.created_at, u.completed_at, u.priority, u.due_date, u.source, \
+     u.started_at";
+
+fn user_action_from_row(row: &Row) -> rusqlite::Result<UserAction> {
+    Ok(UserAction {
+        id: uuid_at(row, 0)?,
+        plan_id: optional_uuid_at(row, 1)?,
+        source_file: row.get(2)?,
+        line_number: row.get(3)?,
+        title: row.get(4)?,
+        description: row.get(5)?,
+        status: row
+            .get::<_, String>(6)?
+            .parse::<ActionStatus>()
+            .unwrap_or(ActionStatus::Pending),
+        created_at: datetime_at(row, 7)?,
+        completed_at: optional_datetime_at(row, 8)?,
+        priority: row
+            .get::<_, Option<String>>(9)?
+            .and_then(|p| p.parse::<Priority>().ok()),
+        due_date: optional_datetime_at(row, 10)?,
+        source: row
+            .get::<_, String>(11)?
+            .parse::<EntitySource>()
+            .unwrap_or_default(),
+        started_at: optional_datetime_at(row, 12)?,
+    })
+}
+
+/// Every live Note (in room `?1`, when it isn't NULL) with how many links
+/// join it to other live notes
+const NOTE_DEGREES_CTE: &str = "WITH degrees AS (
+        SELECT n.id, n.title, COUNT(o.id) AS degree
+        FROM notes n
+        LEFT JOIN links l ON n.id IN (l.source_id, l.target_id)
+        LEFT JOIN notes o
+            ON o.id = CASE WHEN l.source_id = n.id THEN l.target_id ELSE l.source_id END
+            AND o.deleted_at IS NULL
+        WHERE n.deleted_at IS NULL AND (?1 IS NULL OR n.room_id = ?1)
+        GROUP BY n.id
+    )";
+
+const ARTICLE_COLUMNS: &str = "a.id, a.url, a.title, a.raw_content, a.summary, a.room_id, a.status, \
+     a.source_metadata, a.summary_usage, a.created_at, a.updated_at, a.read_at, \
+     a.content_purged, a.summaries, a.last_error";
+
+fn article_from_row(row: &Row) -> rusqlite::Result<Article> {
+    let metadata: SourceMetadata = row
+        .get::<_, Option<String>>(7)?
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+    Ok(Article {
+        id: uuid_at(row, 0)?,
+        url: row.get(1)?,
+        title: row.get(2)?,
+        raw_content: row.get(3)?,
+        summary: row.get(4)?,
+        room_id: optional_uuid_at(row, 5)?,
+        status: row
+            .get::<_, String>(6)?
+            .parse::<ArticleStatus>()
+            .unwrap_or(ArticleStatus::Pending),
+        source_metadata: metadata,
+        summary_usage: row
+            .get::<_, Option<String>>(8)?
+            .and_then(|s| serde_json::from_str(&s).ok()),
+        created_at: datetime_at(row, 9)?,
+        updated_at: datetime_at(row, 10)?,
+        read_at: optional_datetime_at(row, 11)?,
+        content_purged: row.get(12)?,
+        summaries: serde_json::from_str(&row.get::<_, String>(13)?).unwrap_or_default(),
+        last_error: row.get(14)?,
+    })
+}
+
+/// An article's summaries as stored, with the primary summary filed under
+/// the default style even if the map was built without it (e.g. imported
+/// from an export that predates styles)
+fn summaries_json(article: &Article) -> StoreResult<String> {
+    let mut summaries = article.summaries.clone();
+    if let Some(summary) = &article.summary {
+        summaries
+            .entry(DEFAULT_SUMMARY_STYLE.to_string())
+            .or_insert_with(|| summary.clone());
+    }
+    Ok(serde_json::to_string(&summaries)?)
+}
+
+// Helper functions for parsing stored values. A malformed value fails the
+// read with a conversion error naming the column, rather than turning into
+// a nil id or the current time and hiding the corruption.
+
+fn uuid_at(row: &Row, idx: usize) -> rusqlite::Result<Uuid> {
+    parse_column(idx, row.get::<_, String>(idx)?, Uuid::parse_str)
+}
+
+fn optional_uuid_at(row: &Row, idx: usize) -> rusqlite::Result<Option<Uuid>> {
+    row.get::<_, Option<String>>(idx)?
+        .map(|s| parse_column(idx, s, Uuid::parse_str))
+        .transpose()
+}
+
+fn datetime_at(row: &Row, idx: usize) -> rusqlite::Result<DateTime<Utc>> {
+    parse_column(idx, row.get::<_, String>(idx)?, parse_rfc3339)
+}
+
+fn optional_datetime_at(row: &Row, idx: usize) -> rusqlite::Result<Option<DateTime<Utc>>> {
+    row.get::<_, Option<String>>(idx)?
+        .map(|s| parse_column(idx, s, parse_rfc3339))
+        .transpose()
+}
+
+/// A column holding a name that parses into `T`, such as a status
+fn parsed_at<T: std::str::FromStr<Err = String>>(row: &Row, idx: usize) -> rusqlite::Result<T> {
+    parse_column(idx, row.get::<_, String>(idx)?, |s| {
+        s.parse::<T>().map_err(CoreError::InvalidInput)
+    })
+}
+
+fn parse_rfc3339(s: &str) -> Result<DateTime<Utc>, chrono::ParseError> {
+    DateTime::parse_from_rfc3339(s).map(|dt| dt.with_timezone(&Utc))
+}
+
+fn parse_column<T, E>(
+    idx: usize,
+    s: String,
+    parse: impl Fn(&str) -> Result<T, E>,
+) -> rusqlite::Result<T>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    parse(&s).map_err(|err| {
+        rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(err))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minmind_core::{link_types, FixedClock};
+
+    #[test]
+    fn test_room_crud() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+
+        // Create
+        let mut room = Room::new("Test Room").with_description("A test room");
+        store.create_room(&mut room)?;
+
+        // Read
+        let fetched = store.get_room(room.id)?.expect("Room should exist");
+        assert_eq!(fetched.name, "Test Room");
+
+        // List
+        let rooms = store.list_rooms()?;
+        assert_eq!(rooms.len(), 1);
+
+        // Delete
+        store.delete_room(room.id)?;
+        assert!(store.get_room(room.id)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_note_crud() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+
+        // Create a room first
+        let mut room = Room::new("Notes Room");
+        store.create_room(&mut room)?;
+
+        // Create a note
+        let note =
+            Note::new(room.id, "Test Note", minmind_core::NoteType::Idea).with_content("Some content");
+        store.create_note(&note)?;
+
+        let missing_room = Uuid::new_v4();
+        let orphan = Note::new(missing_room, "Orphan", minmind_core::NoteType::Idea);
+        match store.create_note(&orphan) {
+            Err(StoreError::NotFound(what)) => assert_eq!(what, format!("Room {}", missing_room)),
+            other => panic!("expected NotFound, got {:?}", other),
+        }
+        let orphan = Article::new("https://example.com", "Orphan", "").with_room(missing_room);
+        assert!(matches!(store.create_article(&orphan), Err(StoreError::NotFound(_))));
+        assert!(store.room_exists(room.id)? && !store.room_exists(missing_room)?);
+
+        // Read
+        let fetched = store.get_note(note.id)?.expect("Note should exist");
+        assert_eq!(fetched.title, "Test Note");
+        assert_eq!(fetched.content, "Some content");
+        assert_eq!(fetched.content_format, ContentFormat::Markdown);
+
+        // Plain-text notes keep their format
+        let plain = Note::new(room.id, "Plain", minmind_core::NoteType::Log)
+            .with_content("*not* markdown")
+            .with_content_format(ContentFormat::PlainText);
+        store.create_note(&plain)?;
+        let fetched = store.get_note(plain.id)?.expect("Note should exist");
+        assert_eq!(fetched.content_format, ContentFormat::PlainText);
+
+        // Search
+        let results = store.search_notes("content")?;
+        assert_eq!(results.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_notes_in_room() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+
+        let mut work = Room::new("Work");
+        store.create_room(&mut work)?;
+        let mut home = Room::new("Home");
+        store.create_room(&mut home)?;
+
+        let here = Note::new(work.id, "Quarterly plan", NoteType::Idea).with_content("budget review");
+        let there = Note::new(home.id, "Household", NoteType::Idea).with_content("budget for groceries");
+        store.create_note(&here)?;
+        store.create_note(&there)?;
+
+        assert_eq!(store.search_notes("budget")?.len(), 2);
+
+        let scoped = store.search_notes_in_room(work.id, "budget")?;
+        assert_eq!(scoped.len(), 1);
+        assert_eq!(scoped[0].id, here.id);
+
+        assert!(store.search_notes_in_room(home.id, "quarterly")?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_notes_filtered() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut work = Room::new("Work");
+        let mut home = Room::new("Home");
+        store.create_room(&mut work)?;
+        store.create_room(&mut home)?;
+        for (room, title, note_type) in [
+            (work.id, "Deploy task", NoteType::Task),
+            (work.id, "Deploy idea", NoteType::Idea),
+            (home.id, "Deploy the shelves", NoteType::Task),
+            (work.id, "Standup", NoteType::Task),
+        ] {
+            store.create_note(&Note::new(room, title, note_type))?;
+        }
+
+        let search = |room_id, note_type| -> StoreResult<Vec<String>> {
+            let notes = store.search_notes_filtered("deploy", room_id, note_type, 10)?;
+            Ok(notes.into_iter().map(|n| n.title).collect())
+        };
+        assert_eq!(search(Some(work.id), Some(NoteType::Task))?, ["Deploy task"]);
+        assert_eq!(search(None, Some(NoteType::Task))?.len(), 2);
+        assert_eq!(search(Some(work.id), None)?.len(), 2);
+        assert!(search(Some(home.id), Some(NoteType::Idea))?.is_empty());
+        assert_eq!(
+            store.count_search_matches_filtered("deploy", Some(work.id), Some(NoteType::Task))?,
+            1
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_notes_limited() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut work = Room::new("Work");
+        store.create_room(&mut work)?;
+        let mut home = Room::new("Home");
+        store.create_room(&mut home)?;
+        for i in 0..5 {
+            store.create_note(&Note::new(work.id, format!("Budget {}", i), NoteType::Idea))?;
+        }
+        let trashed = Note::new(home.id, "Old budget", NoteType::Idea);
+        store.create_note(&trashed)?;
+        store.create_note(&Note::new(home.id, "Home budget", NoteType::Idea))?;
+        store.trash_note(trashed.id)?;
+
+        assert_eq!(store.search_notes_limited("budget", None, 3)?.len(), 3);
+        assert_eq!(store.count_search_matches("budget", None)?, 6);
+        assert_eq!(store.search_notes_limited("budget", None, 50)?.len(), 6);
+
+        let scoped = store.search_notes_limited("budget", Some(home.id), 3)?;
+        assert_eq!(scoped.len(), 1);
+        assert_eq!(store.count_search_matches("budget", Some(home.id))?, 1);
+        assert_eq!(store.count_search_matches("nothing", None)?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_note_versions() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Drafts");
+        store.create_room(&mut room)?;
+        let mut note = Note::new(room.id, "Draft", NoteType::Idea).with_content("First take");
+        store.create_note(&note)?;
+        let created = note.updated_at;
+        assert_eq!(store.note_version_count(note.id)?, 0);
+
+        note.update_content("Second take");
+        store.update_note(&note)?;
+        // Moving it leaves the text alone, so saves no version
+        store.move_note(note.id, room.id)?;
+        store.rename_note(note.id, "Final", false)?;
+        assert_eq!(store.note_version_count(note.id)?, 2);
+
+        let first = store.get_note_version(note.id, 1)?.unwrap();
+        assert_eq!((first.title.as_str(), first.content.as_str()), ("Draft", "First take"));
+        assert_eq!(first.updated_at.timestamp(), created.timestamp());
+        let second = store.get_note_version(note.id, 2)?.unwrap();
+        assert_eq!((second.title.as_str(), second.content.as_str()), ("Draft", "Second take"));
+        assert!(store.get_note_version(note.id, 3)?.is_none());
+
+        let current = store.get_note(note.id)?.unwrap();
+        assert!(current.diff(&second).title_changed());
+        assert!(!current.diff(&second).content_changed());
+
+        store.delete_note(note.id)?;
+        assert_eq!(store.note_version_count(note.id)?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_rooms() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut keep = Room::new("Reading");
+        store.create_room(&mut keep)?;
+        let mut kept_child = Room::new("Papers").with_parent(keep.id);
+        store.create_room(&mut kept_child)?;
+        let mut absorb = Room::new("Books");
+        store.create_room(&mut absorb)?;
+        let mut same_name = Room::new("papers").with_parent(absorb.id);
+        store.create_room(&mut same_name)?;
+        let mut fiction = Room::new("Fiction").with_parent(absorb.id);
+        store.create_room(&mut fiction)?;
+
+        for (room, title) in [(keep.id, "Kept"), (absorb.id, "Dune"), (same_name.id, "Attention")] {
+            store.create_note(&Note::new(room, title, NoteType::Reference))?;
+        }
+        let article = Article::new("https://example.com", "Review", "").with_room(absorb.id);
+        store.create_article(&article)?;
+        store.create_summary_config(&SummaryConfig::new_for_room("Mine", "Be brief", keep.id))?;
+        let theirs = SummaryConfig::new_for_room("Theirs", "Be long", absorb.id);
+        store.create_summary_config(&theirs)?;
+
+        // Merging a room into itself or a room below it would lose rooms
+        assert!(store.merge_rooms(absorb.id, absorb.id).is_err());
+        assert!(matches!(store.merge_rooms(fiction.id, absorb.id), Err(StoreError::RoomCycle(_))));
+
+        assert_eq!(store.merge_rooms(keep.id, absorb.id)?, 2);
+
+        assert!(!store.room_exists(absorb.id)? && !store.room_exists(same_name.id)?);
+        let titles = |room| -> StoreResult<Vec<String>> {
+            let mut titles: Vec<_> =
+                store.list_notes_in_room(room)?.into_iter().map(|n| n.title).collect();
+            titles.sort();
+            Ok(titles)
+        };
+        assert_eq!(titles(keep.id)?, ["Dune", "Kept"]);
+        assert_eq!(titles(kept_child.id)?, ["Attention"]);
+        assert_eq!(store.get_room(fiction.id)?.unwrap().parent_id, Some(keep.id));
+        assert_eq!(store.get_article(article.id)?.unwrap().room_id, Some(keep.id));
+        let theirs = store.get_summary_config(theirs.id)?.unwrap();
+        assert_eq!((theirs.room_id, theirs.active), (Some(keep.id), false));
+        assert_eq!(store.get_active_summary_config(Some(keep.id))?.unwrap().name, "Mine");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_notes_in_room_recursive() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut project = Room::new("Project");
+        store.create_room(&mut project)?;
+        let mut design = Room::new("Design").with_parent(project.id);
+        store.create_room(&mut design)?;
+        let mut sketches = Room::new("Sketches").with_parent(design.id);
+        store.create_room(&mut sketches)?;
+        let mut other = Room::new("Other");
+        store.create_room(&mut other)?;
+
+        for (room, title) in [
+            (project.id, "Goals"),
+            (design.id, "Layout"),
+            (sketches.id, "Napkin"),
+            (other.id, "Unrelated"),
+        ] {
+            store.create_note(&Note::new(room, title, NoteType::Idea))?;
+        }
+        let trashed = Note::new(design.id, "Scrapped", NoteType::Idea);
+        store.create_note(&trashed)?;
+        store.trash_note(trashed.id)?;
+
+        let titles = |notes: Vec<Note>| {
+            let mut titles: Vec<String> = notes.into_iter().map(|n| n.title).collect();
+            titles.sort();
+            titles
+        };
+        let everything = ["Goals", "Layout", "Napkin"];
+        assert_eq!(titles(store.notes_in_room_recursive(project.id)?), everything);
+        assert_eq!(titles(store.notes_in_room_recursive(design.id)?), ["Layout", "Napkin"]);
+
+        // A parent cycle (which the Store refuses to create) still terminates
+        store.conn.execute(
+            "UPDATE rooms SET parent_id = ?1 WHERE id = ?2",
+            [sketches.id.to_string(), project.id.to_string()],
+        )?;
+        assert_eq!(titles(store.notes_in_room_recursive(design.id)?), everything);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_latest_and_first_note_in_room() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Journal");
+        store.create_room(&mut room)?;
+        assert!(store.latest_note_in_room(room.id)?.is_none());
+
+        let start = Utc::now() - Duration::days(10);
+        // (title, created days after start, updated days after start)
+        let days = [("Monday", 0, 3), ("Tuesday", 1, 8), ("Wednesday", 2, 5)];
+        for (title, created, updated) in days {
+            let mut note = Note::new(room.id, title, NoteType::Idea);
+            note.created_at = start + Duration::days(created);
+            note.updated_at = start + Duration::days(updated);
+            store.create_note(&note)?;
+        }
+        let mut trashed = Note::new(room.id, "Scrapped", NoteType::Idea);
+        trashed.created_at = start - Duration::days(1);
+        store.create_note(&trashed)?;
+        store.trash_note(trashed.id)?;
+
+        let title = |note: Option<Note>| note.map(|n| n.title);
+        assert_eq!(title(store.latest_note_in_room(room.id)?).as_deref(), Some("Tuesday"));
+        assert_eq!(title(store.first_note_in_room(room.id)?).as_deref(), Some("Monday"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_notes_in_room_filtered() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+
+        let mut room = Room::new("Mixed");
+        store.create_room(&mut room)?;
+
+        for (title, note_type, status) in [
+            ("Open task", NoteType::Task, Some(Status::Active)),
+            ("Done task", NoteType::Task, Some(Status::Completed)),
+            ("Old idea", NoteType::Idea, Some(Status::Archived)),
+            ("Live idea", NoteType::Idea, Some(Status::Active)),
+            ("Reference", NoteType::Reference, None),
+        ] {
+            let mut note = Note::new(room.id, title, note_type);
+            note.status = status;
+            store.create_note(&note)?;
+        }
+
+        let titles = |notes: Vec<Note>| {
+            let mut titles: Vec<String> = notes.into_iter().map(|n| n.title).collect();
+            titles.sort();
+            titles
+        };
+
+        assert_eq!(store.list_notes_in_room_filtered(room.id, None, None)?.len(), 5);
+        assert_eq!(
+            titles(store.list_notes_in_room_filtered(room.id, Some(NoteType::Task), None)?),
+            ["Done task", "Open task"]
+        );
+        assert_eq!(
+            titles(store.list_notes_in_room_filtered(room.id, None, Some(Status::Active))?),
+            ["Live idea", "Open task"]
+        );
+        assert_eq!(
+            titles(store.list_notes_in_room_filtered(
+                room.id,
+                Some(NoteType::Idea),
+                Some(Status::Archived)
+            )?),
+            ["Old idea"]
+        );
+        assert!(store
+            .list_notes_in_room_filtered(room.id, Some(NoteType::Log), None)?
+            .is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_graph_stats() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut home = Room::new("Home");
+        store.create_room(&mut home)?;
+        let mut away = Room::new("Away");
+        store.create_room(&mut away)?;
+
+        let note = |room: &Room, title: &str| -> StoreResult<Note> {
+            let note = Note::new(room.id, title, NoteType::Idea);
+            store.create_note(&note)?;
+            Ok(note)
+        };
+        let hub = note(&home, "Hub")?;
+        let spoke1 = note(&home, "Spoke 1")?;
+        let spoke2 = note(&home, "Spoke 2")?;
+        let loner = note(&home, "Loner")?;
+        let far = note(&away, "Far")?;
+        let trashed = note(&away, "Trashed")?;
+        let edges = [(&hub, &spoke1), (&hub, &spoke2), (&far, &hub), (&trashed, &loner)];
+        for (source, target) in edges {
+            store.create_link(&Link::new(source.id, target.id))?;
+        }
+        store.trash_note(trashed.id)?;
+
+        assert_eq!(store.count_links()?, 3);
+        assert_eq!(store.count_isolated_notes(None)?, 1);
+        assert_eq!(store.count_isolated_notes(Some(away.id))?, 0);
+
+        let all = store.graph_stats(None)?;
+        assert_eq!((all.notes, all.links, all.isolated_notes), (5, 3, 1));
+        assert!((all.average_links_per_note - 1.2).abs() < 1e-9);
+        assert_eq!(all.most_connected, Some((hub.id, "Hub".to_string(), 3)));
+
+        let home_stats = store.graph_stats(Some(home.id))?;
+        assert_eq!((home_stats.notes, home_stats.links, home_stats.isolated_notes), (4, 3, 1));
+        assert!((home_stats.average_links_per_note - 1.25).abs() < 1e-9);
+
+        let away_stats = store.graph_stats(Some(away.id))?;
+        assert_eq!((away_stats.notes, away_stats.links), (1, 1));
+        assert_eq!(away_stats.most_connected.map(|(id, _, _)| id), Some(far.id));
+
+        let empty = Store::in_memory()?.graph_stats(None)?;
+        assert_eq!(empty, GraphStats::default());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_note_count_by_type() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+
+        let mut room = Room::new("Mixed");
+        store.create_room(&mut room)?;
+        let mut other = Room::new("Other");
+        store.create_room(&mut other)?;
+
+        for (title, note_type) in [
+            ("Idea 1", NoteType::Idea),
+            ("Idea 2", NoteType::Idea),
+            ("Task 1", NoteType::Task),
+            ("Ref 1", NoteType::Reference),
+            ("Ref 2", NoteType::Reference),
+            ("Ref 3", NoteType::Reference),
+        ] {
+            store.create_note(&Note::new(room.id, title, note_type))?;
+        }
+        store.create_note(&Note::new(other.id, "Elsewhere", NoteType::Task))?;
+
+        let counts = store.note_count_by_type(room.id)?;
+        assert_eq!(counts.get(&NoteType::Idea), Some(&2));
+        assert_eq!(counts.get(&NoteType::Task), Some(&1));
+        assert_eq!(counts.get(&NoteType::Reference), Some(&3));
+        assert_eq!(counts.get(&NoteType::Log), None);
+
+        store.conn.execute("UPDATE notes SET note_type = 'doodle' WHERE title = 'Idea 1'", [])?;
+        assert!(matches!(
+            store.note_count_by_type(room.id),
+            Err(StoreError::Database(rusqlite::Error::FromSqlConversionFailure(0, _, _)))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_note_with_links() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Test Room");
+        store.create_room(&mut room)?;
+
+        let center = Note::new(room.id, "Center", NoteType::Idea);
+        let target = Note::new(room.id, "Target", NoteType::Idea);
+        let source = Note::new(room.id, "Source", NoteType::Idea);
+        let trashed = Note::new(room.id, "Trashed", NoteType::Idea);
+        for note in [&center, &target, &source, &trashed] {
+            store.create_note(note)?;
+        }
+        store.create_link(&Link::new(center.id, target.id).with_type("related"))?;
+        store.create_link(&Link::new(source.id, center.id))?;
+        store.create_link(&Link::new(center.id, trashed.id))?;
+        store.trash_note(trashed.id)?;
+
+        let loaded = store.get_note_with_links(center.id)?.expect("note exists");
+        assert_eq!(loaded.note.id, center.id);
+        assert_eq!(loaded.outgoing.len(), 1);
+        assert_eq!(loaded.outgoing[0].0.link_type.as_deref(), Some("related"));
+        assert_eq!(loaded.outgoing[0].1.title, "Target");
+        assert_eq!(loaded.incoming.len(), 1);
+        assert_eq!(loaded.incoming[0].1.title, "Source");
+
+        assert!(store.get_note_with_links(Uuid::new_v4())?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_link_exists() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Test Room");
+        store.create_room(&mut room)?;
+        let a = Note::new(room.id, "A", NoteType::Idea);
+        let b = Note::new(room.id, "B", NoteType::Idea);
+        store.create_note(&a)?;
+        store.create_note(&b)?;
+
+        assert!(!store.link_exists(a.id, b.id, None)?);
+        store.create_link(&Link::new(a.id, b.id))?;
+        assert!(store.link_exists(a.id, b.id, None)?);
+        assert!(!store.link_exists(b.id, a.id, None)?);
+        assert!(!store.link_exists(a.id, b.id, Some("related"))?);
+
+        assert!(store.create_link_if_absent(&Link::new(a.id, b.id).with_type("related"))?);
+        assert!(store.link_exists(a.id, b.id, Some("related"))?);
+        assert!(!store.create_link_if_absent(&Link::new(a.id, b.id).with_type("related"))?);
+        assert!(!store.create_link_if_absent(&Link::new(a.id, b.id))?);
+        assert_eq!(store.get_links_for_note(a.id)?.len(), 2);
+
+        let missing = Uuid::new_v4();
+        assert!(store.note_exists(a.id)?);
+        assert!(!store.note_exists(missing)?);
+        assert!(matches!(
+            store.create_link(&Link::new(a.id, missing)),
+            Err(StoreError::NotFound(_))
+        ));
+
+        let article = Article::new("https://example.com", "Read", "");
+        store.create_article(&article)?;
+        assert!(store.article_exists(article.id)?);
+        assert!(!store.article_exists(missing)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_link_types_in_use() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Test Room");
+        store.create_room(&mut room)?;
+        let notes: Vec<_> = ["A", "B", "C"]
+            .into_iter()
+            .map(|title| Note::new(room.id, title, NoteType::Idea))
+            .collect();
+        for note in &notes {
+            store.create_note(note)?;
+        }
+        assert!(store.link_types_in_use()?.is_empty());
+
+        let (a, b, c) = (notes[0].id, notes[1].id, notes[2].id);
+        store.create_link(&Link::new(a, b).with_type("inspired_by"))?;
+        store.create_link(&Link::new(a, c).with_type(link_types::BLOCKS))?;
+        store.create_link(&Link::new(b, c).with_type(link_types::BLOCKS))?;
+        store.create_link(&Link::new(c, a).with_type(link_types::RELATED))?;
+        store.create_link(&Link::new(b, a))?;
+
+        assert_eq!(store.link_types_in_use()?, ["blocks", "inspired_by", "related"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_link_rejects_self_link() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Test Room");
+        store.create_room(&mut room)?;
+        let note = Note::new(room.id, "Lonely", NoteType::Idea);
+        store.create_note(&note)?;
+
+        let result = store.create_link(&Link::new(note.id, note.id));
+        assert!(matches!(
+            result,
+            Err(StoreError::Core(CoreError::InvalidInput(_)))
+        ));
+        assert!(store.get_links_for_note(note.id)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_delete_note_report() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Test Room");
+        store.create_room(&mut room)?;
+
+        let doomed = Note::new(room.id, "Doomed", NoteType::Idea);
+        let first = Note::new(room.id, "First", NoteType::Idea);
+        let second = Note::new(room.id, "Second", NoteType::Idea);
+        for note in [&doomed, &first, &second] {
+            store.create_note(note)?;
+        }
+        store.create_link(&Link::new(doomed.id, second.id))?;
+        store.create_link(&Link::new(first.id, doomed.id))?;
+        store.create_link(&Link::new(first.id, second.id))?;
+
+        let report = store.delete_note_report(doomed.id)?;
+        assert_eq!(report.removed_links, 2);
+        assert_eq!(report.linked_titles, vec!["First", "Second"]);
+        assert!(store.get_note(doomed.id)?.is_none());
+        assert_eq!(store.get_links_for_note(first.id)?.len(), 1);
+
+        assert!(matches!(
+            store.delete_note_report(doomed.id),
+            Err(StoreError::NotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_room_parent_batch() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut outer = Room::new("Outer");
+        store.create_room(&mut outer)?;
+        let mut inner = Room::new("Inner").with_parent(outer.id);
+        store.create_room(&mut inner)?;
+        let mut other = Room::new("Other");
+        store.create_room(&mut other)?;
+
+        // Swapping the nesting is only valid as a whole: moving Outer under
+        // Inner first would create a cycle until Inner is moved out
+        store.set_room_parent_batch(&[(outer.id, Some(inner.id)), (inner.id, None)])?;
+        assert_eq!(store.get_room(outer.id)?.unwrap().parent_id, Some(inner.id));
+        assert_eq!(store.get_room(inner.id)?.unwrap().parent_id, None);
+
+        // A collectively cyclic set is rejected without applying any move
+        let result =
+            store.set_room_parent_batch(&[(other.id, Some(outer.id)), (inner.id, Some(other.id))]);
+        assert!(matches!(result, Err(StoreError::RoomCycle(_))));
+        assert_eq!(store.get_room(other.id)?.unwrap().parent_id, None);
+        assert_eq!(store.get_room(inner.id)?.unwrap().parent_id, None);
+
+        let result = store.set_room_parent_batch(&[(other.id, Some(other.id))]);
+        assert!(matches!(result, Err(StoreError::RoomCycle(_))));
+        let result = store.set_room_parent_batch(&[(Uuid::new_v4(), None)]);
+        assert!(matches!(result, Err(StoreError::NotFound(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_room_parent_batch_keeps_sibling_names_unique() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut work = Room::new("Work");
+        store.create_room(&mut work)?;
+        let mut home = Room::new("Home");
+        store.create_room(&mut home)?;
+        let mut work_notes = Room::new("Notes").with_parent(work.id);
+        store.create_room(&mut work_notes)?;
+        let mut home_notes = Room::new("notes").with_parent(home.id);
+        store.create_room(&mut home_notes)?;
+
+        // Onto an existing sibling with the same name
+        match store.set_room_parent_batch(&[(home_notes.id, Some(work.id))]) {
+            Err(StoreError::DuplicateName(name)) => assert_eq!(name, "Notes"),
+            other => panic!("expected DuplicateName, got {:?}", other),
+        }
+        // Two moved rooms landing side by side
+        let result = store.set_room_parent_batch(&[(work_notes.id, None), (home_notes.id, None)]);
+        assert!(matches!(result, Err(StoreError::DuplicateName(_))));
+        assert_eq!(store.get_room(work_notes.id)?.unwrap().parent_id, Some(work.id));
+        assert_eq!(store.get_room(home_notes.id)?.unwrap().parent_id, Some(home.id));
+
+        // Valid once the clashing sibling moves away in the same batch
+        store.set_room_parent_batch(&[(work_notes.id, None), (home_notes.id, Some(work.id))])?;
+        assert_eq!(store.get_room(home_notes.id)?.unwrap().parent_id, Some(work.id));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_room_names_unique_among_siblings() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut work = Room::new("Work");
+        store.create_room(&mut work)?;
+
+        let mut shouting = Room::new("work");
+        match store.create_room(&mut shouting) {
+            Err(StoreError::DuplicateName(name)) => assert_eq!(name, "Work"),
+            other => panic!("expected DuplicateName, got {:?}", other),
+        }
+        assert_eq!(store.list_rooms()?.len(), 1);
+
+        // The same name under another parent is fine, and keeps its casing
+        let mut nested = Room::new("WORK").with_parent(work.id);
+        store.create_room(&mut nested)?;
+        assert_eq!(store.get_room(nested.id)?.unwrap().name, "WORK");
+
+        let mut home = Room::new("Home");
+        store.create_room(&mut home)?;
+        home.rename("wOrK");
+        assert!(matches!(store.update_room(&home), Err(StoreError::DuplicateName(_))));
+        home.rename("HOME");
+        store.update_room(&home)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_malformed_stored_values_are_errors() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Room");
+        store.create_room(&mut room)?;
+        let note = Note::new(room.id, "Note", NoteType::Idea);
+        store.create_note(&note)?;
+
+        // Damage the rows the way a hand-edited or half-written file might
+        store.conn.execute_batch("PRAGMA foreign_keys = OFF")?;
+        store.conn.execute(
+            "UPDATE notes SET room_id = 'not-a-uuid' WHERE id = ?",
+            [note.id.to_string()],
+        )?;
+        assert!(matches!(
+            store.get_note(note.id),
+            Err(StoreError::Database(rusqlite::Error::FromSqlConversionFailure(1, _, _)))
+        ));
+
+        store.conn.execute(
+            "UPDATE rooms SET created_at = 'yesterday' WHERE id = ?",
+            [room.id.to_string()],
+        )?;
+        assert!(matches!(store.get_room(room.id), Err(StoreError::Database(_))));
+        assert!(store.list_rooms().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_or_create_room() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let work = store.get_or_create_room("Work", None)?;
+        let personal = store.get_or_create_room("Personal", None)?;
+        assert_eq!(store.get_or_create_room("work", None)?.id, work.id);
+
+        // The same name under different parents is a different room
+        let work_ideas = store.get_or_create_room("Ideas", Some(work.id))?;
+        let personal_ideas = store.get_or_create_room("Ideas", Some(personal.id))?;
+        assert_ne!(work_ideas.id, personal_ideas.id);
+        assert_eq!(work_ideas.parent_id, Some(work.id));
+        assert_eq!(store.get_or_create_room("Ideas", Some(work.id))?.id, work_ideas.id);
+
+        let top_ideas = store.get_or_create_room("Ideas", None)?;
+        assert_eq!(top_ideas.parent_id, None);
+        assert_eq!(store.list_rooms()?.len(), 5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_table_stats() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let article = Article::new("https://example.com/big", "Big", "x".repeat(100_000));
+        store.create_article(&article)?;
+
+        let stats = store.table_stats()?;
+        let articles = stats.iter().find(|s| s.name == "articles").unwrap();
+        assert_eq!(articles.rows, 1);
+        assert!(articles.bytes >= 100_000);
+        assert!(stats.windows(2).all(|pair| pair[0].bytes >= pair[1].bytes));
+        assert!(stats.iter().all(|s| !s.name.starts_with("sqlite_")));
+
+        // Without dbstat, sizes are the bytes stored in each row; with it,
+        // page sizes add overhead and indexes on top
+        let payload = store.table_payload_bytes("articles", "\"articles\"")?;
+        assert!(payload >= 100_000);
+        if articles.size_estimated {
+            assert_eq!(articles.bytes, payload);
+        } else {
+            assert!(payload < articles.bytes);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_templates() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        assert!(store.get_template("task")?.is_none());
+
+        store.set_template("Task", "## Goal\n\n## Steps\n")?;
+        store.set_template("meeting", "## Attendees\n")?;
+        assert_eq!(store.get_template("task")?.as_deref(), Some("## Goal\n\n## Steps\n"));
+
+        store.set_template("task", "## Goal\n")?;
+        assert_eq!(store.get_template("TASK")?.as_deref(), Some("## Goal\n"));
+        assert_eq!(store.list_templates()?, vec!["meeting", "task"]);
+
+        assert!(store.delete_template("meeting")?);
+        assert!(!store.delete_template("meeting")?);
+        assert!(store.set_template("  ", "body").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_room_json() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut project = Room::new("Project");
+        store.create_room(&mut project)?;
+        let mut child = Room::new("Child").with_parent(project.id);
+        store.create_room(&mut child)?;
+        let mut elsewhere = Room::new("Elsewhere");
+        store.create_room(&mut elsewhere)?;
+
+        let plan = Note::new(project.id, "Plan", NoteType::Idea);
+        let detail = Note::new(child.id, "Detail", NoteType::Idea);
+        let outside = Note::new(elsewhere.id, "Outside", NoteType::Idea);
+        for note in [&plan, &detail, &outside] {
+            store.create_note(note)?;
+        }
+        store.create_link(&Link::new(plan.id, detail.id))?;
+        store.create_link(&Link::new(plan.id, outside.id))?;
+        store.create_article(&Article::new("https://example.com", "Read", "").with_room(child.id))?;
+
+        let subtree = store.room_subtree_ids(project.id)?;
+        assert_eq!(subtree.len(), 2);
+
+        let export: PalaceExport =
+            serde_json::from_str(&store.export_room_json(&subtree, false)?)?;
+        assert_eq!(export.rooms.len(), 2);
+        assert_eq!(export.notes.len(), 2);
+        assert_eq!(export.links.len(), 1);
+        assert!(export.external_links.is_empty());
+        assert_eq!(export.articles.len(), 1);
+
+        let export: PalaceExport =
+            serde_json::from_str(&store.export_room_json(&[project.id], true)?)?;
+        assert_eq!(export.notes.len(), 1);
+        assert!(export.links.is_empty());
+        assert_eq!(export.external_links.len(), 2);
+        assert!(export.articles.is_empty());
+
+        let full: PalaceExport = serde_json::from_str(&store.export_json()?)?;
+        assert_eq!(full.notes.len(), 3);
+        assert_eq!(full.links.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_room_json_beyond_variable_limit() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Big");
+        store.create_room(&mut room)?;
+        let first = Note::new(room.id, "Note 0", NoteType::Idea);
+        store.create_note(&first)?;
+
+        // More notes than SQLite's default 32766 bound variables, cloned in SQL
+        // to keep the test fast
+        let copies = 33_000;
+        store.conn.execute(
+            "WITH RECURSIVE seq(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM seq WHERE i < ?1)
+             INSERT INTO notes (id, room_id, title, content, note_type, status, created_at,
+                                updated_at, content_format, deleted_at, source)
+             SELECT lower(hex(randomblob(16))), room_id, 'Note ' || i, content, note_type,
+                    status, created_at, updated_at, content_format, deleted_at, source
+             FROM notes, seq WHERE id = ?2",
+            params![copies, first.id.to_string()],
+        )?;
+        let second = Note::new(room.id, "Linked", NoteType::Idea);
+        store.create_note(&second)?;
+        store.create_link(&Link::new(first.id, second.id))?;
+
+        let export: PalaceExport =
+            serde_json::from_str(&store.export_room_json(&[room.id], false)?)?;
+        assert_eq!(export.notes.len(), copies + 2);
+        assert_eq!(export.links.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_json() -> StoreResult<()> {
+        let source = Store::in_memory()?;
+        let mut parent = Room::new("Parent");
+        source.create_room(&mut parent)?;
+        let mut child = Room::new("Child").with_parent(parent.id);
+        source.create_room(&mut child)?;
+        let first = Note::new(child.id, "First", NoteType::Idea);
+        let second = Note::new(parent.id, "Second", NoteType::Idea);
+        source.create_note(&first)?;
+        source.create_note(&second)?;
+        source.create_link(&Link::new(first.id, second.id))?;
+        let article = Article::new("https://example.com", "Read", "").with_room(child.id);
+        source.create_article(&article)?;
+
+        // Rewrite the export the way schema 1 wrote it, children listed first
+        let mut old: serde_json::Value = serde_json::from_str(&source.export_json()?)?;
+        let fields = old.as_object_mut().unwrap();
+        fields.remove("minmind_version");
+        fields.remove("schema_version");
+        fields.insert("version".to_string(), 1.into());
+        fields["rooms"].as_array_mut().unwrap().reverse();
+        let old = old.to_string();
+
+        let store = Store::in_memory()?;
+        let mut reports = Vec::new();
+        let report = store.import_json_with_progress(&old, &mut |p| reports.push(p))?;
+        assert_eq!(reports.len(), 6);
+        assert_eq!(reports[1], Progress::new("rooms", 2, 2));
+        assert!(reports.last().unwrap().is_phase_done());
+        assert_eq!(
+            report,
+            ImportReport {
+                rooms: 2,
+                notes: 2,
+                links: 1,
+                articles: 1
+            }
+        );
+        assert_eq!(store.get_room(child.id)?.unwrap().parent_id, Some(parent.id));
+        assert_eq!(store.get_links_for_note(first.id)?.len(), 1);
+        assert_eq!(store.import_json(&source.export_json()?)?, ImportReport::default());
+
+        // A same-named room elsewhere absorbs the imported one and its contents
+        let other = Store::in_memory()?;
+        let mut existing = Room::new("parent");
+        other.create_room(&mut existing)?;
+        assert_eq!(other.import_json(&old)?.rooms, 1);
+        assert_eq!(other.get_note(second.id)?.unwrap().room_id, existing.id);
+        assert_eq!(other.get_room(child.id)?.unwrap().parent_id, Some(existing.id));
+        assert!(other.get_room(parent.id)?.is_none());
+
+        let newer = old.replace(r#""version":1"#, r#""version":99"#);
+        assert!(matches!(
+            store.import_json(&newer),
+            Err(StoreError::Core(CoreError::InvalidInput(_)))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_notes_fuzzy() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Test Room");
+        store.create_room(&mut room)?;
+        for title in ["Weekly Summary", "Summer plans", "Grocery list"] {
+            store.create_note(&Note::new(room.id, title, NoteType::Idea))?;
+        }
+
+        assert!(store.search_notes("summry")?.is_empty());
+        let suggestions = store.search_notes_fuzzy("summry", None, 5)?;
+        let titles: Vec<_> = suggestions.iter().map(|n| n.title.as_str()).collect();
+        assert_eq!(titles, vec!["Weekly Summary", "Summer plans"]);
+
+        assert_eq!(store.search_notes_fuzzy("summry", Some(room.id), 1)?.len(), 1);
+        assert!(store.search_notes_fuzzy("xylophone", None, 5)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_entity_source_persisted() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Test Room");
+        store.create_room(&mut room)?;
+
+        let manual = Note::new(room.id, "Typed", NoteType::Idea);
+        let approved = Note::new(room.id, "From article", NoteType::Reference)
+            .with_source(EntitySource::ArticleApproval);
+        store.create_note(&manual)?;
+        store.create_note(&approved)?;
+        assert_eq!(store.get_note(manual.id)?.unwrap().source, EntitySource::Manual);
+        assert_eq!(store.get_note(approved.id)?.unwrap().source, EntitySource::ArticleApproval);
+
+        let copy = store.duplicate_note(approved.id, None)?;
+        assert_eq!(copy.source, EntitySource::Manual);
+
+        let synced = UserAction::from_plan("Renew cert", "plans/ops.md", 3);
+        let api = UserAction::new("Posted").with_source(EntitySource::Api);
+        store.create_user_action(&synced)?;
+        store.create_user_action(&api)?;
+        assert_eq!(store.get_user_action(synced.id)?.unwrap().source, EntitySource::PlanSync);
+        assert_eq!(store.get_user_action(api.id)?.unwrap().source, EntitySource::Api);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_similar_articles() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let every = |author: &str| {
+            SourceMetadata {
+                site_name: Some("Every".to_string()),
+                ..Default::default()
+            }
+            .with_authors([author])
+        };
+
+        let mut first = Article::new("https://every.to/a", "A", "");
+        first.source_metadata = every("Dan Shipper");
+        let mut second = Article::new("https://every.to/b", "B", "");
+        second.source_metadata = every("Evan Armstrong");
+        let mut same_author = Article::new("https://example.com/c", "C", "");
+        same_author.source_metadata = SourceMetadata::default().with_authors(["dan shipper"]);
+        let mut unrelated = Article::new("https://other.com/d", "D", "");
+        unrelated.source_metadata = SourceMetadata {
+            site_name: Some("Other".to_string()),
+            ..Default::default()
+        };
+        let bare = Article::new("https://bare.com/e", "E", "");
+        for article in [&first, &second, &same_author, &unrelated, &bare] {
+            store.create_article(article)?;
+        }
+
+        let mut titles: Vec<_> = store
+            .find_similar_articles(first.id)?
+            .into_iter()
+            .map(|a| a.title)
+            .collect();
+        titles.sort();
+        assert_eq!(titles, vec!["B", "C"]);
+        assert!(store.find_similar_articles(unrelated.id)?.is_empty());
+        assert!(store.find_similar_articles(bare.id)?.is_empty());
+        assert!(store.find_similar_articles(Uuid::new_v4())?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_search_query_rejected() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+
+        for query in ["", "   ", "\t\n", "AND", "* OR \""] {
+            assert!(matches!(store.search_notes(query), Err(StoreError::InvalidQuery(_))));
+            assert!(matches!(store.search_articles(query), Err(StoreError::InvalidQuery(_))));
+            assert!(matches!(
+                store.search_user_actions(query),
+                Err(StoreError::InvalidQuery(_))
+            ));
+        }
+        assert!(store.search_notes("rust")?.is_empty());
+        assert!(!is_blank_search_query("and"));
+
+        // Bare operators get their own message; quoted, they are terms
+        assert!(!is_blank_search_query("AND"));
+        match store.search_notes("AND") {
+            Err(StoreError::InvalidQuery(message)) => assert!(message.contains("operator")),
+            other => panic!("expected InvalidQuery, got {:?}", other),
+        }
+        let mut room = Room::new("Grammar");
+        store.create_room(&mut room)?;
+        let note = Note::new(room.id, "Conjunctions", NoteType::Reference).with_content("and or");
+        store.create_note(&note)?;
+        assert_eq!(store.search_notes("\"AND\"")?[0].id, note.id);
+        assert_eq!(store.search_notes("rust OR \"or\"")?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_rooms_with_counts() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut busy = Room::new("Busy");
+        let mut empty = Room::new("Empty");
+        store.create_room(&mut busy)?;
+        store.create_room(&mut empty)?;
+
+        store.create_note(&Note::new(busy.id, "Idea", NoteType::Idea))?;
+        store.create_note(&Note::new(busy.id, "Task", NoteType::Task))?;
+        let trashed = Note::new(busy.id, "Trashed", NoteType::Idea);
+        store.create_note(&trashed)?;
+        store.trash_note(trashed.id)?;
+        let mut article = Article::new("https://example.com", "Article", "Content");
+        article.room_id = Some(busy.id);
+        store.create_article(&article)?;
+
+        let rooms = store.list_rooms_with_counts()?;
+        assert_eq!(rooms.len(), 2);
+        let (room, counts) = &rooms[0];
+        assert_eq!(room.name, "Busy");
+        assert_eq!(
+            *counts,
+            RoomCounts {
+                notes: 2,
+                articles: 1,
+                todos: 1
+            }
+        );
+        let (room, counts) = &rooms[1];
+        assert_eq!(room.name, "Empty");
+        assert_eq!(*counts, RoomCounts::default());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_articles_by_statuses() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let pending = Article::new("https://example.com/a", "Pending", "");
+        let mut summarized = Article::new("https://example.com/b", "Summarized", "");
+        summarized.set_summary("Short");
+        let mut archived = Article::new("https://example.com/c", "Archived", "");
+        archived.archive();
+        for article in [&pending, &summarized, &archived] {
+            store.create_article(article)?;
+        }
+
+        let titles = |statuses: &[ArticleStatus]| -> StoreResult<Vec<String>> {
+            let articles = store.list_articles_by_statuses(statuses)?;
+            Ok(articles.into_iter().map(|a| a.title).collect())
+        };
+        assert_eq!(
+            titles(&[ArticleStatus::Summarized, ArticleStatus::Pending])?,
+            ["Summarized", "Pending"]
+        );
+        assert_eq!(
+            titles(&[ArticleStatus::Pending, ArticleStatus::Summarized])?,
+            ["Pending", "Summarized"]
+        );
+        assert!(titles(&[])?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_articles_in_room() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut work = Room::new("Work");
+        let mut home = Room::new("Home");
+        store.create_room(&mut work)?;
+        store.create_room(&mut home)?;
+
+        let queued = Article::new("https://example.com/a", "Queued", "").with_room(work.id);
+        let mut archived = Article::new("https://example.com/b", "Archived", "").with_room(work.id);
+        archived.archive();
+        let elsewhere = Article::new("https://example.com/c", "Elsewhere", "").with_room(home.id);
+        let unfiled = Article::new("https://example.com/d", "Unfiled", "");
+        for article in [&queued, &archived, &elsewhere, &unfiled] {
+            store.create_article(article)?;
+        }
+
+        let titles = |articles: Vec<Article>| -> Vec<String> {
+            let mut titles: Vec<_> = articles.into_iter().map(|a| a.title).collect();
+            titles.sort();
+            titles
+        };
+        assert_eq!(titles(store.list_articles_in_room(work.id, None)?), ["Archived", "Queued"]);
+        assert_eq!(
+            titles(store.list_articles_in_room(work.id, Some(ArticleStatus::Pending))?),
+            ["Queued"]
+        );
+        assert!(store.list_articles_in_room(home.id, Some(ArticleStatus::Archived))?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_article_crud() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+
+        // Create
+        let article = Article::new(
+            "https://example.com/article",
+            "Test Article",
+            "Article content here",
+        );
+        store.create_article(&article)?;
+
+        // Read
+        let fetched = store.get_article(article.id)?.expect("Article should exist");
+        assert_eq!(fetched.title, "Test Article");
+        assert_eq!(fetched.status, ArticleStatus::Pending);
 
         // Read by URL
         let by_url = store.get_article_by_url("https://example.com/article")?;
         assert!(by_url.is_some());
 
-        // List by status
-        let pending = store.list_articles_by_status(ArticleStatus::Pending)?;
-        assert_eq!(pending.len(), 1);
+        // List by status
+        let pending = store.list_articles_by_status(ArticleStatus::Pending)?;
+        assert_eq!(pending.len(), 1);
+
+        // Update
+        let mut article = fetched;
+        article.set_summary("A summary");
+        store.update_article(&article)?;
+        let updated = store.get_article(article.id)?.unwrap();
+        assert_eq!(updated.status, ArticleStatus::Summarized);
+        assert!(updated.read_at.is_none());
+
+        // Mark read
+        let mut article = updated;
+        article.mark_read();
+        store.update_article(&article)?;
+        let read = store.get_article(article.id)?.unwrap();
+        assert!(read.is_read());
+        assert_eq!(read.status, ArticleStatus::Summarized);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_article_status_counts() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        assert!(store.article_status_counts()?.is_empty());
+
+        store.create_article(&Article::new("https://example.com/1", "One", "Content"))?;
+        store.create_article(&Article::new("https://example.com/2", "Two", "Content"))?;
+        let mut summarized = Article::new("https://example.com/3", "Three", "Content");
+        summarized.set_summary("Summary");
+        store.create_article(&summarized)?;
+
+        let counts = store.article_status_counts()?;
+        assert_eq!(counts.get(&ArticleStatus::Pending), Some(&2));
+        assert_eq!(counts.get(&ArticleStatus::Summarized), Some(&1));
+        assert_eq!(counts.get(&ArticleStatus::Reviewed), None);
+
+        store.conn.execute("UPDATE articles SET status = 'lost' WHERE title = 'Two'", [])?;
+        assert!(matches!(
+            store.article_status_counts(),
+            Err(StoreError::Database(rusqlite::Error::FromSqlConversionFailure(0, _, _)))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_summary_usage_round_trip() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+
+        let mut first = Article::new("https://example.com/1", "First", "Content");
+        store.create_article(&first)?;
+        first.set_summary_with_usage(
+            "Summary",
+            SummaryUsage::new(1000, 200).with_model("claude-sonnet-4-20250514"),
+        );
+        store.update_article(&first)?;
+
+        let mut second = Article::new("https://example.com/2", "Second", "Content");
+        second.set_summary_with_usage("Summary", SummaryUsage::new(500, 100));
+        store.create_article(&second)?;
+
+        let fetched = store.get_article(first.id)?.unwrap();
+        let usage = fetched.summary_usage.expect("usage should persist");
+        assert_eq!(usage.input_tokens, 1000);
+        assert_eq!(usage.model.as_deref(), Some("claude-sonnet-4-20250514"));
+
+        let total = store.total_summary_usage()?;
+        assert_eq!(total.input_tokens, 1500);
+        assert_eq!(total.output_tokens, 300);
+        assert!((total.estimated_cost_usd.unwrap() - 0.006).abs() < 1e-9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_styled_summaries_round_trip() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut article = Article::new("https://example.com/styles", "Styles", "Content");
+        // As if imported from an export made before summary styles existed
+        article.summary = Some("Primary".to_string());
+        store.create_article(&article)?;
+        let mut fetched = store.get_article(article.id)?.unwrap();
+        assert_eq!(fetched.summaries.get(DEFAULT_SUMMARY_STYLE).unwrap(), "Primary");
+
+        fetched.set_styled_summary("tldr", "Short");
+        store.update_article(&fetched)?;
+        let fetched = store.get_article(article.id)?.unwrap();
+        assert_eq!(fetched.summary_in("tldr"), Some("Short"));
+        assert_eq!(fetched.summary_in(DEFAULT_SUMMARY_STYLE), Some("Primary"));
+
+        Ok(())
+    }
 
-        // Update
-        let mut article = fetched;
-        article.set_summary("A summary");
-        store.update_article(&article)?;
-        let updated = store.get_article(article.id)?.unwrap();
-        assert_eq!(updated.status, ArticleStatus::Summarized);
+    #[test]
+    fn test_store_shortest_unique_prefix() -> StoreResult<()> {
+        let mut store = Store::in_memory()?;
+        let a = Uuid::parse_str("12345678-0000-0000-0000-000000000000").unwrap();
+        let b = Uuid::parse_str("12349999-0000-0000-0000-000000000000").unwrap();
+
+        assert_eq!(store.shortest_unique_prefix(a, &[a, b]), "12345");
+        store.set_min_id_prefix(8);
+        assert_eq!(store.shortest_unique_prefix(a, &[a, b]), "12345678");
 
         Ok(())
     }
@@ -1081,8 +4551,8 @@ mod tests {
         store.create_summary_config(&global)?;
 
         // Create a room and room-specific config
-        let room = Room::new("Technical");
-        store.create_room(&room)?;
+        let mut room = Room::new("Technical");
+        store.create_room(&mut room)?;
         let room_config = SummaryConfig::new_for_room("Technical", "Summarize technically", room.id);
         store.create_summary_config(&room_config)?;
 
@@ -1097,6 +4567,38 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_user_action_counts_by_source() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        for (line, status) in [
+            (1, ActionStatus::Pending),
+            (2, ActionStatus::Completed),
+            (3, ActionStatus::Completed),
+            (4, ActionStatus::Pending),
+            (5, ActionStatus::Skipped),
+        ] {
+            let mut action = UserAction::from_plan("Step", "plans/001.md", line);
+            action.status = status;
+            store.create_user_action(&action)?;
+        }
+        store.create_user_action(&UserAction::from_plan("Other", "plans/002.md", 1))?;
+
+        let counts = store.user_action_counts_by_source("plans/001.md")?;
+        assert_eq!(counts.get(&ActionStatus::Pending), Some(&2));
+        assert_eq!(counts.get(&ActionStatus::Completed), Some(&2));
+        assert_eq!(counts.get(&ActionStatus::Skipped), Some(&1));
+        assert_eq!(counts.get(&ActionStatus::InProgress), None);
+        assert!(store.user_action_counts_by_source("plans/missing.md")?.is_empty());
+
+        store.conn.execute("UPDATE user_actions SET status = 'maybe' WHERE line_number = 4", [])?;
+        assert!(matches!(
+            store.user_action_counts_by_source("plans/001.md"),
+            Err(StoreError::Database(rusqlite::Error::FromSqlConversionFailure(0, _, _)))
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn test_user_action_crud() -> StoreResult<()> {
         let store = Store::in_memory()?;
@@ -1134,10 +4636,849 @@ mod tests {
         assert_eq!(updated.status, ActionStatus::Completed);
         assert!(updated.completed_at.is_some());
 
+        // Relink to a moved plan file
+        assert_eq!(store.rename_source_file("plans/001-foundation.md", "plans/001-base.md")?, 1);
+        assert!(store.list_user_actions_by_source("plans/001-foundation.md")?.is_empty());
+        let moved = store.list_user_actions_by_source("plans/001-base.md")?;
+        assert_eq!(moved[0].status, ActionStatus::Completed);
+        assert_eq!(store.rename_source_file("plans/missing.md", "plans/other.md")?, 0);
+
         // Delete
         store.delete_user_action(action.id)?;
         assert!(store.get_user_action(action.id)?.is_none());
 
         Ok(())
     }
+
+    #[test]
+    fn test_room_slug_lookup_and_collisions() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+
+        let mut first = Room::new("Deep Work");
+        store.create_room(&mut first)?;
+        assert_eq!(first.slug, "deep-work");
+
+        let mut second = Room::new("deep work!");
+        store.create_room(&mut second)?;
+        assert_eq!(second.slug, "deep-work-2");
+
+        let mut third = Room::new("Deep-Work");
+        store.create_room(&mut third)?;
+        assert_eq!(third.slug, "deep-work-3");
+
+        let fetched = store.get_room_by_slug("deep-work-2")?.expect("Room should exist");
+        assert_eq!(fetched.id, second.id);
+        assert!(store.get_room_by_slug("missing")?.is_none());
+
+        // Renaming keeps the slug stable
+        let mut renamed = fetched;
+        renamed.rename("Shallow Work");
+        store.update_room(&renamed)?;
+        let fetched = store.get_room(renamed.id)?.unwrap();
+        assert_eq!(fetched.slug, "deep-work-2");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_audit_trail_records_transitions() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+
+        let mut article = Article::new("https://example.com/a", "Audited", "Content");
+        store.create_article(&article)?;
+
+        article.set_summary("Summary");
+        store.update_article_with_source(&article, audit_sources::SUMMARIZER)?;
+
+        // Saving without a status change doesn't add an entry
+        article.title = "Audited (edited)".to_string();
+        store.update_article(&article)?;
+
+        article.mark_reviewed();
+        store.update_article_with_source(&article, audit_sources::DASHBOARD)?;
+
+        let trail = store.audit_trail(article.id)?;
+        let steps: Vec<_> = trail
+            .iter()
+            .map(|e| (e.from_status.as_deref(), e.to_status.as_str(), e.source.as_str()))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                (None, "pending", "manual"),
+                (Some("pending"), "summarized", "summarizer"),
+                (Some("summarized"), "reviewed", "dashboard"),
+            ]
+        );
+        assert!(trail.iter().all(|e| e.entity_type == AuditEntity::Article));
+
+        // Entries survive the entity's deletion
+        store.delete_article(article.id)?;
+        assert_eq!(store.audit_trail(article.id)?.len(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_archive_reviewed_before() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+
+        let mut old = Article::new("https://example.com/old", "Old", "Content");
+        old.status = ArticleStatus::Reviewed;
+        old.updated_at = Utc::now() - Duration::days(40);
+        let mut recent = Article::new("https://example.com/recent", "Recent", "Content");
+        recent.status = ArticleStatus::Reviewed;
+        let mut pending = Article::new("https://example.com/pending", "Pending", "Content");
+        pending.updated_at = Utc::now() - Duration::days(40);
+        for article in [&old, &recent, &pending] {
+            store.create_article(article)?;
+        }
+
+        let archived = store.archive_reviewed_before(Utc::now() - Duration::days(30))?;
+        assert_eq!(archived, 1);
+        assert_eq!(store.get_article(old.id)?.unwrap().status, ArticleStatus::Archived);
+        assert_eq!(store.get_article(recent.id)?.unwrap().status, ArticleStatus::Reviewed);
+        assert_eq!(store.get_article(pending.id)?.unwrap().status, ArticleStatus::Pending);
+
+        let trail = store.audit_trail(old.id)?;
+        let last = trail.last().expect("archive is audited");
+        assert_eq!(last.to_status, "archived");
+        assert_eq!(last.source, audit_sources::CLEANUP);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_purge_article_content() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+
+        let mut reviewed = Article::new("https://example.com/a", "Quantum gardening", "zucchini");
+        reviewed.set_summary("A primer on lettuce");
+        reviewed.mark_reviewed();
+        let pending = Article::new("https://example.com/b", "Pending", "Still reading");
+        store.create_article(&reviewed)?;
+        store.create_article(&pending)?;
+
+        assert!(matches!(
+            store.purge_article_content(pending.id),
+            Err(StoreError::Core(CoreError::InvalidInput(_)))
+        ));
+        store.purge_article_content(reviewed.id)?;
+
+        let purged = store.get_article(reviewed.id)?.unwrap();
+        assert!(purged.content_purged);
+        assert!(purged.raw_content.is_empty());
+        assert_eq!(purged.summary.as_deref(), Some("A primer on lettuce"));
+        assert_eq!(purged.url, "https://example.com/a");
+
+        // Title and summary stay searchable; the dropped content doesn't
+        assert_eq!(store.search_articles("quantum")?.len(), 1);
+        assert_eq!(store.search_articles("lettuce")?.len(), 1);
+        assert!(store.search_articles("zucchini")?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_purge_article_content_before() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+
+        let mut old = Article::new("https://example.com/old", "Old", "Content");
+        old.archive_at(Utc::now() - Duration::days(40));
+        let mut recent = Article::new("https://example.com/recent", "Recent", "Content");
+        recent.archive();
+        let mut pending = Article::new("https://example.com/pending", "Pending", "Content");
+        pending.updated_at = Utc::now() - Duration::days(40);
+        for article in [&old, &recent, &pending] {
+            store.create_article(article)?;
+        }
+
+        let cutoff = Utc::now() - Duration::days(30);
+        assert_eq!(store.purge_article_content_before(cutoff)?, 1);
+        assert!(store.get_article(old.id)?.unwrap().content_purged);
+        assert!(!store.get_article(recent.id)?.unwrap().content_purged);
+        assert!(!store.get_article(pending.id)?.unwrap().content_purged);
+        assert_eq!(store.purge_article_content_before(cutoff)?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_audit_trail_for_user_actions() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+
+        let mut action = UserAction::new("Audited todo");
+        store.create_user_action_with_source(&action, audit_sources::PLAN_SYNC)?;
+        action.start();
+        store.update_user_action(&action)?;
+        action.complete();
+        store.update_user_action(&action)?;
+
+        let trail = store.audit_trail(action.id)?;
+        assert_eq!(trail.len(), 3);
+        assert_eq!(trail[0].source, "plan_sync");
+        assert_eq!(trail[1].to_status, "in_progress");
+        assert_eq!(trail[2].from_status.as_deref(), Some("in_progress"));
+        assert_eq!(trail[2].to_status, "completed");
+
+        let stored = store.get_user_action(action.id)?.unwrap();
+        assert!(stored.started_at.is_some());
+        assert_eq!(stored.started_at, action.started_at);
+        assert_eq!(stored.cycle_time(), action.cycle_time());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_corrupt_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("minmind.db");
+        std::fs::write(&path, b"this is definitely not a sqlite database, just garbage bytes")
+            .unwrap();
+
+        match Store::open(&path) {
+            Err(StoreError::CorruptDatabase { path: reported }) => assert_eq!(reported, path),
+            Err(other) => panic!("expected CorruptDatabase, got {other:?}"),
+            Ok(_) => panic!("expected CorruptDatabase, got a store"),
+        }
+    }
+
+    #[test]
+    fn test_open_readonly() -> StoreResult<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("minmind.db");
+        let mut room = Room::new("Shared");
+        Store::open(&path)?.create_room(&mut room)?;
+
+        let store = Store::open_readonly(&path)?;
+        assert!(store.is_read_only());
+        assert_eq!(store.list_rooms()?.len(), 1);
+        let mut other = Room::new("Other");
+        assert!(matches!(store.create_room(&mut other), Err(StoreError::ReadOnly)));
+        assert!(matches!(store.delete_room(room.id), Err(StoreError::ReadOnly)));
+
+        // A database missing migrations is never upgraded in read-only mode
+        let stale = dir.path().join("stale.db");
+        Connection::open(&stale)?.execute_batch("CREATE TABLE notes (id TEXT);")?;
+        assert!(matches!(Store::open_readonly(&stale), Err(StoreError::Migration(_))));
+
+        assert!(Store::open_readonly(dir.path().join("missing.db")).is_err());
+        assert!(matches!(store.begin_exclusive(), Err(StoreError::ReadOnly)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_exclusive_transaction_blocks_other_handles() -> StoreResult<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("minmind.db");
+        let holder = Store::open(&path)?;
+        let waiter = Store::open(&path)?;
+        let impatient = Store::open(&path)?;
+        impatient.set_busy_timeout(StdDuration::from_millis(50))?;
+        waiter.set_busy_timeout(StdDuration::from_secs(5))?;
+
+        let tx = holder.begin_exclusive()?;
+        let mut room = Room::new("Import");
+        holder.create_room(&mut room)?;
+        // Store methods with their own transaction join the exclusive one
+        let note = Note::new(room.id, "Imported", NoteType::Reference);
+        holder.create_note(&note)?;
+        holder.add_note_tag(note.id, "bulk")?;
+
+        let mut blocked = Room::new("Blocked");
+        assert!(matches!(impatient.create_room(&mut blocked), Err(StoreError::Database(_))));
+
+        let waiting = std::thread::spawn(move || {
+            let mut room = Room::new("Waited");
+            waiter.create_room(&mut room)
+        });
+        std::thread::sleep(StdDuration::from_millis(200));
+        tx.commit()?;
+        waiting.join().unwrap()?;
+
+        let names: Vec<String> = holder.list_rooms()?.into_iter().map(|r| r.name).collect();
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&"Import".to_string()));
+        assert!(names.contains(&"Waited".to_string()));
+        assert_eq!(holder.tags_for_note(note.id)?, vec!["bulk".to_string()]);
+
+        // Dropping an uncommitted transaction undoes everything in it
+        let tx = holder.begin_exclusive()?;
+        let mut discarded = Room::new("Discarded");
+        holder.create_room(&mut discarded)?;
+        drop(tx);
+        assert!(holder.get_room(discarded.id)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_attachments_dedup_and_gc() -> StoreResult<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Store::open(dir.path().join("minmind.db"))?;
+        let mut room = Room::new("Files");
+        store.create_room(&mut room)?;
+        let first = Note::new(room.id, "First", NoteType::Reference);
+        let second = Note::new(room.id, "Second", NoteType::Reference);
+        store.create_note(&first)?;
+        store.create_note(&second)?;
+
+        let source = dir.path().join("diagram.png");
+        std::fs::write(&source, b"not really a png").unwrap();
+        let a = store.add_attachment(first.id, &source)?;
+        let b = store.add_attachment(second.id, &source)?;
+        assert_eq!(a.filename, "diagram.png");
+        assert_eq!(a.mime_type, "image/png");
+        assert_eq!(a.size, 16);
+        assert_eq!(a.stored_path, b.stored_path);
+        let blob = store.attachment_path(&a)?;
+        assert_eq!(std::fs::read(&blob).unwrap(), b"not really a png");
+
+        let listed = store.list_attachments(first.id)?;
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].content_hash, a.content_hash);
+
+        // The blob survives while another note still refers to it
+        store.delete_note(first.id)?;
+        assert!(store.list_attachments(first.id)?.is_empty());
+        assert!(blob.exists());
+        store.delete_note(second.id)?;
+        assert!(!blob.exists());
+
+        assert!(matches!(
+            store.add_attachment(Uuid::new_v4(), &source),
+            Err(StoreError::NotFound(_))
+        ));
+        assert!(Store::in_memory()?.add_attachment(first.id, &source).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_attachment_blobs_outlive_rolled_back_deletes() -> StoreResult<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Store::open(dir.path().join("minmind.db"))?;
+        let mut room = Room::new("Files");
+        store.create_room(&mut room)?;
+        let note = Note::new(room.id, "Attached", NoteType::Reference);
+        store.create_note(&note)?;
+        let source = dir.path().join("notes.txt");
+        std::fs::write(&source, b"attached").unwrap();
+        let blob = store.attachment_path(&store.add_attachment(note.id, &source)?)?;
+
+        // A write still in progress elsewhere, and a blob not yet recorded
+        let shard = blob.parent().unwrap();
+        let partial = shard.join("ffff.partial");
+        std::fs::write(&partial, b"half").unwrap();
+        let unrecorded = shard.join("ffff");
+        std::fs::write(&unrecorded, b"new").unwrap();
+
+        let tx = store.begin_exclusive()?;
+        store.delete_note(note.id)?;
+        assert!(blob.exists());
+        tx.rollback()?;
+        assert_eq!(store.list_attachments(note.id)?.len(), 1);
+        assert!(blob.exists());
+
+        let tx = store.begin_exclusive()?;
+        store.delete_note(note.id)?;
+        assert!(blob.exists());
+        tx.commit()?;
+        assert!(!blob.exists());
+        // Only the deleted note's blob goes
+        assert!(partial.exists() && unrecorded.exists());
+
+        // The full sweep takes the unrecorded blob but not the partial one
+        assert_eq!(store.collect_attachment_garbage()?, 1);
+        assert!(partial.exists() && !unrecorded.exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_duplicate_tag_is_noop() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Tagged");
+        store.create_room(&mut room)?;
+        let note = Note::new(room.id, "A", NoteType::Idea);
+        store.create_note(&note)?;
+
+        store.add_note_tag(note.id, "Rust")?;
+        store.add_note_tag(note.id, "  rust ")?;
+        store.add_note_tag(note.id, "#RUST")?;
+        assert_eq!(store.tags_for_note(note.id)?, vec!["rust"]);
+        assert_eq!(store.list_tags()?, vec![("rust".to_string(), 1)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tags_rename_and_counts() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Tagged");
+        store.create_room(&mut room)?;
+
+        let a = Note::new(room.id, "A", NoteType::Idea);
+        let b = Note::new(room.id, "B", NoteType::Idea);
+        store.create_note(&a)?;
+        store.create_note(&b)?;
+
+        store.add_note_tag(a.id, "#Rust")?;
+        store.add_note_tag(b.id, "rust")?;
+        store.add_note_tag(b.id, "Systems")?;
+        assert_eq!(store.tags_for_note(b.id)?, vec!["rust", "systems"]);
+        assert_eq!(
+            store.list_tags()?,
+            vec![("rust".to_string(), 2), ("systems".to_string(), 1)]
+        );
+
+        // Renaming onto an existing tag merges them
+        store.rename_tag("systems", "rust")?;
+        assert_eq!(store.list_tags()?, vec![("rust".to_string(), 2)]);
+
+        store.rename_tag("rust", "rustlang")?;
+        assert_eq!(store.list_notes_with_tag("rustlang")?.len(), 2);
+        assert!(matches!(store.rename_tag("missing", "x"), Err(StoreError::NotFound(_))));
+
+        // Removing the last use drops the tag, deleting a note drops its tags
+        assert!(store.remove_note_tag(a.id, "rustlang")?);
+        store.delete_note(b.id)?;
+        store.rename_tag("rustlang", "rl").unwrap_err();
+        assert!(store.tags_for_note(b.id)?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rename_note_updates_backlinks() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Test Room");
+        store.create_room(&mut room)?;
+
+        let target = Note::new(room.id, "Roadmap", NoteType::Idea);
+        let first = Note::new(room.id, "Standup", NoteType::Log)
+            .with_content("Discussed [[Roadmap]] and [[roadmap|the plan]].");
+        let second = Note::new(room.id, "Retro", NoteType::Log)
+            .with_content("Follow up on [[Roadmap#Q3]].");
+        let unrelated = Note::new(room.id, "Groceries", NoteType::Idea)
+            .with_content("[[Shopping]] list");
+        for note in [&target, &first, &second, &unrelated] {
+            store.create_note(note)?;
+        }
+
+        assert_eq!(store.rename_note(target.id, "Roadmap 2025", true)?, 2);
+        assert_eq!(store.get_note(target.id)?.unwrap().title, "Roadmap 2025");
+        assert_eq!(
+            store.get_note(first.id)?.unwrap().content,
+            "Discussed [[Roadmap 2025]] and [[Roadmap 2025|the plan]]."
+        );
+        assert_eq!(
+            store.get_note(second.id)?.unwrap().content,
+            "Follow up on [[Roadmap 2025#Q3]]."
+        );
+        assert_eq!(store.get_note(unrelated.id)?.unwrap().content, "[[Shopping]] list");
+
+        // Without the opt-in, content is left alone
+        assert_eq!(store.rename_note(target.id, "Roadmap", false)?, 0);
+        assert!(store.get_note(first.id)?.unwrap().content.contains("[[Roadmap 2025]]"));
+
+        let mut other = Room::new("Other Room");
+        store.create_room(&mut other)?;
+        store.move_note(target.id, other.id)?;
+        assert_eq!(store.get_note(target.id)?.unwrap().room_id, other.id);
+        assert!(matches!(
+            store.move_note(Uuid::new_v4(), other.id),
+            Err(StoreError::NotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_duplicate_note() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut templates = Room::new("Templates");
+        store.create_room(&mut templates)?;
+        let mut work = Room::new("Work");
+        store.create_room(&mut work)?;
+
+        let template = Note::new(templates.id, "Weekly review", NoteType::Reference)
+            .with_content("- wins\n- misses");
+        store.create_note(&template)?;
+        store.add_note_tag(template.id, "template")?;
+        let other = Note::new(templates.id, "Other", NoteType::Idea);
+        store.create_note(&other)?;
+        store.create_link(&Link::new(template.id, other.id))?;
+
+        let copy = store.duplicate_note(template.id, Some(work.id))?;
+        assert_ne!(copy.id, template.id);
+        assert_eq!(copy.room_id, work.id);
+        assert_eq!(copy.title, "Weekly review (copy)");
+        assert_eq!(copy.content, template.content);
+        assert_eq!(store.tags_for_note(copy.id)?, vec!["template"]);
+        assert!(store.get_links_for_note(copy.id)?.is_empty());
+
+        let same_room = store.duplicate_note(template.id, None)?;
+        assert_eq!(same_room.room_id, templates.id);
+        assert!(matches!(
+            store.duplicate_note(template.id, Some(Uuid::new_v4())),
+            Err(StoreError::NotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_trash_restore_and_purge() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Trash");
+        store.create_room(&mut room)?;
+
+        let keep = Note::new(room.id, "Keep", NoteType::Idea).with_content("findme");
+        let old = Note::new(room.id, "Old", NoteType::Idea).with_content("findme");
+        store.create_note(&keep)?;
+        store.create_note(&old)?;
+        store.create_link(&Link::new(keep.id, old.id))?;
+
+        store.trash_note(old.id)?;
+        assert_eq!(store.list_notes_in_room(room.id)?.len(), 1);
+        assert_eq!(store.list_notes()?.iter().map(|n| n.id).collect::<Vec<_>>(), [keep.id]);
+        assert_eq!(store.search_notes("findme")?.len(), 1);
+        assert!(store.get_note(old.id)?.unwrap().deleted_at.is_some());
+
+        store.restore_note(old.id)?;
+        assert_eq!(store.list_notes_in_room(room.id)?.len(), 2);
+        assert!(store.restore_note(old.id).is_err());
+
+        // Backdate the trashing so it falls outside the retention window
+        store.trash_note(old.id)?;
+        store.conn.execute(
+            "UPDATE notes SET deleted_at = ?2 WHERE id = ?1",
+            params![old.id.to_string(), (Utc::now() - Duration::days(40)).to_rfc3339()],
+        )?;
+        store.trash_note(keep.id)?;
+
+        assert_eq!(store.purge_trashed(Duration::days(30))?, 1);
+        assert!(store.get_note(old.id)?.is_none());
+        assert!(store.get_links_for_note(keep.id)?.is_empty());
+        assert_eq!(store.list_trashed_notes()?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_audit_trail_rejects_unknown_entity_types() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let action = UserAction::new("Audited");
+        store.create_user_action(&action)?;
+        store.conn.execute(
+            "UPDATE audit_log SET entity_type = 'invoice' WHERE entity_id = ?",
+            [action.id.to_string()],
+        )?;
+
+        assert!(matches!(
+            store.audit_trail(action.id),
+            Err(StoreError::Database(rusqlite::Error::FromSqlConversionFailure(1, _, _)))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_audit_trail_stamps_transitions_with_clock() -> StoreResult<()> {
+        let clock = FixedClock::new(Utc::now());
+        let mut store = Store::in_memory()?;
+        store.set_clock(clock.clone());
+
+        // Completed once before, then reopened: completed_at is stale
+        let mut action = UserAction::new("Reopened");
+        action.complete_at(clock.now() - Duration::days(3));
+        action.status = ActionStatus::Pending;
+        store.create_user_action(&action)?;
+
+        clock.advance(Duration::hours(1));
+        action.status = ActionStatus::Completed;
+        store.update_user_action(&action)?;
+
+        let trail = store.audit_trail(action.id)?;
+        assert_eq!(trail.last().unwrap().to_status, "completed");
+        assert_eq!(trail.last().unwrap().at, clock.now());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_trash_retention_with_fixed_clock() -> StoreResult<()> {
+        let clock = FixedClock::new(Utc::now());
+        let mut store = Store::in_memory()?;
+        store.set_clock(clock.clone());
+        let mut room = Room::new("Trash");
+        store.create_room(&mut room)?;
+
+        let note = Note::new(room.id, "Old", NoteType::Idea);
+        store.create_note(&note)?;
+        store.trash_note(note.id)?;
+        assert_eq!(store.get_note(note.id)?.unwrap().deleted_at, Some(clock.now()));
+
+        clock.advance(Duration::days(29));
+        assert_eq!(store.purge_trashed(Duration::days(30))?, 0);
+
+        clock.advance(Duration::days(2));
+        assert_eq!(store.purge_trashed(Duration::days(30))?, 1);
+        assert!(store.get_note(note.id)?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_genius_config_round_trip() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let genius = Genius::new("Tuned", Provider::Ollama, "llama3.1")
+            .with_config(serde_json::json!({"num_ctx": 8192}))
+            .with_temperature(0.3)?
+            .with_max_tokens(512)?;
+        store.create_genius(&genius)?;
+
+        let loaded = store.get_genius(genius.id)?.unwrap();
+        assert_eq!(loaded.temperature(), Some(0.3));
+        assert_eq!(loaded.max_tokens(), Some(512));
+        assert_eq!(loaded.config["num_ctx"], 8192);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_summary_prompt() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Technical");
+        store.create_room(&mut room)?;
+
+        let resolved = store.resolve_summary_prompt(Some(room.id), None)?;
+        assert_eq!(resolved, ResolvedPrompt::default_prompt());
+        assert_eq!(resolved.prompt, minmind_core::DEFAULT_SUMMARY_PROMPT);
+
+        let global = SummaryConfig::new_global("Learning", "Summarize for learning");
+        store.create_summary_config(&global)?;
+        let resolved = store.resolve_summary_prompt(Some(room.id), None)?;
+        assert_eq!(resolved.prompt, "Summarize for learning");
+        assert_eq!(
+            resolved.source,
+            PromptSource::GlobalConfig { id: global.id, name: "Learning".to_string() }
+        );
+
+        let genius = Genius::new("Precise", Provider::Anthropic, "claude-sonnet-4-20250514");
+        store.create_genius(&genius)?;
+        let tech =
+            SummaryConfig::new_for_room("Tech", "Be precise", room.id).with_genius(genius.id);
+        store.create_summary_config(&tech)?;
+        let resolved = store.resolve_summary_prompt(Some(room.id), None)?;
+        assert_eq!(resolved.prompt, "Be precise");
+        assert_eq!(resolved.source.to_string(), "room config 'Tech'");
+        assert_eq!(resolved.genius_id, Some(genius.id));
+        // Articles outside the room still get the global config
+        let outside = store.resolve_summary_prompt(None, None)?;
+        assert_eq!(outside.source.to_string(), "config 'Learning'");
+
+        let resolved = store.resolve_summary_prompt(Some(room.id), Some("Just the facts\n"))?;
+        assert_eq!(resolved.prompt, "Just the facts");
+        assert_eq!(resolved.source, PromptSource::Override);
+        assert_eq!(resolved.genius_id, Some(genius.id));
+        assert!(store.resolve_summary_prompt(Some(room.id), Some("  ")).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_summary_config_genius_binding() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Technical");
+        store.create_room(&mut room)?;
+
+        let genius = Genius::new("Precise", Provider::Anthropic, "claude-sonnet-4-20250514");
+        store.create_genius(&genius)?;
+        let config =
+            SummaryConfig::new_for_room("Tech", "Be precise", room.id).with_genius(genius.id);
+        store.create_summary_config(&config)?;
+
+        let active = store.get_active_summary_config(Some(room.id))?.expect("config");
+        assert_eq!(active.genius_id, Some(genius.id));
+
+        // Deleting the genius unbinds the config rather than failing
+        store.delete_genius(genius.id)?;
+        let active = store.get_active_summary_config(Some(room.id))?.expect("config");
+        assert_eq!(active.genius_id, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_user_actions() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+
+        let mut keys = UserAction::new("Rotate API keys");
+        keys.description = Some("Use the vault for staging".to_string());
+        let dns = UserAction::new("Update DNS records");
+        store.create_user_action(&keys)?;
+        store.create_user_action(&dns)?;
+
+        assert_eq!(store.search_user_actions("vault")?.len(), 1);
+        assert_eq!(store.search_user_actions("dns")?[0].id, dns.id);
+
+        // Updates and deletes keep the index current
+        let mut dns = dns;
+        dns.title = "Update nameservers".to_string();
+        store.update_user_action(&dns)?;
+        assert!(store.search_user_actions("dns")?.is_empty());
+        store.delete_user_action(keys.id)?;
+        assert!(store.search_user_actions("vault")?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rebuild_search_index() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Stale");
+        store.create_room(&mut room)?;
+
+        // Simulate rows the index never saw: write them, then wipe the index
+        let note = Note::new(room.id, "Forgotten", NoteType::Reference).with_content("needle");
+        store.create_note(&note)?;
+        store.create_article(&Article::new("https://example.com", "Haystack", "needle"))?;
+        store.conn.execute_batch(
+            "INSERT INTO notes_fts(notes_fts) VALUES('delete-all');
+             INSERT INTO articles_fts(articles_fts) VALUES('delete-all');",
+        )?;
+        assert!(store.search_notes("needle")?.is_empty());
+        assert!(store.search_articles("needle")?.is_empty());
+        let health = store.search_index_health()?;
+        assert!(!health.is_healthy());
+        let drifted: Vec<_> = health.mismatches().map(|count| count.table).collect();
+        assert_eq!(drifted, vec!["notes", "articles"]);
+        assert_eq!((health.indexes[0].rows, health.indexes[0].indexed), (1, 0));
+
+        store.rebuild_search_index()?;
+        assert!(store.search_index_health()?.is_healthy());
+        assert_eq!(store.search_notes("needle")?[0].id, note.id);
+        assert_eq!(store.search_articles("needle")?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_index_follows_raw_sql_writes() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let mut room = Room::new("Raw");
+        store.create_room(&mut room)?;
+        let note = Note::new(room.id, "Original", NoteType::Reference).with_content("needle");
+        store.create_note(&note)?;
+
+        let matches = |query: &str| -> StoreResult<Vec<String>> {
+            let mut stmt = store.conn.prepare(
+                "SELECT n.id FROM notes_fts JOIN notes n ON n.rowid = notes_fts.rowid
+                 WHERE notes_fts MATCH ?",
+            )?;
+            let ids = stmt.query_map([query], |row| row.get(0))?.collect::<Result<_, _>>()?;
+            Ok(ids)
+        };
+        let integrity_check = || {
+            store.conn.execute("INSERT INTO notes_fts(notes_fts) VALUES('integrity-check')", [])
+        };
+
+        // The triggers keep the index in step with writes that bypass Store
+        store.conn.execute(
+            "UPDATE notes SET title = 'Renamed', content = 'haystack' WHERE id = ?",
+            [note.id.to_string()],
+        )?;
+        integrity_check()?;
+        assert!(store.search_index_health()?.is_healthy());
+        assert!(matches("needle")?.is_empty());
+        assert!(matches("Original")?.is_empty());
+        assert_eq!(matches("haystack")?, [note.id.to_string()]);
+        assert_eq!(matches("title:Renamed")?, [note.id.to_string()]);
+
+        store.conn.execute("DELETE FROM notes WHERE id = ?", [note.id.to_string()])?;
+        integrity_check()?;
+        assert!(store.search_index_health()?.is_healthy());
+        assert!(matches("haystack OR Renamed")?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_todo_report() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+
+        let mut recent = UserAction::new("Recently done");
+        recent.complete();
+        let mut old = UserAction::new("Done long ago");
+        old.complete();
+        old.completed_at = Some(Utc::now() - Duration::days(10));
+        let mut wip = UserAction::new("Underway");
+        wip.start();
+        let pending = UserAction::new("Not started");
+        for action in [&recent, &old, &wip, &pending] {
+            store.create_user_action(action)?;
+        }
+
+        let report = store.generate_todo_report(Utc::now() - Duration::days(1))?;
+        let titles = |actions: &[UserAction]| {
+            actions.iter().map(|a| a.title.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(titles(&report.completed), vec!["Recently done"]);
+        assert_eq!(titles(&report.in_progress), vec!["Underway"]);
+        assert_eq!(titles(&report.pending), vec!["Not started"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_user_actions_filters_compose() -> StoreResult<()> {
+        let store = Store::in_memory()?;
+        let now = Utc::now();
+
+        let urgent = UserAction::from_plan("Renew TLS cert", "plans/ops.md", 3)
+            .with_priority(Priority::High)
+            .with_due_date(now + Duration::days(1));
+        let later = UserAction::from_plan("Renew domain", "plans/ops.md", 7)
+            .with_priority(Priority::Low)
+            .with_due_date(now + Duration::days(60));
+        let mut done = UserAction::new("Renew passport").with_priority(Priority::High);
+        done.complete();
+        for action in [&urgent, &later, &done] {
+            store.create_user_action(action)?;
+        }
+
+        let filter = ActionFilter::new().with_priority(Priority::High);
+        assert_eq!(store.query_user_actions(&filter)?.len(), 2);
+
+        let filter = filter.with_status(ActionStatus::Pending);
+        assert_eq!(store.query_user_actions(&filter)?[0].id, urgent.id);
+
+        let filter = ActionFilter::new().with_due_before(now + Duration::days(7));
+        assert_eq!(store.query_user_actions(&filter)?.len(), 1);
+
+        let mut started = UserAction::new("Renew lease").with_due_date(now - Duration::minutes(1));
+        started.start();
+        let mut finished_late =
+            UserAction::new("Renew visa").with_due_date(now - Duration::days(3));
+        finished_late.complete();
+        let not_yet = UserAction::new("Renew car").with_due_date(now + Duration::minutes(1));
+        for action in [&started, &finished_late, &not_yet] {
+            store.create_user_action(action)?;
+        }
+        let filter = ActionFilter::new().with_due_before(now).open_only();
+        let overdue = store.query_user_actions(&filter)?;
+        assert_eq!(overdue.len(), 1);
+        assert_eq!(overdue[0].id, started.id);
+
+        let filter = ActionFilter::new().with_source_file("plans/ops.md").with_search("domain");
+        let found = store.query_user_actions(&filter)?;
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].priority, Some(Priority::Low));
+        assert!(found[0].due_date.is_some());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,85 @@
+//! Transactions spanning several Store calls
+//!
+//! Each Store method that writes more than one row wraps itself in a
+//! deferred transaction: SQLite only takes the write lock at the first
+//! write, so short operations never block readers or each other for long.
+//! That is the right default, but a bulk job (an import, a backup) made of
+//! many such calls can interleave with another process's writes between
+//! them. [`Store::begin_exclusive`](crate::Store::begin_exclusive) takes the
+//! lock up front instead and holds it until the job commits; Store methods
+//! called in the meantime join that transaction rather than opening their
+//! own.
+
+use std::ops::Deref;
+
+use rusqlite::{Connection, Transaction, TransactionBehavior};
+
+use crate::{Store, StoreResult};
+
+/// A transaction holding the database's write lock from the start.
+///
+/// Store calls made while it is open become part of it. Dropping it without
+/// calling [`commit`](ExclusiveTransaction::commit) rolls everything back.
+pub struct ExclusiveTransaction<'a> {
+    tx: Transaction<'a>,
+    store: &'a Store,
+}
+
+impl<'a> ExclusiveTransaction<'a> {
+    pub(crate) fn begin(store: &'a Store, conn: &'a Connection) -> StoreResult<Self> {
+        let tx = Transaction::new_unchecked(conn, TransactionBehavior::Exclusive)?;
+        Ok(Self { tx, store })
+    }
+
+    /// Make every change since the transaction began permanent
+    ///
+    /// Attachment blobs left unreferenced by notes deleted in the
+    /// transaction are only removed now that the deletions are permanent.
+    pub fn commit(self) -> StoreResult<()> {
+        self.tx.commit()?;
+        self.store.remove_orphaned_blobs();
+        Ok(())
+    }
+
+    /// Discard every change since the transaction began
+    pub fn rollback(self) -> StoreResult<()> {
+        self.tx.rollback()?;
+        Ok(())
+    }
+}
+
+/// The transaction a single Store method writes in: its own deferred one,
+/// or the caller's [`ExclusiveTransaction`] when one is open
+pub(crate) enum WriteTransaction<'a> {
+    Own(Transaction<'a>),
+    Joined(&'a Connection),
+}
+
+impl<'a> WriteTransaction<'a> {
+    pub(crate) fn begin(conn: &'a Connection) -> StoreResult<Self> {
+        if conn.is_autocommit() {
+            Ok(Self::Own(conn.unchecked_transaction()?))
+        } else {
+            Ok(Self::Joined(conn))
+        }
+    }
+
+    /// Commit our own transaction; a joined one is left to its owner
+    pub(crate) fn commit(self) -> StoreResult<()> {
+        if let Self::Own(tx) = self {
+            tx.commit()?;
+        }
+        Ok(())
+    }
+}
+
+impl Deref for WriteTransaction<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        match self {
+            Self::Own(tx) => tx,
+            Self::Joined(conn) => conn,
+        }
+    }
+}
@@ -1,11 +1,12 @@
 """Article processing - extraction, summarization, and review."""
 
-from .extractor import ArticleExtractor, ExtractedArticle
+from .extractor import ArticleExtractor, ExtractedArticle, ExtractionError
 from .summarizer import ArticleSummarizer, Summary
 
 __all__ = [
     "ArticleExtractor",
     "ExtractedArticle",
+    "ExtractionError",
     "ArticleSummarizer",
     "Summary",
 ]
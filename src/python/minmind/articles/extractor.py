@@ -11,12 +11,31 @@ class SourceMetadata(BaseModel):
     """Metadata about the article source."""
 
     author: str | None = None
+    authors: list[str] = []
     published_at: datetime | None = None
+    # Date exactly as found in the page; the Rust side parses it leniently
+    published_raw: str | None = None
     site_name: str | None = None
     description: str | None = None
     image_url: str | None = None
 
 
+class ExtractionError(Exception):
+    """Extraction failed in a way the caller can act on.
+
+    `kind` is one of `paywall`, `timeout`, `not_found` or `no_content`, or
+    `fetch_failed` for anything else.
+    """
+
+    def __init__(self, kind: str, message: str):
+        super().__init__(message)
+        self.kind = kind
+        self.message = message
+
+    def to_dict(self) -> dict:
+        return {"error": {"kind": self.kind, "message": self.message}}
+
+
 class ExtractedArticle(BaseModel):
     """An article extracted from a URL."""
 
@@ -49,14 +68,41 @@ class ArticleExtractor:
             ExtractedArticle with title, content, and metadata
 
         Raises:
-            httpx.HTTPError: If the request fails
-            ValueError: If content extraction fails
+            ExtractionError: If the page can't be fetched or has no content
         """
         # Fetch the HTML
-        response = await self.client.get(url)
-        response.raise_for_status()
-        html = response.text
+        try:
+            response = await self.client.get(url)
+        except httpx.TimeoutException as e:
+            raise ExtractionError(
+                "timeout", f"Timed out after {self.timeout}s fetching {url}"
+            ) from e
+        except httpx.HTTPError as e:
+            raise ExtractionError("fetch_failed", f"Could not fetch {url}: {e}") from e
+
+        status = response.status_code
+        if status in (404, 410):
+            raise ExtractionError("not_found", f"{url} returned {status}")
+        if status in (401, 402, 403):
+            raise ExtractionError("paywall", f"{url} requires a login or subscription ({status})")
+        if status >= 400:
+            raise ExtractionError("fetch_failed", f"{url} returned {status}")
+
+        return self.extract_html(response.text, url)
+
+    def extract_html(self, html: str, url: str) -> ExtractedArticle:
+        """Extract article content from already-fetched HTML.
+
+        Args:
+            html: The page's HTML
+            url: The URL to record as the article's source
+
+        Returns:
+            ExtractedArticle with title, content, and metadata
 
+        Raises:
+            ExtractionError: If no article content can be found
+        """
         # Extract content using trafilatura
         content = trafilatura.extract(
             html,
@@ -67,7 +113,7 @@ class ArticleExtractor:
         )
 
         if not content:
-            raise ValueError(f"Could not extract content from {url}")
+            raise ExtractionError("no_content", f"Could not extract content from {url}")
 
         # Extract metadata
         metadata_dict = trafilatura.extract_metadata(html)
@@ -82,8 +128,13 @@ class ArticleExtractor:
                 ),
                 image_url=metadata_dict.image if hasattr(metadata_dict, "image") else None,
             )
+            if metadata.author:
+                # trafilatura joins multiple bylines with "; "
+                metadata.authors = [a.strip() for a in metadata.author.split(";") if a.strip()]
+                metadata.author = metadata.authors[0] if metadata.authors else None
             # Try to parse date
             if hasattr(metadata_dict, "date") and metadata_dict.date:
+                metadata.published_raw = str(metadata_dict.date)
                 try:
                     metadata.published_at = datetime.fromisoformat(metadata_dict.date)
                 except (ValueError, TypeError):
@@ -28,6 +28,8 @@ class Summary(BaseModel):
     model: str
     prompt_used: str
     tokens_used: int | None = None
+    input_tokens: int | None = None
+    output_tokens: int | None = None
 
 
 class SummaryConfig(BaseModel):
@@ -84,22 +86,18 @@ class ArticleSummarizer:
 
             response = await self.genius.chat(messages)
 
+            input_tokens, output_tokens = self._split_usage(response.usage)
             tokens = None
-            if response.usage:
-                tokens = response.usage.get("input_tokens", 0) + response.usage.get(
-                    "output_tokens", 0
-                )
-                # Also check for OpenAI-style token naming
-                if tokens == 0:
-                    tokens = response.usage.get("prompt_tokens", 0) + response.usage.get(
-                        "completion_tokens", 0
-                    )
+            if input_tokens is not None and output_tokens is not None:
+                tokens = input_tokens + output_tokens
 
             return Summary(
                 content=response.content,
                 model=response.model,
                 prompt_used=config.system_prompt,
                 tokens_used=tokens,
+                input_tokens=input_tokens,
+                output_tokens=output_tokens,
             )
         finally:
             # Restore original prompt
@@ -181,25 +179,35 @@ Please provide an improved summary that addresses their feedback while maintaini
 
             response = await self.genius.chat(messages)
 
+            input_tokens, output_tokens = self._split_usage(response.usage)
             tokens = None
-            if response.usage:
-                tokens = response.usage.get("input_tokens", 0) + response.usage.get(
-                    "output_tokens", 0
-                )
-                if tokens == 0:
-                    tokens = response.usage.get("prompt_tokens", 0) + response.usage.get(
-                        "completion_tokens", 0
-                    )
+            if input_tokens is not None and output_tokens is not None:
+                tokens = input_tokens + output_tokens
 
             return Summary(
                 content=response.content,
                 model=response.model,
                 prompt_used=config.system_prompt,
                 tokens_used=tokens,
+                input_tokens=input_tokens,
+                output_tokens=output_tokens,
             )
         finally:
             self.genius.config.system_prompt = original_prompt
 
+    @staticmethod
+    def _split_usage(usage: dict | None) -> tuple[int | None, int | None]:
+        """Normalize provider usage into (input_tokens, output_tokens).
+
+        Handles both Anthropic (`input_tokens`/`output_tokens`) and OpenAI
+        (`prompt_tokens`/`completion_tokens`) naming.
+        """
+        if not usage:
+            return None, None
+        if "input_tokens" in usage or "output_tokens" in usage:
+            return usage.get("input_tokens", 0), usage.get("output_tokens", 0)
+        return usage.get("prompt_tokens", 0), usage.get("completion_tokens", 0)
+
     def _build_article_message(self, article: ExtractedArticle) -> str:
         """Build the user message containing article content.
 
@@ -214,7 +222,9 @@ Please provide an improved summary that addresses their feedback while maintaini
             f"Source: {article.url}",
         ]
 
-        if article.metadata.author:
+        if article.metadata.authors:
+            parts.append(f"Authors: {', '.join(article.metadata.authors)}")
+        elif article.metadata.author:
             parts.append(f"Author: {article.metadata.author}")
 
         if article.metadata.published_at:
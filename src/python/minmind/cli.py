@@ -6,31 +6,52 @@ import json
 import os
 import sys
 
-from .articles import ArticleExtractor, ArticleSummarizer
+from .articles import ArticleExtractor, ArticleSummarizer, ExtractedArticle, ExtractionError
 from .articles.summarizer import SummaryConfig
-from .geniuses import AnthropicGenius, GeniusConfig, OpenAIGenius
+from .geniuses import AnthropicGenius, GeniusConfig, OllamaGenius, OpenAIGenius
+
+
+def _article_to_dict(article: ExtractedArticle) -> dict:
+    """Serialize an extracted article for the Rust CLI."""
+    return {
+        "url": article.url,
+        "title": article.title,
+        "content": article.content,
+        "metadata": {
+            "author": article.metadata.author,
+            "authors": article.metadata.authors,
+            "published_at": (
+                article.metadata.published_at.isoformat()
+                if article.metadata.published_at
+                else article.metadata.published_raw
+            ),
+            "site_name": article.metadata.site_name,
+            "description": article.metadata.description,
+            "image_url": article.metadata.image_url,
+        },
+    }
 
 
 async def extract_article(url: str) -> dict:
     """Extract article content from a URL."""
     async with ArticleExtractor() as extractor:
         article = await extractor.extract(url)
-        return {
-            "url": article.url,
-            "title": article.title,
-            "content": article.content,
-            "metadata": {
-                "author": article.metadata.author,
-                "published_at": (
-                    article.metadata.published_at.isoformat()
-                    if article.metadata.published_at
-                    else None
-                ),
-                "site_name": article.metadata.site_name,
-                "description": article.metadata.description,
-                "image_url": article.metadata.image_url,
-            },
-        }
+        return _article_to_dict(article)
+
+
+async def extract_file(path: str, url: str) -> dict:
+    """Extract article content from a saved HTML file."""
+    with open(path, encoding="utf-8", errors="replace") as f:
+        html = f.read()
+    async with ArticleExtractor() as extractor:
+        return _article_to_dict(extractor.extract_html(html, url))
+
+
+DEFAULT_MODELS = {
+    "anthropic": "claude-sonnet-4-20250514",
+    "openai": "gpt-4o",
+    "ollama": "llama3.1",
+}
 
 
 async def summarize_article(
@@ -38,11 +59,15 @@ async def summarize_article(
     provider: str,
     prompt: str,
     db_path: str | None = None,
+    model: str | None = None,
+    temperature: float | None = None,
+    max_tokens: int | None = None,
 ) -> dict:
     """Summarize an article using AI.
     
     This function reads the article from the database, summarizes it,
-    and returns the summary.
+    and returns the summary. ``temperature`` and ``max_tokens`` override the
+    summarizer defaults when given.
     """
     # Import sqlite3 to read from the database
     import sqlite3
@@ -76,8 +101,11 @@ async def summarize_article(
     )
     
     # Create the Genius based on provider
+    if provider not in DEFAULT_MODELS:
+        raise ValueError(f"Unknown provider: {provider}")
+
     genius_config = GeniusConfig(
-        model="claude-sonnet-4-20250514" if provider == "anthropic" else "gpt-4o",
+        model=model or DEFAULT_MODELS[provider],
         system_prompt=prompt,
         temperature=0.5,
         max_tokens=2048,
@@ -87,20 +115,33 @@ async def summarize_article(
         genius = AnthropicGenius(genius_config)
     elif provider == "openai":
         genius = OpenAIGenius(genius_config)
+    elif provider == "ollama":
+        genius = OllamaGenius(genius_config)
     else:
         raise ValueError(f"Unknown provider: {provider}")
     
     # Summarize
     summarizer = ArticleSummarizer(genius)
-    summary_config = SummaryConfig(system_prompt=prompt)
+    overrides = {}
+    if temperature is not None:
+        overrides["temperature"] = temperature
+    if max_tokens is not None:
+        overrides["max_tokens"] = max_tokens
+    summary_config = SummaryConfig(system_prompt=prompt, **overrides)
     
     summary = await summarizer.summarize(article, summary_config)
     
-    return {
+    result = {
         "summary": summary.content,
         "model": summary.model,
         "tokens_used": summary.tokens_used,
     }
+    if summary.input_tokens is not None and summary.output_tokens is not None:
+        result["usage"] = {
+            "input_tokens": summary.input_tokens,
+            "output_tokens": summary.output_tokens,
+        }
+    return result
 
 
 def main():
@@ -111,6 +152,7 @@ def main():
     # Extract command
     extract_parser = subparsers.add_parser("extract", help="Extract article content")
     extract_parser.add_argument("url", help="URL to extract")
+    extract_parser.add_argument("--file", help="Read HTML from this file instead of fetching")
     
     # Summarize command
     summarize_parser = subparsers.add_parser("summarize", help="Summarize an article")
@@ -118,12 +160,24 @@ def main():
     summarize_parser.add_argument("--provider", default="anthropic", help="AI provider")
     summarize_parser.add_argument("--prompt", required=True, help="System prompt")
     summarize_parser.add_argument("--db", help="Database path")
+    summarize_parser.add_argument("--model", help="Model name (defaults per provider)")
+    summarize_parser.add_argument("--temperature", type=float, help="Sampling temperature")
+    summarize_parser.add_argument("--max-tokens", type=int, help="Response length limit")
     
     args = parser.parse_args()
     
     try:
         if args.command == "extract":
-            result = asyncio.run(extract_article(args.url))
+            try:
+                if args.file:
+                    result = asyncio.run(extract_file(args.file, args.url))
+                else:
+                    result = asyncio.run(extract_article(args.url))
+            except ExtractionError as e:
+                # Structured failure on stdout so the Rust CLI can give advice
+                print(json.dumps(e.to_dict()))
+                print(f"Error: {e}", file=sys.stderr)
+                sys.exit(1)
             print(json.dumps(result))
         elif args.command == "summarize":
             result = asyncio.run(summarize_article(
@@ -131,6 +185,9 @@ def main():
                 args.provider,
                 args.prompt,
                 args.db,
+                args.model,
+                args.temperature,
+                args.max_tokens,
             ))
             print(json.dumps(result))
     except Exception as e:
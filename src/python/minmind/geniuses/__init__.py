@@ -2,6 +2,7 @@
 
 from .base import Genius, GeniusConfig, Message, Response
 from .anthropic import AnthropicGenius
+from .ollama import OllamaGenius
 from .openai import OpenAIGenius
 
 __all__ = [
@@ -10,5 +11,6 @@ __all__ = [
     "Message",
     "Response",
     "AnthropicGenius",
+    "OllamaGenius",
     "OpenAIGenius",
 ]
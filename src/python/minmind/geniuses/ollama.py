@@ -0,0 +1,94 @@
+"""Ollama (local model) implementation of Genius."""
+
+import json
+import os
+from typing import AsyncIterator
+
+import httpx
+
+from .base import Genius, GeniusConfig, Message, Response
+
+DEFAULT_OLLAMA_URL = "http://localhost:11434/api/generate"
+
+
+class OllamaUnavailableError(RuntimeError):
+    """Raised when the Ollama server can't be reached."""
+
+    def __init__(self, endpoint: str):
+        super().__init__(
+            f"Could not reach Ollama at {endpoint}. "
+            "Start it with `ollama serve` (or set MINMIND_OLLAMA_URL to its address)."
+        )
+
+
+class OllamaGenius(Genius):
+    """Genius implementation using a local Ollama server.
+
+    Content never leaves the machine. The endpoint defaults to
+    `http://localhost:11434/api/generate` and can be overridden with the
+    `MINMIND_OLLAMA_URL` environment variable.
+    """
+
+    def __init__(self, config: GeniusConfig, endpoint: str | None = None):
+        super().__init__(config)
+        self.endpoint = endpoint or os.environ.get("MINMIND_OLLAMA_URL", DEFAULT_OLLAMA_URL)
+
+    async def chat(self, messages: list[Message]) -> Response:
+        """Send messages and collect the full response from Ollama."""
+        parts: list[str] = []
+        usage = None
+        model = self.config.model
+
+        async for chunk in self._generate(messages):
+            parts.append(chunk.get("response", ""))
+            if chunk.get("done"):
+                model = chunk.get("model", model)
+                usage = {
+                    "input_tokens": chunk.get("prompt_eval_count", 0),
+                    "output_tokens": chunk.get("eval_count", 0),
+                }
+
+        return Response(content="".join(parts), model=model, usage=usage)
+
+    async def stream(self, messages: list[Message]) -> AsyncIterator[str]:
+        """Stream a response from Ollama token by token."""
+        async for chunk in self._generate(messages):
+            if chunk.get("response"):
+                yield chunk["response"]
+
+    async def _generate(self, messages: list[Message]) -> AsyncIterator[dict]:
+        """POST to the generate endpoint and yield each streamed JSON chunk."""
+        payload = {
+            "model": self.config.model,
+            "prompt": self._build_prompt(messages),
+            "stream": True,
+            "options": {
+                "temperature": self.config.temperature,
+                "num_predict": self.config.max_tokens,
+            },
+        }
+        if self.config.system_prompt:
+            payload["system"] = self.config.system_prompt
+
+        try:
+            async with httpx.AsyncClient(timeout=None) as client:
+                async with client.stream("POST", self.endpoint, json=payload) as response:
+                    if response.status_code >= 400:
+                        body = (await response.aread()).decode(errors="replace")
+                        raise RuntimeError(f"Ollama returned {response.status_code}: {body}")
+                    async for line in response.aiter_lines():
+                        if not line.strip():
+                            continue
+                        chunk = json.loads(line)
+                        if "error" in chunk:
+                            raise RuntimeError(f"Ollama error: {chunk['error']}")
+                        yield chunk
+        except httpx.ConnectError as e:
+            raise OllamaUnavailableError(self.endpoint) from e
+
+    @staticmethod
+    def _build_prompt(messages: list[Message]) -> str:
+        """Flatten a conversation into a single generate prompt."""
+        if len(messages) == 1:
+            return messages[0].content
+        return "\n\n".join(f"{m.role.capitalize()}: {m.content}" for m in messages)